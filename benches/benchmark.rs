@@ -244,6 +244,76 @@ fn benchmark_beam_search(c: &mut Criterion) {
     group.finish();
 }
 
+fn benchmark_early_exit(c: &mut Criterion) {
+    let mut group = c.benchmark_group("early_exit");
+
+    // A gating check: the first banned term sits near the start of a long message.
+    let automaton = FuzzyAhoCorasickBuilder::new()
+        .fuzzy(FuzzyLimits::new().edits(1))
+        .case_insensitive(true)
+        .build(["tincidunt", "porta", "vestibulum", "accumsan"]);
+    let text = "Lorem ipsum dolor sit amet, consectetur adipiscing elit. Vestibulum eros ipsum, tincidutn eu metus ut, commodo accumsan mi. ".repeat(20);
+    let opts = SearchOptions::new().threshold(0.8);
+
+    group.bench_function("search", |b| {
+        b.iter(|| {
+            let _ = automaton.search(black_box(&text), &opts);
+        });
+    });
+
+    group.bench_function("is_match", |b| {
+        b.iter(|| {
+            let _ = automaton.is_match(black_box(&text), &opts);
+        });
+    });
+
+    group.bench_function("find_first", |b| {
+        b.iter(|| {
+            let _ = automaton.find_first(black_box(&text), &opts);
+        });
+    });
+
+    group.finish();
+}
+
+fn benchmark_searcher(c: &mut Criterion) {
+    let mut group = c.benchmark_group("short_records");
+
+    // Many short searches in a loop, where per-call buffer allocation is a visible share.
+    let automaton = FuzzyAhoCorasickBuilder::new()
+        .fuzzy(FuzzyLimits::new().edits(1))
+        .case_insensitive(true)
+        .build(["john smith", "jane doe", "müller", "garcía"]);
+    let records = [
+        "Jon Smith",
+        "JANE DOE",
+        "Hans Mueller",
+        "Ana Garcia",
+        "Jöhn Smíth",
+        "unrelated record",
+    ];
+    let opts = SearchOptions::new().threshold(0.8);
+
+    group.bench_function("search", |b| {
+        b.iter(|| {
+            for record in &records {
+                let _ = automaton.search(black_box(record), &opts);
+            }
+        });
+    });
+
+    let mut searcher = automaton.searcher();
+    group.bench_function("searcher", |b| {
+        b.iter(|| {
+            for record in &records {
+                let _ = searcher.search(black_box(record), &opts);
+            }
+        });
+    });
+
+    group.finish();
+}
+
 criterion_group!(
     benches,
     benchmark_search,
@@ -252,6 +322,8 @@ criterion_group!(
     benchmark_fuzzy_levels,
     benchmark_build,
     benchmark_replace,
-    benchmark_beam_search
+    benchmark_beam_search,
+    benchmark_early_exit,
+    benchmark_searcher
 );
 criterion_main!(benches);
@@ -0,0 +1,208 @@
+//! Edit scripts for matches found with [`SearchOptions::alignment`](crate::SearchOptions::alignment):
+//! which pattern graphemes lined up with which haystack graphemes, and through which edit.
+//!
+//! The search records each state it enqueues as a [`TraceStep`] pointing at the state it expanded
+//! from, so the path that produced an accepted match can be walked back to its start window. Steps
+//! store automaton nodes rather than pattern offsets: every step moves down the matched pattern's
+//! own trie path, so a node's position on that path is the number of pattern graphemes consumed.
+
+use crate::Pattern;
+use crate::grapheme::GraphemeStorage;
+use crate::trie::{TrieNode, TrieView};
+use std::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// One step of a match's alignment against its pattern (see [`FuzzyMatch::alignment`]), with the
+/// byte ranges it covers in the pattern text and in the haystack.
+///
+/// [`FuzzyMatch::alignment`]: crate::FuzzyMatch::alignment
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EditOp {
+    /// The graphemes are the same (after case folding, on a case-insensitive engine).
+    Match {
+        /// Byte range in the pattern text.
+        pattern: Range<usize>,
+        /// Byte range in the haystack.
+        text: Range<usize>,
+    },
+    /// A pattern grapheme stood in for by a different haystack grapheme — or, through a
+    /// multi-character mapping, several for several.
+    Sub {
+        /// Byte range in the pattern text.
+        pattern: Range<usize>,
+        /// Byte range in the haystack.
+        text: Range<usize>,
+    },
+    /// A haystack grapheme with no counterpart in the pattern.
+    Ins {
+        /// Empty range at the point of insertion in the pattern text.
+        pattern: Range<usize>,
+        /// Byte range in the haystack.
+        text: Range<usize>,
+    },
+    /// A pattern grapheme with no counterpart in the haystack.
+    Del {
+        /// Byte range in the pattern text.
+        pattern: Range<usize>,
+        /// Empty range at the point of deletion in the haystack.
+        text: Range<usize>,
+    },
+    /// Two neighbouring graphemes, in swapped order.
+    Swap {
+        /// Byte range of both graphemes in the pattern text.
+        pattern: Range<usize>,
+        /// Byte range of both graphemes in the haystack.
+        text: Range<usize>,
+    },
+}
+
+impl EditOp {
+    /// Byte range of the step in the pattern text (empty for an [`Ins`](EditOp::Ins)).
+    #[must_use]
+    pub fn pattern(&self) -> Range<usize> {
+        match self {
+            EditOp::Match { pattern, .. }
+            | EditOp::Sub { pattern, .. }
+            | EditOp::Ins { pattern, .. }
+            | EditOp::Del { pattern, .. }
+            | EditOp::Swap { pattern, .. } => pattern.clone(),
+        }
+    }
+
+    /// Byte range of the step in the haystack (empty for a [`Del`](EditOp::Del)).
+    #[must_use]
+    pub fn text(&self) -> Range<usize> {
+        match self {
+            EditOp::Match { text, .. }
+            | EditOp::Sub { text, .. }
+            | EditOp::Ins { text, .. }
+            | EditOp::Del { text, .. }
+            | EditOp::Swap { text, .. } => text.clone(),
+        }
+    }
+
+    /// Move the haystack range `by` bytes right, for a match found in a slice of the haystack.
+    pub(crate) fn shift_text(&mut self, by: usize) {
+        match self {
+            EditOp::Match { text, .. }
+            | EditOp::Sub { text, .. }
+            | EditOp::Ins { text, .. }
+            | EditOp::Del { text, .. }
+            | EditOp::Swap { text, .. } => *text = text.start + by..text.end + by,
+        }
+    }
+}
+
+/// The transition that produced a traced state.
+#[derive(Clone, Copy)]
+pub(crate) enum Step {
+    Match,
+    /// A substitution or a multi-character mapping.
+    Sub,
+    Ins,
+    Del,
+    Swap,
+}
+
+/// One enqueued state of a traced search: the state it was expanded from (an index into the
+/// window's arena) and the transition taken — both `None` for the window's root state — and where
+/// it left the automaton and the haystack.
+#[derive(Clone, Copy)]
+pub(crate) struct TraceStep {
+    pub(crate) parent: u32,
+    pub(crate) step: Option<Step>,
+    pub(crate) node: u32,
+    /// Haystack grapheme position after the step.
+    pub(crate) j: u32,
+}
+
+/// Append a step to `arena`, returning its index for the new state's `trace`.
+#[inline]
+pub(crate) fn record(
+    arena: &mut Vec<TraceStep>,
+    parent: u32,
+    step: Option<Step>,
+    node: u32,
+    j: u32,
+) -> u32 {
+    arena.push(TraceStep {
+        parent,
+        step,
+        node,
+        j,
+    });
+    // One step per enqueued state: bounded like the state count itself.
+    (arena.len() - 1) as u32
+}
+
+/// Walk the path ending at `leaf` back to its start window and express it as [`EditOp`]s against
+/// `pattern`, whose folded graphemes (as the builder inserted them) lead from the root to the
+/// matched output node.
+pub(crate) fn reconstruct<T: TrieView, G: GraphemeStorage>(
+    trie: T,
+    arena: &[TraceStep],
+    leaf: u32,
+    pattern: &Pattern,
+    case_insensitive: bool,
+    graphemes: &G,
+    haystack_len: usize,
+) -> Vec<EditOp> {
+    let mut path = vec![arena[leaf as usize]];
+    while let Some(step) = path.last().filter(|step| step.step.is_some()) {
+        path.push(arena[step.parent as usize]);
+    }
+    path.reverse();
+
+    // The pattern's trie path, node by node, and its grapheme byte offsets.
+    let pattern_offsets: Vec<usize> = pattern
+        .pattern
+        .grapheme_indices(true)
+        .map(|(offset, _)| offset)
+        .chain([pattern.pattern.len()])
+        .collect();
+    let mut pattern_nodes = vec![0];
+    let mut node = 0;
+    for grapheme in pattern.pattern.graphemes(true) {
+        let next = if case_insensitive {
+            trie.node(node).find_transition(&grapheme.to_lowercase())
+        } else {
+            trie.node(node).find_transition(grapheme)
+        };
+        let Some(next) = next else { break };
+        node = next;
+        pattern_nodes.push(node);
+    }
+    let pattern_pos = |node: u32, fallback: usize| {
+        pattern_nodes
+            .iter()
+            .position(|&n| n == node)
+            .unwrap_or(fallback)
+    };
+    let text_byte = |j: u32| {
+        let j = j as usize;
+        if j < graphemes.gs_len() {
+            graphemes.gs_byte_offset(j)
+        } else {
+            haystack_len
+        }
+    };
+
+    let mut ops = Vec::with_capacity(path.len() - 1);
+    let (mut p, mut j) = (0, path[0].j);
+    for step in &path[1..] {
+        let Some(kind) = step.step else { continue };
+        let next_p = pattern_pos(step.node, p);
+        let pattern = pattern_offsets[p]..pattern_offsets[next_p];
+        let text = text_byte(j)..text_byte(step.j);
+        ops.push(match kind {
+            Step::Match => EditOp::Match { pattern, text },
+            Step::Sub => EditOp::Sub { pattern, text },
+            Step::Ins => EditOp::Ins { pattern, text },
+            Step::Del => EditOp::Del { pattern, text },
+            Step::Swap => EditOp::Swap { pattern, text },
+        });
+        (p, j) = (next_p, step.j);
+    }
+    ops
+}
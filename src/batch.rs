@@ -0,0 +1,99 @@
+//! Batch search: many haystacks against one engine, either on the calling thread with one set of
+//! reused buffers or spread over worker threads, with the results always in input order.
+
+use crate::{FuzzyAhoCorasick, FuzzyMatches, SearchError, SearchOptions};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Most haystacks a worker of [`search_batch_parallel`](FuzzyAhoCorasick::search_batch_parallel)
+/// claims at once. Claiming in blocks keeps the shared counter off the per-haystack path; capping the
+/// block keeps the work balanced when haystack lengths vary.
+const MAX_BLOCK: usize = 256;
+
+impl FuzzyAhoCorasick {
+    /// [`search`](Self::search) each of `haystacks` with the same `opts`, returning one result per
+    /// haystack, in input order. The search buffers are reused from one haystack to the next (see
+    /// [`Searcher`](crate::Searcher)), so a batch of short records costs little more than the
+    /// searches themselves.
+    ///
+    /// A haystack too large to index gets its [`SearchError`] in its own slot; the others are still
+    /// searched.
+    ///
+    /// ```
+    /// use fuzzy_aho_corasick::{FuzzyAhoCorasickBuilder, FuzzyLimits, SearchOptions};
+    /// let engine = FuzzyAhoCorasickBuilder::new()
+    ///     .fuzzy(FuzzyLimits::new().edits(1))
+    ///     .build(["smith", "jones"]);
+    /// let records = ["john smyth", "mary jnoes", "nobody"];
+    /// let results = engine.search_batch(&records, &SearchOptions::new().threshold(0.7));
+    /// let counts: Vec<usize> = results.iter().map(|r| r.as_ref().unwrap().len()).collect();
+    /// assert_eq!(counts, [1, 1, 0]);
+    /// ```
+    pub fn search_batch<'a, H: AsRef<str>>(
+        &'a self,
+        haystacks: &'a [H],
+        opts: &SearchOptions,
+    ) -> Vec<Result<FuzzyMatches<'a>, SearchError>> {
+        let mut searcher = self.searcher();
+        haystacks
+            .iter()
+            .map(|haystack| searcher.search(haystack.as_ref(), opts))
+            .collect()
+    }
+
+    /// Parallel [`search_batch`](Self::search_batch): `threads` workers, each with its own reused
+    /// buffers, claim blocks of haystacks from the batch and search them, sharing this immutable
+    /// engine. The results are identical to `search_batch`'s and in input order. Uses `std::thread`
+    /// only — no runtime dependencies.
+    ///
+    /// `threads` is clamped to at least 1; pass [`std::thread::available_parallelism`] for "all
+    /// cores". A batch too small to split is searched on the calling thread.
+    ///
+    /// # Panics
+    /// Propagates a panic from a worker thread, re-raised on the calling thread.
+    pub fn search_batch_parallel<'a, H: AsRef<str> + Sync>(
+        &'a self,
+        haystacks: &'a [H],
+        opts: &SearchOptions,
+        threads: usize,
+    ) -> Vec<Result<FuzzyMatches<'a>, SearchError>> {
+        let threads = threads.max(1);
+        // Several blocks per worker, so one that draws long haystacks doesn't hold up the rest.
+        let block = haystacks.len().div_ceil(threads * 8).clamp(1, MAX_BLOCK);
+        let blocks = haystacks.len().div_ceil(block);
+        if threads == 1 || blocks <= 1 {
+            return self.search_batch(haystacks, opts);
+        }
+
+        let next = AtomicUsize::new(0);
+        let mut done: Vec<(usize, Vec<Result<FuzzyMatches<'a>, SearchError>>)> =
+            std::thread::scope(|scope| {
+                let workers: Vec<_> = (0..threads.min(blocks))
+                    .map(|_| {
+                        scope.spawn(|| {
+                            let mut searcher = self.searcher();
+                            let mut out = Vec::new();
+                            loop {
+                                let first = next.fetch_add(block, Ordering::Relaxed);
+                                if first >= haystacks.len() {
+                                    break;
+                                }
+                                let last = (first + block).min(haystacks.len());
+                                let results = haystacks[first..last]
+                                    .iter()
+                                    .map(|haystack| searcher.search(haystack.as_ref(), opts))
+                                    .collect();
+                                out.push((first, results));
+                            }
+                            out
+                        })
+                    })
+                    .collect();
+                workers
+                    .into_iter()
+                    .flat_map(|worker| worker.join().expect("batch search worker panicked"))
+                    .collect()
+            });
+        done.sort_unstable_by_key(|&(first, _)| first);
+        done.into_iter().flat_map(|(_, results)| results).collect()
+    }
+}
@@ -0,0 +1,171 @@
+//! Options and helpers shared by the command-line tools.
+
+// Each tool uses only some of the helpers.
+#![allow(dead_code)]
+
+use clap::Args;
+use fuzzy_aho_corasick::{
+    DEFAULT_THRESHOLD, FuzzyAhoCorasickBuilder, FuzzyLimits, FuzzyPenalties, NumEdits,
+};
+use std::fmt::Write as _;
+
+/// Engine configuration: edit limits, case folding, penalties, mappings, and the threshold.
+#[derive(Args, Debug)]
+pub struct EngineArgs {
+    /// Total edits a match may use, of any mix of types [default: 1, unless a per-type limit is
+    /// given]
+    #[arg(short = 'k', long, value_name = "N")]
+    pub edits: Option<NumEdits>,
+    /// Inserted graphemes a match may use
+    #[arg(long, value_name = "N")]
+    pub insertions: Option<NumEdits>,
+    /// Deleted graphemes a match may use
+    #[arg(long, value_name = "N")]
+    pub deletions: Option<NumEdits>,
+    /// Substituted graphemes a match may use
+    #[arg(long, value_name = "N")]
+    pub substitutions: Option<NumEdits>,
+    /// Transpositions a match may use
+    #[arg(long, value_name = "N")]
+    pub swaps: Option<NumEdits>,
+    /// Match case-insensitively
+    #[arg(short = 'i', long)]
+    pub ignore_case: bool,
+    /// Penalty for an inserted grapheme
+    #[arg(long, value_name = "PENALTY")]
+    pub insertion_penalty: Option<f32>,
+    /// Penalty for a deleted grapheme
+    #[arg(long, value_name = "PENALTY")]
+    pub deletion_penalty: Option<f32>,
+    /// Penalty for a substituted grapheme
+    #[arg(long, value_name = "PENALTY")]
+    pub substitution_penalty: Option<f32>,
+    /// Penalty for a transposition
+    #[arg(long, value_name = "PENALTY")]
+    pub swap_penalty: Option<f32>,
+    /// Treat two sequences as equivalent, optionally with a similarity score below 1 (repeatable)
+    #[arg(long = "map", value_name = "A=B[:SCORE]", value_parser = parse_mapping)]
+    pub mappings: Vec<(String, String, f32)>,
+    /// Minimum similarity a match must reach
+    #[arg(short = 't', long, default_value_t = DEFAULT_THRESHOLD)]
+    pub threshold: f32,
+}
+
+impl EngineArgs {
+    /// A builder configured from the options, ready for the patterns.
+    pub fn builder(&self) -> FuzzyAhoCorasickBuilder {
+        let mut limits = FuzzyLimits::new();
+        if let Some(num) = self.insertions {
+            limits = limits.insertions(num);
+        }
+        if let Some(num) = self.deletions {
+            limits = limits.deletions(num);
+        }
+        if let Some(num) = self.substitutions {
+            limits = limits.substitutions(num);
+        }
+        if let Some(num) = self.swaps {
+            limits = limits.swaps(num);
+        }
+        let per_type = [
+            self.insertions,
+            self.deletions,
+            self.substitutions,
+            self.swaps,
+        ];
+        match self.edits {
+            Some(edits) => limits = limits.edits(edits),
+            None if per_type.iter().all(Option::is_none) => limits = limits.edits(1),
+            None => {}
+        }
+
+        let mut penalties = FuzzyPenalties::default();
+        if let Some(penalty) = self.insertion_penalty {
+            penalties = penalties.insertion(penalty);
+        }
+        if let Some(penalty) = self.deletion_penalty {
+            penalties = penalties.deletion(penalty);
+        }
+        if let Some(penalty) = self.substitution_penalty {
+            penalties = penalties.substitution(penalty);
+        }
+        if let Some(penalty) = self.swap_penalty {
+            penalties = penalties.swap(penalty);
+        }
+
+        let mut builder = FuzzyAhoCorasickBuilder::new()
+            .fuzzy(limits)
+            .penalties(penalties)
+            .case_insensitive(self.ignore_case);
+        for (a, b, score) in &self.mappings {
+            builder = builder.mapping_scored(a.as_str(), b.as_str(), *score);
+        }
+        builder
+    }
+}
+
+/// Parse a `--map` value: `A=B`, or `A=B:SCORE` when the text after the last `:` is a number.
+fn parse_mapping(value: &str) -> Result<(String, String, f32), String> {
+    let (a, rest) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected A=B[:SCORE], got {value:?}"))?;
+    let (b, score) = match rest.rsplit_once(':') {
+        Some((b, score)) => match score.parse::<f32>() {
+            Ok(score) if (0.0..=1.0).contains(&score) => (b, score),
+            Ok(_) => return Err(format!("score must be within 0..=1, got {score}")),
+            Err(_) => (rest, 1.0),
+        },
+        None => (rest, 1.0),
+    };
+    if a.is_empty() || b.is_empty() {
+        return Err(format!(
+            "both sides of a mapping must be non-empty, got {value:?}"
+        ));
+    }
+    Ok((a.to_owned(), b.to_owned(), score))
+}
+
+/// Append `s` to `out` as a JSON string literal.
+pub fn push_json_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c < '\u{20}' => {
+                let _ = write!(out, "\\u{:04x}", u32::from(c));
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_mapping, push_json_string};
+
+    #[test]
+    fn test_parse_mapping() {
+        assert_eq!(parse_mapping("ae=æ"), Ok(("ae".into(), "æ".into(), 1.0)));
+        assert_eq!(
+            parse_mapping("rn=m:0.8"),
+            Ok(("rn".into(), "m".into(), 0.8))
+        );
+        // A trailing `:` part that isn't a number belongs to the sequence.
+        assert_eq!(parse_mapping("a=b:c"), Ok(("a".into(), "b:c".into(), 1.0)));
+        assert!(parse_mapping("ab").is_err());
+        assert!(parse_mapping("=b").is_err());
+        assert!(parse_mapping("a=b:1.5").is_err());
+    }
+
+    #[test]
+    fn test_push_json_string() {
+        let mut out = String::new();
+        push_json_string(&mut out, "a \"q\" \\ \n\u{1}é");
+        assert_eq!(out, r#""a \"q\" \\ \n\u0001é""#);
+    }
+}
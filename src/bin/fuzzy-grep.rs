@@ -0,0 +1,378 @@
+#![warn(clippy::pedantic)]
+
+//! `fuzzy-grep`: print the fuzzy matches of a set of patterns in files or standard input.
+//!
+//! Files are searched with [`FuzzyAhoCorasick::search_stream_parallel`] across all cores, so only
+//! the matches and the line offsets of a file are kept in memory, never its text. Standard input
+//! (and every input with `-j 1`) is searched with [`FuzzyAhoCorasick::search_stream`] instead, and
+//! its matches are printed as each window is searched, so a pipe that stays open (`tail -f`) gets
+//! its output as it goes. Each match is printed as `file:line:column:text`, followed by the pattern
+//! and the similarity (tab-separated), or as one JSON object per line with `--json`.
+//! Exits with 0 when something matched, 1 when nothing did, and 2 on an error.
+
+mod common;
+
+use clap::Parser;
+use common::{EngineArgs, push_json_string};
+use fuzzy_aho_corasick::{FuzzyAhoCorasick, Pattern, StreamMatch};
+use std::collections::VecDeque;
+use std::ffi::OsString;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::{Arc, Mutex};
+
+/// Search files for fuzzy matches of one or more patterns.
+#[derive(Parser, Debug)]
+#[command(name = "fuzzy-grep", version)]
+struct Cli {
+    /// A pattern to search for (repeatable); the first positional argument is then a file
+    #[arg(short = 'e', long = "pattern", value_name = "PATTERN")]
+    patterns: Vec<String>,
+    /// Read patterns from a pattern list file: one per line, with optional tab-separated options
+    /// (repeatable)
+    #[arg(short = 'f', long = "patterns-file", value_name = "FILE")]
+    pattern_files: Vec<PathBuf>,
+    /// Print each match as a JSON object on its own line
+    #[arg(long)]
+    json: bool,
+    /// Worker threads per input [default: all cores]
+    #[arg(short = 'j', long, value_name = "N")]
+    threads: Option<usize>,
+    #[command(flatten)]
+    engine: EngineArgs,
+    /// The pattern (unless given with -e or -f), then the files to search; none or `-` reads
+    /// standard input
+    #[arg(value_name = "PATTERN | FILE")]
+    args: Vec<OsString>,
+}
+
+/// The line starts of an input read so far, from the line of the earliest match still to be
+/// printed on: what turns the stream's byte offsets into line and column numbers.
+struct Lines {
+    /// Bytes read.
+    read: u64,
+    /// The 1-based number of the line starting at `line_start`.
+    line: usize,
+    line_start: u64,
+    /// Byte offset of every later line start.
+    starts: VecDeque<u64>,
+    /// Set when the output failed, to stop reading the input.
+    stopped: bool,
+}
+
+impl Default for Lines {
+    fn default() -> Self {
+        Self {
+            read: 0,
+            line: 1,
+            line_start: 0,
+            starts: VecDeque::new(),
+            stopped: false,
+        }
+    }
+}
+
+impl Lines {
+    /// The 1-based line and byte column of `offset`, which must not precede `line_start`.
+    fn position(&self, offset: u64) -> (usize, u64) {
+        let later = self.starts.partition_point(|&start| start <= offset);
+        let line_start = later
+            .checked_sub(1)
+            .map_or(self.line_start, |i| self.starts[i]);
+        (self.line + later, offset - line_start + 1)
+    }
+
+    /// Forget the line starts before the line of `offset`, once no earlier match is left to print.
+    fn forget_before(&mut self, offset: u64) {
+        while let Some(start) = self.starts.pop_front_if(|start| *start <= offset) {
+            self.line += 1;
+            self.line_start = start;
+        }
+    }
+}
+
+/// A reader that records where each line starts into the [`Lines`] it shares with the printing
+/// side.
+struct LineIndex<R> {
+    reader: R,
+    lines: Arc<Mutex<Lines>>,
+}
+
+impl<R: Read> Read for LineIndex<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.lines.lock().unwrap().stopped {
+            return Err(io::Error::other("output failed"));
+        }
+        let n = self.reader.read(buf)?;
+        let mut lines = self.lines.lock().unwrap();
+        let read = lines.read;
+        for (i, _) in buf[..n].iter().enumerate().filter(|&(_, &b)| b == b'\n') {
+            lines.starts.push_back(read + i as u64 + 1);
+        }
+        lines.read += n as u64;
+        Ok(n)
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(&cli) {
+        Ok(status) => status,
+        // The reader went away (`fuzzy-grep … | head`): not an error.
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("fuzzy-grep: {e}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn run(cli: &Cli) -> io::Result<ExitCode> {
+    let mut patterns: Vec<Pattern> = cli.patterns.iter().map(Pattern::from).collect();
+    for path in &cli.pattern_files {
+        let list = std::fs::read_to_string(path)
+            .and_then(|text| {
+                Pattern::parse_list(&text)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+            })
+            .map_err(|e| io::Error::new(e.kind(), format!("{}: {e}", path.display())))?;
+        patterns.extend(list);
+    }
+    let mut inputs = cli.args.iter();
+    if cli.patterns.is_empty() && cli.pattern_files.is_empty() {
+        let Some(pattern) = inputs.next() else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "no pattern given (pass one, or use -e/-f)",
+            ));
+        };
+        let pattern = pattern.to_str().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the pattern is not valid UTF-8",
+            )
+        })?;
+        patterns.push(Pattern::from(pattern));
+    }
+    let mut inputs: Vec<&OsString> = inputs.collect();
+    let stdin = OsString::from("-");
+    if inputs.is_empty() {
+        inputs.push(&stdin);
+    }
+
+    let engine = cli.engine.builder().build(patterns);
+    let threads = cli.threads.unwrap_or_else(|| {
+        std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get)
+    });
+    let mut out = BufWriter::new(io::stdout().lock());
+    let (mut any_match, mut failed) = (false, false);
+    for input in inputs {
+        let (name, result) = if input == "-" {
+            let name = "<stdin>".to_owned();
+            let result = search_as_read(&engine, cli, &name, io::stdin(), &mut out);
+            (name, result)
+        } else {
+            let name = PathBuf::from(input).display().to_string();
+            let result = File::open(input).and_then(|file| {
+                if threads == 1 {
+                    search_as_read(&engine, cli, &name, file, &mut out)
+                } else {
+                    search_parallel(&engine, cli, threads, &name, file, &mut out)
+                }
+            });
+            (name, result)
+        };
+        match result {
+            Ok(found) => any_match |= found,
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => return Err(e),
+            Err(e) => {
+                out.flush()?;
+                eprintln!("fuzzy-grep: {name}: {e}");
+                failed = true;
+            }
+        }
+    }
+    out.flush()?;
+    Ok(if failed {
+        ExitCode::from(2)
+    } else if any_match {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+/// Search `reader` across `threads` workers and print its matches once it is read through.
+/// Returns whether anything matched.
+fn search_parallel<R: Read + Send>(
+    engine: &FuzzyAhoCorasick,
+    cli: &Cli,
+    threads: usize,
+    name: &str,
+    reader: R,
+    out: &mut impl Write,
+) -> io::Result<bool> {
+    let lines = Arc::new(Mutex::new(Lines::default()));
+    let index = LineIndex {
+        reader,
+        lines: Arc::clone(&lines),
+    };
+    let mut matches = Vec::new();
+    engine.search_stream_parallel(index, cli.engine.threshold, threads, |m| {
+        matches.push(m);
+    })?;
+    let found = !matches.is_empty();
+    let mut lines = lines.lock().unwrap();
+    print_matches(engine, cli.json, name, &mut lines, &mut matches, out)?;
+    Ok(found)
+}
+
+/// Search `reader` on this thread, printing the matches of each window as soon as it is searched,
+/// so only one window's matches and the line starts since the last printed one are held. Returns
+/// whether anything matched.
+fn search_as_read<R: Read>(
+    engine: &FuzzyAhoCorasick,
+    cli: &Cli,
+    name: &str,
+    reader: R,
+    out: &mut impl Write,
+) -> io::Result<bool> {
+    let lines = Arc::new(Mutex::new(Lines::default()));
+    let index = LineIndex {
+        reader,
+        lines: Arc::clone(&lines),
+    };
+    let (mut found, mut printed) = (false, Ok(()));
+    let mut window = Vec::new();
+    // `search_stream` reads a window, then reports all of its matches: a match arriving after more
+    // input was read belongs to a later window, which starts after everything reported so far.
+    let mut window_read = 0;
+    let searched = engine.search_stream(index, cli.engine.threshold, |m| {
+        found = true;
+        let mut lines = lines.lock().unwrap();
+        if lines.read != window_read && printed.is_ok() {
+            printed = print_matches(engine, cli.json, name, &mut lines, &mut window, out);
+            lines.stopped = printed.is_err();
+        }
+        window_read = lines.read;
+        window.push(m);
+    });
+    printed?;
+    searched?;
+    let mut lines = lines.lock().unwrap();
+    print_matches(engine, cli.json, name, &mut lines, &mut window, out)?;
+    Ok(found)
+}
+
+/// Print `matches` in input order and flush, emptying it. Every match still to come must start at
+/// or after the last of them, whose line is all `lines` keeps afterwards.
+fn print_matches(
+    engine: &FuzzyAhoCorasick,
+    json: bool,
+    name: &str,
+    lines: &mut Lines,
+    matches: &mut Vec<StreamMatch>,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    matches.sort_unstable_by_key(|m| (m.start, m.pattern_index));
+    for m in matches.drain(..) {
+        lines.forget_before(m.start);
+        out.write_all(format_match(engine, json, name, lines, &m).as_bytes())?;
+    }
+    out.flush()
+}
+
+/// One output line for `m`, newline included.
+fn format_match(
+    engine: &FuzzyAhoCorasick,
+    json: bool,
+    name: &str,
+    lines: &Lines,
+    m: &StreamMatch,
+) -> String {
+    let (line, column) = lines.position(m.start);
+    let pattern = &engine.patterns()[m.pattern_index];
+    let mut out = String::new();
+    if json {
+        out.push_str("{\"file\":");
+        push_json_string(&mut out, name);
+        let _ = write!(
+            out,
+            ",\"line\":{line},\"column\":{column},\"start\":{},\"end\":{},\"text\":",
+            m.start, m.end
+        );
+        push_json_string(&mut out, &m.text);
+        out.push_str(",\"pattern\":");
+        push_json_string(&mut out, pattern.as_str());
+        let _ = write!(
+            out,
+            ",\"pattern_index\":{},\"similarity\":{},\"edits\":{}",
+            m.pattern_index, m.similarity, m.edits
+        );
+        if !pattern.metadata.is_empty() {
+            out.push_str(",\"metadata\":{");
+            for (i, (key, value)) in pattern.metadata.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                push_json_string(&mut out, key);
+                out.push(':');
+                push_json_string(&mut out, value);
+            }
+            out.push('}');
+        }
+        out.push_str("}\n");
+    } else {
+        // Keep one match per line, even when it spans a line break.
+        let text = m.text.replace('\n', "\\n").replace('\r', "\\r");
+        let _ = writeln!(
+            out,
+            "{name}:{line}:{column}:{text}\t{pattern}\t{:.3}",
+            m.similarity
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LineIndex, Lines};
+    use std::io::Read;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_line_index_position() {
+        let text = b"ab\ncd\n\nef";
+        let lines = Arc::new(Mutex::new(Lines::default()));
+        let mut index = LineIndex {
+            reader: &text[..],
+            lines: Arc::clone(&lines),
+        };
+        // Small reads, so line breaks land at every offset within a read.
+        let mut buf = [0; 2];
+        while index.read(&mut buf).unwrap() > 0 {}
+        let mut lines = lines.lock().unwrap();
+        assert_eq!(lines.starts, [3, 6, 7]);
+
+        assert_eq!(lines.position(0), (1, 1));
+        // A line break belongs to the line it ends.
+        assert_eq!(lines.position(2), (1, 3));
+        assert_eq!(lines.position(3), (2, 1));
+        assert_eq!(lines.position(5), (2, 3));
+        // The empty third line is just its line break.
+        assert_eq!(lines.position(6), (3, 1));
+        assert_eq!(lines.position(7), (4, 1));
+        assert_eq!(lines.position(8), (4, 2));
+
+        // Forgetting the lines before a printed match keeps later positions.
+        lines.forget_before(5);
+        assert_eq!(lines.starts, [6, 7]);
+        assert_eq!(lines.position(5), (2, 3));
+        lines.forget_before(7);
+        assert!(lines.starts.is_empty());
+        assert_eq!(lines.position(8), (4, 2));
+    }
+}
@@ -0,0 +1,280 @@
+#![warn(clippy::pedantic)]
+
+//! `fuzzy-replace`: substitute the fuzzy matches of a table of patterns, like a fuzzy `sed`.
+//!
+//! The table is a TSV file of `pattern<TAB>replacement` lines, built into a
+//! [`FuzzyReplacer`](fuzzy_aho_corasick::FuzzyReplacer) and applied with
+//! [`replace_stream_with`](fuzzy_aho_corasick::FuzzyReplacer::replace_stream_with). Output goes
+//! to standard output, back into each file (`--in-place`, optionally keeping a backup), or is shown
+//! as a unified diff without writing anything (`--dry-run`). How many changes each table entry
+//! made is reported on standard error.
+
+mod common;
+
+use clap::Parser;
+use common::EngineArgs;
+use fuzzy_aho_corasick::{FuzzyMatch, FuzzyReplacer};
+use similar::TextDiff;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+/// Fuzzy find-and-replace driven by a table of patterns and replacements.
+#[derive(Parser, Debug)]
+#[command(name = "fuzzy-replace", version)]
+struct Cli {
+    /// TSV file of `pattern<TAB>replacement` lines; `\t`, `\n` and `\\` are unescaped in both
+    #[arg(short = 'r', long, value_name = "TSV")]
+    table: PathBuf,
+    /// Write the output back into each file instead of to standard output
+    #[arg(long)]
+    in_place: bool,
+    /// With --in-place, keep each original file as FILE + SUFFIX
+    #[arg(long, value_name = "SUFFIX", requires = "in_place")]
+    backup: Option<String>,
+    /// Print a unified diff of the changes instead of writing any output
+    #[arg(long)]
+    dry_run: bool,
+    /// Don't report the replacement counts on standard error
+    #[arg(short = 'q', long)]
+    quiet: bool,
+    #[command(flatten)]
+    engine: EngineArgs,
+    /// Files to process; none or `-` reads standard input
+    #[arg(value_name = "FILE")]
+    files: Vec<PathBuf>,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(&cli) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        // The reader went away (`fuzzy-replace … | head`): not an error.
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("fuzzy-replace: {e}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// Process every input; `Ok(false)` when some of them failed (already reported).
+fn run(cli: &Cli) -> io::Result<bool> {
+    let table = fs::read_to_string(&cli.table)
+        .and_then(|text| {
+            parse_table(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        })
+        .map_err(|e| io::Error::new(e.kind(), format!("{}: {e}", cli.table.display())))?;
+    let replacer = cli.engine.builder().build_replacer(
+        table
+            .iter()
+            .map(|(pattern, replacement)| (pattern.as_str(), replacement.as_str())),
+    );
+
+    let stdin = PathBuf::from("-");
+    let files = if cli.files.is_empty() {
+        std::slice::from_ref(&stdin)
+    } else {
+        &cli.files[..]
+    };
+    let mut counts = vec![0_usize; table.len()];
+    let mut ok = true;
+    for path in files {
+        // A match already spelled like its replacement changes nothing, so it isn't counted.
+        let count = |m: &FuzzyMatch| {
+            if m.text != table[m.pattern_index].1 {
+                counts[m.pattern_index] += 1;
+            }
+        };
+        let result = if cli.dry_run {
+            diff(&replacer, cli, path, count)
+        } else if cli.in_place {
+            replace_in_place(&replacer, cli, path, count)
+        } else if path == Path::new("-") {
+            let stdout = BufWriter::new(io::stdout().lock());
+            replace_to(&replacer, cli, io::stdin(), stdout, count)
+        } else {
+            let stdout = BufWriter::new(io::stdout().lock());
+            File::open(path).and_then(|file| replace_to(&replacer, cli, file, stdout, count))
+        };
+        match result {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => return Err(e),
+            Err(e) => {
+                eprintln!("fuzzy-replace: {}: {e}", path.display());
+                ok = false;
+            }
+        }
+    }
+
+    if !cli.quiet {
+        for ((pattern, replacement), count) in table.iter().zip(counts) {
+            eprintln!("{count}\t{}\t{}", escape(pattern), escape(replacement));
+        }
+    }
+    Ok(ok)
+}
+
+/// Stream `reader` through the replacer into `writer`.
+fn replace_to<R: Read, W: Write>(
+    replacer: &FuzzyReplacer,
+    cli: &Cli,
+    reader: R,
+    mut writer: W,
+    on_replace: impl FnMut(&FuzzyMatch),
+) -> io::Result<()> {
+    replacer.replace_stream_with(reader, &mut writer, cli.engine.threshold, on_replace)?;
+    writer.flush()
+}
+
+/// Rewrite the file at `path` through a temporary file next to it, copying the original to its
+/// backup name first when one is asked for. The temporary file is renamed over `path`, so `path`
+/// always holds either the original or the complete output.
+fn replace_in_place(
+    replacer: &FuzzyReplacer,
+    cli: &Cli,
+    path: &Path,
+    on_replace: impl FnMut(&FuzzyMatch),
+) -> io::Result<()> {
+    if path == Path::new("-") {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "--in-place needs files, not standard input",
+        ));
+    }
+    let input = File::open(path)?;
+    let permissions = input.metadata()?.permissions();
+    let mut temp_name = path.file_name().unwrap_or_default().to_owned();
+    temp_name.push(format!(".fuzzy-replace-{}", std::process::id()));
+    let temp = path.with_file_name(temp_name);
+
+    let written = File::create(&temp).and_then(|output| {
+        replace_to(replacer, cli, input, BufWriter::new(output), on_replace)?;
+        fs::set_permissions(&temp, permissions)?;
+        if let Some(suffix) = &cli.backup {
+            let mut backup = path.as_os_str().to_owned();
+            backup.push(suffix);
+            fs::copy(path, backup)?;
+        }
+        fs::rename(&temp, path)
+    });
+    if written.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    written
+}
+
+/// Print the changes the replacer would make to `path` as a unified diff.
+fn diff(
+    replacer: &FuzzyReplacer,
+    cli: &Cli,
+    path: &Path,
+    on_replace: impl FnMut(&FuzzyMatch),
+) -> io::Result<()> {
+    let (name, original) = if path == Path::new("-") {
+        let mut original = Vec::new();
+        io::stdin().read_to_end(&mut original)?;
+        ("<stdin>".to_owned(), original)
+    } else {
+        (path.display().to_string(), fs::read(path)?)
+    };
+    let mut output = Vec::new();
+    replace_to(replacer, cli, &original[..], &mut output, on_replace)?;
+    if output != original {
+        let (old, new) = (
+            String::from_utf8_lossy(&original),
+            String::from_utf8_lossy(&output),
+        );
+        let diff = TextDiff::from_lines(&old, &new);
+        let mut stdout = io::stdout().lock();
+        write!(stdout, "{}", diff.unified_diff().header(&name, &name))?;
+        stdout.flush()?;
+    }
+    Ok(())
+}
+
+/// Parse the replacement table: one `pattern<TAB>replacement` pair per line (the replacement is
+/// everything after the first tab), blank lines skipped.
+fn parse_table(text: &str) -> Result<Vec<(String, String)>, String> {
+    let mut pairs = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        if line.is_empty() {
+            continue;
+        }
+        let line_error = |e: &str| format!("line {}: {e}", i + 1);
+        let (pattern, replacement) = line
+            .split_once('\t')
+            .ok_or_else(|| line_error("expected pattern<TAB>replacement"))?;
+        let pattern = unescape(pattern).map_err(|e| line_error(&e))?;
+        if pattern.is_empty() {
+            return Err(line_error("empty pattern"));
+        }
+        let replacement = unescape(replacement).map_err(|e| line_error(&e))?;
+        pairs.push((pattern, replacement));
+    }
+    Ok(pairs)
+}
+
+/// Decode the `\t`, `\n` and `\\` escapes of a table field.
+fn unescape(field: &str) -> Result<String, String> {
+    let mut out = String::with_capacity(field.len());
+    let mut chars = field.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => out.push('\t'),
+            Some('n') => out.push('\n'),
+            Some('\\') => out.push('\\'),
+            Some(other) => return Err(format!("unknown escape \\{other}")),
+            None => return Err("trailing backslash".to_owned()),
+        }
+    }
+    Ok(out)
+}
+
+/// The inverse of [`unescape`], for reporting table entries.
+fn escape(field: &str) -> String {
+    field
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{escape, parse_table, unescape};
+
+    #[test]
+    fn test_parse_table() {
+        let table =
+            parse_table("teh\tthe\n\nrecieve\treceive\r\ntab\\tbed\ta\tb\nempty\t\n").unwrap();
+        let expected = [
+            ("teh", "the"),
+            ("recieve", "receive"),
+            ("tab\tbed", "a\tb"),
+            ("empty", ""),
+        ];
+        assert_eq!(table, expected.map(|(p, r)| (p.to_owned(), r.to_owned())));
+        assert_eq!(
+            parse_table("a\tb\nno tab").unwrap_err(),
+            "line 2: expected pattern<TAB>replacement"
+        );
+        assert_eq!(parse_table("\tb").unwrap_err(), "line 1: empty pattern");
+        assert_eq!(
+            parse_table("a\\x\tb").unwrap_err(),
+            "line 1: unknown escape \\x"
+        );
+    }
+
+    #[test]
+    fn test_escape_round_trip() {
+        for field in ["plain", "a\tb", "line\nbreak", "back\\slash\\t"] {
+            assert_eq!(unescape(&escape(field)).unwrap(), field);
+        }
+    }
+}
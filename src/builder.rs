@@ -1,10 +1,14 @@
+use crate::cost::{CustomCost, SubstitutionCost};
+use crate::keyboard::KeyboardLayout;
+use crate::ocr::{OCR_MAPPINGS, OCR_SIMILARITY};
+use crate::phonetic::{DEFAULT_PHONETIC_SIMILARITY, PhoneticAlgorithm, PhoneticIndex};
 use crate::structs::{FxHashMap, Similarity};
 use crate::{
     Edge, FuzzyAhoCorasick, FuzzyLimits, FuzzyPenalties, FuzzyReplacer, MappingTransition, Node,
-    Pattern,
+    Pattern, PayloadAhoCorasick,
 };
 use std::collections::VecDeque;
-use std::sync::LazyLock;
+use std::sync::{Arc, LazyLock};
 use unicode_segmentation::UnicodeSegmentation;
 
 /// Builder for [`FuzzyAhoCorasick`].
@@ -21,7 +25,8 @@ use unicode_segmentation::UnicodeSegmentation;
 /// ```
 #[derive(Debug, Default)]
 pub struct FuzzyAhoCorasickBuilder {
-    similarity: Option<&'static Similarity>,
+    similarity: Option<Arc<Similarity>>,
+    substitution_cost: Option<CustomCost>,
     limits: Option<FuzzyLimits>,
     penalties: FuzzyPenalties,
     case_insensitive: bool,
@@ -30,14 +35,17 @@ pub struct FuzzyAhoCorasickBuilder {
     /// Multi-character mapping rules `(seq_a, seq_b, score)`, applied bidirectionally.
     mappings: Vec<(String, String, f32)>,
     min_symbol_similarity: f32,
+    /// Phonetic encoding and the similarity its matches score.
+    phonetic: Option<(PhoneticAlgorithm, f32)>,
 }
 
 impl FuzzyAhoCorasickBuilder {
-    /// Start with sensible defaults (borrowed similarity map, 2 edits, etc.)
+    /// Start with sensible defaults (shared default similarity map, 2 edits, etc.)
     #[must_use]
     pub fn new() -> Self {
         Self {
             similarity: None,
+            substitution_cost: None,
             limits: None,
             penalties: FuzzyPenalties::default(),
             case_insensitive: false,
@@ -45,13 +53,66 @@ impl FuzzyAhoCorasickBuilder {
             auto_beam: None,
             mappings: Vec::new(),
             min_symbol_similarity: 0.0,
+            phonetic: None,
         }
     }
 
-    /// Provide custom similarity data.
+    /// Provide custom similarity data, either an owned [`Similarity`] or an `Arc` shared with other
+    /// engines (e.g. one table per tenant or language, loaded from config at runtime). The engine
+    /// keeps its own reference, so the table is dropped with the last engine using it.
     #[must_use]
-    pub fn similarity(mut self, similarity: &'static Similarity) -> Self {
-        self.similarity = Some(similarity);
+    pub fn similarity(mut self, similarity: impl Into<Arc<Similarity>>) -> Self {
+        self.similarity = Some(similarity.into());
+        self
+    }
+
+    /// Use the ready-made [`Similarity::keyboard`] table for `layout`, so typos on neighbouring keys
+    /// are cheap substitutions. Replaces the default table, like [`similarity`](Self::similarity);
+    /// engines on the same layout share one table.
+    #[must_use]
+    pub fn keyboard_layout(self, layout: KeyboardLayout) -> Self {
+        self.similarity(layout.shared_similarity())
+    }
+
+    /// Tune the engine for OCR output in one call: install the [`Similarity::ocr`] confusion table
+    /// (`0`↔`O`, `1`↔`l`, `5`↔`S`, …, see [`OCR_CONFUSIONS`](crate::OCR_CONFUSIONS)) and register
+    /// the multi-glyph confusions in [`OCR_MAPPINGS`](crate::OCR_MAPPINGS) (`rn`↔`m`, `cl`↔`d`,
+    /// `vv`↔`w`, ligatures, …) as scored mappings.
+    ///
+    /// The table replaces the current one, like [`similarity`](Self::similarity); the mappings are
+    /// added to any already registered, and further [`mapping`](Self::mapping)s can follow.
+    ///
+    /// ```
+    /// use fuzzy_aho_corasick::{FuzzyAhoCorasickBuilder, FuzzyLimits, SearchOptions};
+    ///
+    /// let engine = FuzzyAhoCorasickBuilder::new()
+    ///     .fuzzy(FuzzyLimits::new().edits(2))
+    ///     .ocr_preset()
+    ///     .build(["modern", "world"]);
+    /// let opts = SearchOptions::new().threshold(0.8).sorted().non_overlapping();
+    /// let found: Vec<_> = engine.search("rnodern vvor1d", &opts).unwrap().matched_strings();
+    /// assert_eq!(found, ["rnodern", "vvor1d"]);
+    /// ```
+    #[must_use]
+    pub fn ocr_preset(mut self) -> Self {
+        self.mappings.extend(
+            OCR_MAPPINGS
+                .iter()
+                .map(|&(a, b, score)| (a.to_owned(), b.to_owned(), score)),
+        );
+        self.similarity(Arc::clone(&OCR_SIMILARITY))
+    }
+
+    /// Score substitutions with a custom [`SubstitutionCost`] model instead of the similarity table.
+    /// The model sees whole grapheme clusters, so it can tell `"e\u{301}"` from `"e"` where the
+    /// table only compares first `char`s; exact transitions then compare whole graphemes too.
+    ///
+    /// Searches with a model skip the [bit-parallel pre-filter](FuzzyAhoCorasick::with_prefilter),
+    /// and serialized automata record that a model was used — see
+    /// [`from_bytes_with_substitution_cost`](FuzzyAhoCorasick::from_bytes_with_substitution_cost).
+    #[must_use]
+    pub fn substitution_cost(mut self, cost: impl SubstitutionCost + 'static) -> Self {
+        self.substitution_cost = Some(CustomCost(Arc::new(cost)));
         self
     }
 
@@ -142,6 +203,41 @@ impl FuzzyAhoCorasickBuilder {
         self
     }
 
+    /// Also match patterns by sound: index every pattern by the `algorithm` encoding of its words,
+    /// and report runs of haystack words that encode the same as [`FuzzyMatch`](crate::FuzzyMatch)es
+    /// scoring [`DEFAULT_PHONETIC_SIMILARITY`] (times the pattern weight) — so `"Smyth"` or, with
+    /// [`Cologne`](PhoneticAlgorithm::Cologne), `"Schmidt"` finds `"Smith"` however many edits apart
+    /// they are. Edit-distance matching runs as usual; where both find the same span, the better
+    /// score is kept.
+    ///
+    /// Phonetic matches cover whole words, report no edits, and aren't bound by the edit limits.
+    /// Searches with phonetic matching skip the [bit-parallel
+    /// pre-filter](FuzzyAhoCorasick::with_prefilter).
+    ///
+    /// ```
+    /// use fuzzy_aho_corasick::{FuzzyAhoCorasickBuilder, FuzzyLimits, PhoneticAlgorithm, SearchOptions};
+    ///
+    /// let engine = FuzzyAhoCorasickBuilder::new()
+    ///     .fuzzy(FuzzyLimits::new().edits(1))
+    ///     .phonetic(PhoneticAlgorithm::Cologne)
+    ///     .build(["Smith"]);
+    /// let opts = SearchOptions::new().threshold(0.7).sorted().non_overlapping();
+    /// let found = engine.search("Smyth, Schmidt and Jones", &opts).unwrap().matched_strings();
+    /// assert_eq!(found, ["Smyth", "Schmidt"]);
+    /// ```
+    #[must_use]
+    pub fn phonetic(self, algorithm: PhoneticAlgorithm) -> Self {
+        self.phonetic_scored(algorithm, DEFAULT_PHONETIC_SIMILARITY)
+    }
+
+    /// Like [`phonetic`](Self::phonetic), with phonetic matches scoring `score` (`0.0..=1.0`, before
+    /// the pattern weight) instead of [`DEFAULT_PHONETIC_SIMILARITY`].
+    #[must_use]
+    pub fn phonetic_scored(mut self, algorithm: PhoneticAlgorithm, score: f32) -> Self {
+        self.phonetic = Some((algorithm, score));
+        self
+    }
+
     /// Prefix‑membership‑function – the deeper we are inside a pattern, the
     /// lower the weight (ensures that complete matches rank higher than
     /// partial prefix matches).
@@ -167,6 +263,29 @@ impl FuzzyAhoCorasickBuilder {
         }
     }
 
+    /// Build a [`PayloadAhoCorasick`] from `(pattern, payload)` pairs: each pattern is matched
+    /// fuzzily (with this builder's configuration), and its matches come with its payload.
+    /// A typed alternative to [`build`](Self::build) plus a parallel `Vec` indexed by
+    /// `pattern_index`.
+    #[must_use]
+    pub fn build_with_payloads<T, P>(
+        self,
+        pairs: impl IntoIterator<Item = (T, P)>,
+    ) -> PayloadAhoCorasick<P>
+    where
+        T: Into<Pattern>,
+    {
+        let (patterns, payloads): (Vec<_>, Vec<_>) = pairs
+            .into_iter()
+            .map(|(p, payload)| (p.into(), payload))
+            .unzip();
+
+        PayloadAhoCorasick {
+            engine: self.build(patterns),
+            payloads,
+        }
+    }
+
     /// Builds an immutable [`FuzzyAhoCorasick`] engine from pattern list.
     ///
     /// ```rust
@@ -183,7 +302,9 @@ impl FuzzyAhoCorasickBuilder {
         T: Into<Pattern>,
     {
         let patterns: Vec<Pattern> = inputs.into_iter().map(Into::into).collect();
-        let similarity: &'static Similarity = self.similarity.unwrap_or(&DEFAULT_SIMILARITY);
+        let similarity = self
+            .similarity
+            .unwrap_or_else(|| Arc::clone(&DEFAULT_SIMILARITY));
 
         let mut nodes = vec![Node::new(
             #[cfg(debug_assertions)]
@@ -339,6 +460,9 @@ impl FuzzyAhoCorasickBuilder {
                 .iter()
                 .map(|(g, &next)| Edge::new(g.chars().next().unwrap_or('\0'), next, g.len() == 1))
                 .collect();
+            if self.substitution_cost.is_some() {
+                node.edge_graphemes = node.transitions.keys().map(|g| g.as_str().into()).collect();
+            }
         }
 
         // Per-node reachable bounds (longest pattern / heaviest weight reachable from each node).
@@ -442,6 +566,9 @@ impl FuzzyAhoCorasickBuilder {
         }
 
         let has_pattern_limits = patterns.iter().any(|p| p.limits.is_some());
+        let phonetic = self
+            .phonetic
+            .map(|(algorithm, score)| PhoneticIndex::new(algorithm, score, &patterns));
 
         // Fast-path edit ceiling: when the global limits only constrain total `edits` (all
         // per-type fields `None`) and no pattern has its own limits, the hot loop can check
@@ -471,6 +598,7 @@ impl FuzzyAhoCorasickBuilder {
             nodes,
             patterns,
             similarity,
+            substitution_cost: self.substitution_cost,
             limits: effective_limits,
             penalties: self.penalties,
             case_insensitive: self.case_insensitive,
@@ -480,6 +608,8 @@ impl FuzzyAhoCorasickBuilder {
             beam_width: self.beam_width,
             auto_beam: self.auto_beam,
             min_symbol_similarity: self.min_symbol_similarity,
+            phonetic,
+            shared: None,
         }
     }
 }
@@ -488,8 +618,9 @@ impl FuzzyAhoCorasickBuilder {
  *  Default similarity
  * ---------------------------------------------------------------------- */
 
-/// Singleton that stores the lazily‑initialised vowel/consonant similarity data.
-static DEFAULT_SIMILARITY: LazyLock<Similarity> = LazyLock::new(|| {
+/// Singleton that stores the lazily‑initialised vowel/consonant similarity data, shared by every
+/// engine built without a custom table.
+pub(crate) static DEFAULT_SIMILARITY: LazyLock<Arc<Similarity>> = LazyLock::new(|| {
     let mut map = FxHashMap::default();
     let vowels = ['a', 'e', 'i', 'o', 'u'];
     let consonants = (b'a'..=b'z')
@@ -522,5 +653,5 @@ static DEFAULT_SIMILARITY: LazyLock<Similarity> = LazyLock::new(|| {
     map.insert(('1', 'i'), 0.6);
     map.insert(('s', '5'), 0.5);
     map.insert(('5', 's'), 0.5);
-    Similarity::from_map(map)
+    Arc::new(Similarity::from_map(map))
 });
@@ -0,0 +1,105 @@
+//! Pluggable substitution scoring.
+//!
+//! By default the engine scores a substituted grapheme with the built-in [`Similarity`] table, which
+//! compares the *first `char`* of each grapheme cluster. A [`SubstitutionCost`] model replaces that
+//! table with one that sees whole clusters, so combining-mark sequences (`"é"` vs `"e\u{301}"`),
+//! emoji ZWJ sequences, or script-specific confusions can be scored meaningfully.
+
+use crate::structs::Similarity;
+use std::fmt;
+use std::sync::Arc;
+
+/// A substitution similarity model: how alike a pattern grapheme and the haystack grapheme standing
+/// in for it are, in `0.0..=1.0`. The substitution penalty is `substitution_cost * (1 - sim)`, and the
+/// [weakest-link floor](crate::FuzzyAhoCorasickBuilder::min_symbol_similarity) applies to `sim` as it
+/// does for the built-in table.
+///
+/// The engine calls [`char_similarity`](Self::char_similarity) when both graphemes are single
+/// `char`s — the common case, kept cheap — and [`grapheme_similarity`](Self::grapheme_similarity)
+/// otherwise. Identical graphemes never reach the model (they are exact matches). Install a model
+/// with [`FuzzyAhoCorasickBuilder::substitution_cost`](crate::FuzzyAhoCorasickBuilder::substitution_cost).
+///
+/// ```
+/// use fuzzy_aho_corasick::{FuzzyAhoCorasickBuilder, FuzzyLimits, SearchOptions, SubstitutionCost};
+///
+/// /// Treats graphemes that differ only in combining marks as near-identical.
+/// struct DiacriticAware;
+///
+/// impl SubstitutionCost for DiacriticAware {
+///     fn char_similarity(&self, a: char, b: char) -> f32 {
+///         if a.eq_ignore_ascii_case(&b) { 0.9 } else { 0.0 }
+///     }
+///     fn grapheme_similarity(&self, pattern: &str, haystack: &str) -> f32 {
+///         let base = |g: &str| g.chars().next();
+///         if base(pattern) == base(haystack) { 0.95 } else { 0.0 }
+///     }
+/// }
+///
+/// let engine = FuzzyAhoCorasickBuilder::new()
+///     .fuzzy(FuzzyLimits::new().substitutions(1))
+///     .substitution_cost(DiacriticAware)
+///     .build(["cafe"]);
+/// let hits = engine.search("cafe\u{301}", &SearchOptions::new().threshold(0.9)).unwrap();
+/// assert_eq!(hits.len(), 1);
+/// ```
+pub trait SubstitutionCost: Send + Sync {
+    /// Similarity of substituting the single-`char` pattern grapheme `a` with the single-`char`
+    /// haystack grapheme `b`.
+    fn char_similarity(&self, a: char, b: char) -> f32;
+
+    /// Similarity of substituting the pattern grapheme cluster `pattern` with the haystack grapheme
+    /// cluster `haystack`, where at least one of them spans several `char`s. Both are case-folded
+    /// when the engine is case-insensitive.
+    ///
+    /// The default scores the clusters' first `char`s with
+    /// [`char_similarity`](Self::char_similarity), like the built-in table does.
+    fn grapheme_similarity(&self, pattern: &str, haystack: &str) -> f32 {
+        match (pattern.chars().next(), haystack.chars().next()) {
+            (Some(a), Some(b)) => self.char_similarity(a, b),
+            _ => 0.0,
+        }
+    }
+}
+
+impl SubstitutionCost for Similarity {
+    #[inline]
+    fn char_similarity(&self, a: char, b: char) -> f32 {
+        self.get(a, b)
+    }
+}
+
+impl<T: SubstitutionCost + ?Sized> SubstitutionCost for Arc<T> {
+    #[inline]
+    fn char_similarity(&self, a: char, b: char) -> f32 {
+        (**self).char_similarity(a, b)
+    }
+
+    fn grapheme_similarity(&self, pattern: &str, haystack: &str) -> f32 {
+        (**self).grapheme_similarity(pattern, haystack)
+    }
+}
+
+/// A custom model installed on an engine, cheap to clone between engines.
+#[derive(Clone)]
+pub(crate) struct CustomCost(pub(crate) Arc<dyn SubstitutionCost>);
+
+impl fmt::Debug for CustomCost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SubstitutionCost").finish_non_exhaustive()
+    }
+}
+
+impl CustomCost {
+    /// Score substituting the pattern grapheme `pattern` (first `char` `a`) with the haystack
+    /// grapheme `haystack` (first `char` `b`), taking the char-pair path when both are single `char`s.
+    #[inline]
+    pub(crate) fn score(&self, pattern: &str, a: char, haystack: &str, b: char) -> f32 {
+        if pattern == haystack {
+            1.0
+        } else if pattern.len() == a.len_utf8() && haystack.len() == b.len_utf8() {
+            self.0.char_similarity(a, b)
+        } else {
+            self.0.grapheme_similarity(pattern, haystack)
+        }
+    }
+}
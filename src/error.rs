@@ -1,4 +1,4 @@
-//! Error type returned by the fallible search entry points.
+//! Error types returned by the fallible search, deserialization and pattern-list entry points.
 
 /// An error from a search call.
 ///
@@ -29,3 +29,130 @@ impl core::fmt::Display for SearchError {
 }
 
 impl std::error::Error for SearchError {}
+
+/// An error from [`FuzzyAhoCorasick::from_bytes`](crate::FuzzyAhoCorasick::from_bytes): the bytes are
+/// not a serialized automaton this version of the crate can load.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DeserializeError {
+    /// The input doesn't start with the serialized-automaton magic bytes.
+    BadMagic,
+    /// The input was written by an incompatible version of the format.
+    UnsupportedVersion {
+        /// The format version recorded in the input.
+        found: u32,
+        /// The format version this crate reads and writes.
+        expected: u32,
+    },
+    /// The payload doesn't hash to the checksum recorded in the header (corruption or truncation in
+    /// transit).
+    ChecksumMismatch,
+    /// The input ends before the structure it describes does.
+    Truncated,
+    /// The payload passed the checksum but describes an inconsistent automaton (e.g. an edge to a
+    /// node that doesn't exist). The string names the offending part.
+    Malformed(&'static str),
+    /// The automaton was built with a custom [`SubstitutionCost`](crate::SubstitutionCost) model,
+    /// which isn't serialized; load it with
+    /// [`from_bytes_with_substitution_cost`](crate::FuzzyAhoCorasick::from_bytes_with_substitution_cost).
+    SubstitutionCostRequired,
+}
+
+impl core::fmt::Display for DeserializeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DeserializeError::BadMagic => {
+                f.write_str("not a serialized fuzzy Aho–Corasick automaton")
+            }
+            DeserializeError::UnsupportedVersion { found, expected } => write!(
+                f,
+                "serialized automaton has format version {found}, this crate reads version {expected}"
+            ),
+            DeserializeError::ChecksumMismatch => {
+                f.write_str("serialized automaton is corrupt (checksum mismatch)")
+            }
+            DeserializeError::Truncated => f.write_str("serialized automaton is truncated"),
+            DeserializeError::Malformed(what) => {
+                write!(f, "serialized automaton is malformed: {what}")
+            }
+            DeserializeError::SubstitutionCostRequired => f.write_str(
+                "automaton was built with a custom substitution cost model; supply it via \
+                 from_bytes_with_substitution_cost",
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DeserializeError {}
+
+/// An error from [`Pattern::parse_list`](crate::Pattern::parse_list): a line of the pattern list
+/// that doesn't follow the format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternListError {
+    /// The 1-based line number of the offending line.
+    pub line: usize,
+    /// What is wrong with it.
+    pub kind: PatternListErrorKind,
+}
+
+/// What is wrong with a line of a pattern list (see [`PatternListError`]).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PatternListErrorKind {
+    /// The pattern field is empty.
+    EmptyPattern,
+    /// An option field has no `=`.
+    MissingValue {
+        /// The field as written.
+        field: String,
+    },
+    /// An option key the format doesn't define (metadata keys take a `meta.` prefix).
+    UnknownOption {
+        /// The key as written.
+        key: String,
+    },
+    /// The same option appears twice on one line.
+    DuplicateOption {
+        /// The repeated key.
+        key: String,
+    },
+    /// An option's value doesn't parse as the type the option takes.
+    InvalidValue {
+        /// The option's key.
+        key: String,
+        /// The value as written.
+        value: String,
+    },
+    /// A backslash escape other than `\t`, `\n`, `\#` or `\\` (or a trailing backslash).
+    InvalidEscape {
+        /// The escape sequence as written.
+        escape: String,
+    },
+}
+
+impl core::fmt::Display for PatternListError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            PatternListErrorKind::EmptyPattern => f.write_str("empty pattern"),
+            PatternListErrorKind::MissingValue { field } => {
+                write!(f, "expected key=value, got {field:?}")
+            }
+            PatternListErrorKind::UnknownOption { key } => write!(
+                f,
+                "unknown option {key:?} (metadata keys are written meta.NAME)"
+            ),
+            PatternListErrorKind::DuplicateOption { key } => {
+                write!(f, "option {key:?} given more than once")
+            }
+            PatternListErrorKind::InvalidValue { key, value } => {
+                write!(f, "invalid value {value:?} for option {key:?}")
+            }
+            PatternListErrorKind::InvalidEscape { escape } => {
+                write!(f, "unknown escape {escape:?}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PatternListError {}
@@ -0,0 +1,192 @@
+//! Lazy match iteration: [`FuzzyAhoCorasick::find_iter`] yields matches in leftmost order, searching
+//! only as far into the haystack as the matches taken so far require.
+//!
+//! Every match the automaton finds from a start position begins at that position, so the matches
+//! starting leftmost are known as soon as the first start window that yields any has been explored.
+//! Each step resumes the search there: it takes the matches starting leftmost at or after the resume
+//! point, picks among them, and moves the resume point past what it yielded.
+
+use crate::grapheme::PreparedHaystack;
+use crate::options::SpanConstraints;
+use crate::phonetic::PhoneticWords;
+use crate::search::SearchBuffers;
+use crate::{
+    FuzzyAhoCorasick, FuzzyMatch, FuzzyMatches, MatchKind, Overlap, SearchError, SearchOptions,
+    UniqueId,
+};
+use std::collections::{BTreeSet, VecDeque};
+
+/// Iterator returned by [`FuzzyAhoCorasick::find_iter`].
+pub struct FindIter<'a> {
+    engine: &'a FuzzyAhoCorasick,
+    haystack: &'a str,
+    prepared: PreparedHaystack<'a>,
+    boundaries: Option<Vec<bool>>,
+    opts: SearchOptions,
+    /// Grapheme index of the next start window to explore.
+    resume: usize,
+    /// Search buffers reused from one step to the next.
+    buffers: SearchBuffers,
+    /// The haystack's words for the phonetic matches, which come from a word index rather than the
+    /// automaton walk: each step scans them from the resume point up to its edit-distance matches.
+    words: PhoneticWords<'a>,
+    /// Matches found but not yet yielded (under [`Overlap::Keep`], every match at a position).
+    pending: VecDeque<FuzzyMatch<'a>>,
+    /// Pattern identities already yielded, under [`Overlap::NonOverlappingUnique`].
+    used: BTreeSet<UniqueId>,
+}
+
+impl<'a> FindIter<'a> {
+    /// The matches starting at the leftmost position at or after `self.resume`, best first (see
+    /// [`FuzzyMatches::rank_at_start`]). Empty once the haystack is exhausted.
+    fn next_group(&mut self) -> Vec<FuzzyMatch<'a>> {
+        if self.resume >= self.prepared.len() {
+            return Vec::new();
+        }
+        let constraints = SpanConstraints {
+            boundaries: self.boundaries.as_deref(),
+            anchored: self.opts.anchored,
+            leftmost_only: true,
+            first_window: self.resume,
+            alignment: self.opts.alignment,
+        };
+        let mut group = self
+            .engine
+            .search_prepared(
+                &self.prepared,
+                self.haystack,
+                self.opts.threshold,
+                constraints,
+                &mut self.buffers,
+            )
+            .inner;
+
+        if let Some(index) = &self.engine.phonetic {
+            let resume_byte = self.prepared.byte_offset(self.resume, self.haystack.len());
+            self.words.skip_to(resume_byte);
+            index.merge_words_into(
+                self.engine,
+                self.haystack,
+                &mut self.words,
+                self.opts.threshold,
+                constraints,
+                &mut group,
+            );
+        }
+        let Some(start) = group.iter().map(|m| m.start).min() else {
+            self.resume = self.prepared.len();
+            return group;
+        };
+        group.retain(|m| m.start == start);
+
+        let mut group = FuzzyMatches {
+            haystack: self.haystack,
+            inner: group,
+        };
+        group.rank_at_start(&self.opts);
+        self.resume = self.prepared.grapheme_at(start) + 1;
+        group.inner
+    }
+}
+
+impl<'a> Iterator for FindIter<'a> {
+    type Item = FuzzyMatch<'a>;
+
+    fn next(&mut self) -> Option<FuzzyMatch<'a>> {
+        loop {
+            if let Some(m) = self.pending.pop_front() {
+                return Some(m);
+            }
+            let group = self.next_group();
+            if group.is_empty() {
+                return None;
+            }
+            // The leftmost kinds are non-overlapping by definition.
+            let overlap = if self.opts.match_kind == MatchKind::Ranked {
+                self.opts.overlap
+            } else {
+                Overlap::NonOverlapping
+            };
+            let picked = match overlap {
+                Overlap::Keep => {
+                    self.pending.extend(group);
+                    continue;
+                }
+                Overlap::NonOverlapping | Overlap::Optimal => group.into_iter().next(),
+                Overlap::NonOverlappingUnique => group
+                    .into_iter()
+                    .find(|m| !self.used.contains(&m.unique_id())),
+            };
+            // Every pattern starting here was used already: resume at the next position.
+            let Some(m) = picked else {
+                continue;
+            };
+            if overlap == Overlap::NonOverlappingUnique {
+                self.used.insert(m.unique_id());
+            }
+            self.resume = self.resume.max(self.prepared.grapheme_at(m.end));
+            return Some(m);
+        }
+    }
+}
+
+impl FuzzyAhoCorasick {
+    /// Lazily iterate over the matches in `haystack` in leftmost order, searching only as far as
+    /// the matches taken so far require — stop early or pipeline results without materializing a
+    /// [`FuzzyMatches`].
+    ///
+    /// Each step finds the leftmost position (at or after the previous match) where an accepted
+    /// match starts, and `opts.order` ranks the matches starting there
+    /// ([`Order::Default`](crate::Order::Default) when left unsorted: leftmost-best;
+    /// [`Order::Greedy`](crate::Order::Greedy): leftmost-longest). `opts.overlap` decides what is
+    /// yielded:
+    /// * [`Overlap::Keep`] — every match starting there, best first; the scan resumes at the next
+    ///   position, so the iterator yields the same set as [`search`](Self::search).
+    /// * [`Overlap::NonOverlapping`] — the best one; the scan resumes at its end.
+    /// * [`Overlap::NonOverlappingUnique`] — the best one whose pattern identity hasn't been
+    ///   yielded yet.
+    /// * [`Overlap::Optimal`] — needs every match in the haystack up front, so it is yielded like
+    ///   `NonOverlapping`; run [`search`](Self::search) for the optimal set.
+    ///
+    /// Overlaps are thus resolved positionally, left to right, rather than globally by rank as
+    /// `search` does — a better-scoring match further right never displaces one already yielded.
+    /// A leftmost [`MatchKind`] picks among the matches starting there by its own preference
+    /// instead, and never overlaps: the iterator then yields what `search` returns for the same
+    /// options.
+    ///
+    /// # Errors
+    /// Returns [`SearchError::HaystackTooLarge`] if `haystack` has more than `u32::MAX` grapheme
+    /// clusters — see [`search`](Self::search).
+    ///
+    /// # Example
+    /// ```
+    /// use fuzzy_aho_corasick::{FuzzyAhoCorasickBuilder, FuzzyLimits, SearchOptions};
+    /// let engine = FuzzyAhoCorasickBuilder::new()
+    ///     .fuzzy(FuzzyLimits::new().edits(1))
+    ///     .build(["hello", "world"]);
+    /// let opts = SearchOptions::new().threshold(0.7).non_overlapping();
+    /// let mut matches = engine.find_iter("helo wrld, hello world", &opts).unwrap();
+    /// assert_eq!(matches.next().unwrap().text, "helo");
+    /// assert_eq!(matches.next().unwrap().text, "wrld"); // the rest is never searched
+    /// ```
+    pub fn find_iter<'a>(
+        &'a self,
+        haystack: &'a str,
+        opts: &SearchOptions,
+    ) -> Result<FindIter<'a>, SearchError> {
+        let prepared = self.prepare(haystack)?;
+        let boundaries = opts.boundary.byte_flags(haystack);
+        Ok(FindIter {
+            engine: self,
+            haystack,
+            prepared,
+            boundaries,
+            opts: *opts,
+            resume: 0,
+            buffers: SearchBuffers::default(),
+            words: PhoneticWords::new(haystack),
+            pending: VecDeque::new(),
+            used: BTreeSet::new(),
+        })
+    }
+}
@@ -1,6 +1,6 @@
 //! Grapheme-storage abstraction: lets the BFS hot loop be monomorphized over an
 //! allocation-free ASCII fast path and the full Unicode path.
-use crate::Node;
+use crate::trie::TrieNode;
 use std::borrow::Cow;
 
 /// Compile-time table of bytes `[0x00, 0x01, …, 0x7F]` so that we can return a `&'static str`
@@ -42,10 +42,10 @@ pub(crate) trait GraphemeStorage {
     /// Find the automaton transition from `node` for the grapheme at position `idx`.
     /// The caller passes the already-computed first `char` (`ch`) to avoid a redundant
     /// `gs_first_char` call. For ASCII storage this skips the `&str` creation, `as_bytes()`,
-    /// and byte-length check that `Node::find_transition` would do, by going straight to the
+    /// and byte-length check that `TrieNode::find_transition` would do, by going straight to the
     /// char-based linear scan. For Unicode storage it delegates to `find_transition` since
-    /// multi-byte graphemes need the full `&str` `HashMap` lookup path.
-    fn gs_find_transition(&self, node: &Node, idx: usize, ch: char) -> Option<u32>;
+    /// multi-byte graphemes need the full `&str` lookup path.
+    fn gs_find_transition<N: TrieNode>(&self, node: N, idx: usize, ch: char) -> Option<u32>;
 }
 
 impl GraphemeStorage for Vec<(usize, Cow<'_, str>)> {
@@ -66,11 +66,47 @@ impl GraphemeStorage for Vec<(usize, Cow<'_, str>)> {
         self[idx].1.chars().next().unwrap_or('\0')
     }
     #[inline]
-    fn gs_find_transition(&self, node: &Node, idx: usize, _ch: char) -> Option<u32> {
+    fn gs_find_transition<N: TrieNode>(&self, node: N, idx: usize, _ch: char) -> Option<u32> {
         node.find_transition(self.gs_text(idx))
     }
 }
 
+/// A haystack segmented for the search: its grapheme storage (the ASCII fast path or the full
+/// Unicode one) and the first `char` of every grapheme. Built once per haystack, so a caller that
+/// searches the same haystack repeatedly (the lazy match iterator) segments it only once.
+pub(crate) enum PreparedHaystack<'a> {
+    Ascii(AsciiGraphemes<'a>, Vec<char>),
+    Unicode(Vec<(usize, Cow<'a, str>)>, Vec<char>),
+}
+
+impl PreparedHaystack<'_> {
+    /// Number of graphemes.
+    pub(crate) fn len(&self) -> usize {
+        match self {
+            PreparedHaystack::Ascii(_, chars) | PreparedHaystack::Unicode(_, chars) => chars.len(),
+        }
+    }
+
+    /// Byte offset of the `idx`-th grapheme, or `haystack_len` one past the last.
+    pub(crate) fn byte_offset(&self, idx: usize, haystack_len: usize) -> usize {
+        if idx >= self.len() {
+            return haystack_len;
+        }
+        match self {
+            PreparedHaystack::Ascii(g, _) => g.gs_byte_offset(idx),
+            PreparedHaystack::Unicode(g, _) => g.gs_byte_offset(idx),
+        }
+    }
+
+    /// Index of the first grapheme starting at or after byte offset `byte`.
+    pub(crate) fn grapheme_at(&self, byte: usize) -> usize {
+        match self {
+            PreparedHaystack::Ascii(..) => byte.min(self.len()),
+            PreparedHaystack::Unicode(g, _) => g.partition_point(|&(offset, _)| offset < byte),
+        }
+    }
+}
+
 /// Zero-allocation grapheme storage for all-ASCII haystacks: each byte is a grapheme, and
 /// case-folding is computed on the fly via the static `ascii_byte_to_str` table.
 pub(crate) struct AsciiGraphemes<'a> {
@@ -117,7 +153,7 @@ impl GraphemeStorage for AsciiGraphemes<'_> {
         }
     }
     #[inline]
-    fn gs_find_transition(&self, node: &Node, _idx: usize, ch: char) -> Option<u32> {
+    fn gs_find_transition<N: TrieNode>(&self, node: N, _idx: usize, ch: char) -> Option<u32> {
         // All graphemes are single-byte ASCII, so skip the &str creation and
         // byte-length check in `find_transition` and go straight to the char scan.
         node.find_transition_char(ch)
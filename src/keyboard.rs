@@ -0,0 +1,137 @@
+//! Ready-made similarity tables for typed input, derived from keyboard geometry.
+//!
+//! Keys are placed on a staggered grid (each row shifted right of the one above, as on a physical
+//! keyboard). Two keys are *adjacent* when they sit in the same or a neighbouring row and their
+//! centres are at most one key-width apart horizontally; keys struck by the same finger under
+//! touch-typing (same column group) but not adjacent are *same-finger* keys.
+
+use crate::structs::Similarity;
+use std::sync::{Arc, LazyLock};
+
+/// Similarity of two neighbouring keys — the most common typo.
+pub const ADJACENT_KEY_SIMILARITY: f32 = 0.75;
+/// Similarity of two non-neighbouring keys struck by the same finger.
+pub const SAME_FINGER_SIMILARITY: f32 = 0.5;
+
+/// A keyboard layout for [`Similarity::keyboard`] and
+/// [`FuzzyAhoCorasickBuilder::keyboard_layout`](crate::FuzzyAhoCorasickBuilder::keyboard_layout).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyboardLayout {
+    /// US/UK QWERTY.
+    Qwerty,
+    /// French AZERTY.
+    Azerty,
+    /// German QWERTZ.
+    Qwertz,
+    /// US Dvorak.
+    Dvorak,
+}
+
+/// Horizontal offset of each row, in key widths: number row, top, home, bottom.
+const ROW_OFFSETS: [f32; 4] = [0.0, 0.5, 0.75, 1.25];
+
+impl KeyboardLayout {
+    /// The unshifted characters of the four main rows, left to right.
+    fn rows(self) -> [&'static str; 4] {
+        match self {
+            KeyboardLayout::Qwerty => ["1234567890-=", "qwertyuiop[]", "asdfghjkl;'", "zxcvbnm,./"],
+            KeyboardLayout::Azerty => {
+                ["1234567890)=", "azertyuiop^$", "qsdfghjklmù*", "wxcvbn,;:!"]
+            }
+            KeyboardLayout::Qwertz => {
+                ["1234567890ß´", "qwertzuiopü+", "asdfghjklöä#", "yxcvbnm,.-"]
+            }
+            KeyboardLayout::Dvorak => ["1234567890[]", "',.pyfgcrl/=", "aoeuidhtns-", ";qjkxbmwvz"],
+        }
+    }
+
+    /// The shared table for this layout, built once.
+    pub(crate) fn shared_similarity(self) -> Arc<Similarity> {
+        static TABLES: [LazyLock<Arc<Similarity>>; 4] = [
+            LazyLock::new(|| Arc::new(Similarity::keyboard(KeyboardLayout::Qwerty))),
+            LazyLock::new(|| Arc::new(Similarity::keyboard(KeyboardLayout::Azerty))),
+            LazyLock::new(|| Arc::new(Similarity::keyboard(KeyboardLayout::Qwertz))),
+            LazyLock::new(|| Arc::new(Similarity::keyboard(KeyboardLayout::Dvorak))),
+        ];
+        Arc::clone(&TABLES[self as usize])
+    }
+}
+
+/// The single uppercase `char` of a lowercase letter, if it has one (`'ß'` uppercases to `"SS"`).
+fn uppercase(ch: char) -> Option<char> {
+    let mut upper = ch.to_uppercase();
+    let first = upper.next()?;
+    (upper.next().is_none() && first != ch).then_some(first)
+}
+
+/// Touch-typing finger for a key in column `col` (`0..=3` left hand pinky to index, `4..=7` right
+/// hand index to pinky); the index fingers cover two columns each, and the right pinky everything
+/// from the tenth column on.
+fn finger(col: usize) -> u8 {
+    match col {
+        0 => 0,
+        1 => 1,
+        2 => 2,
+        3 | 4 => 3,
+        5 | 6 => 4,
+        7 => 5,
+        8 => 6,
+        _ => 7,
+    }
+}
+
+impl Similarity {
+    /// A similarity table for typed input on `layout`: neighbouring keys score
+    /// [`ADJACENT_KEY_SIMILARITY`], other keys struck by the same finger
+    /// [`SAME_FINGER_SIMILARITY`], and everything else `0.0`. Letters are covered in both cases
+    /// (lowercase with lowercase, uppercase with uppercase), alongside the digits and punctuation on
+    /// the four main rows.
+    ///
+    /// ```
+    /// use fuzzy_aho_corasick::{KeyboardLayout, Similarity};
+    ///
+    /// let qwerty = Similarity::keyboard(KeyboardLayout::Qwerty);
+    /// assert!(qwerty.get('e', 'w') > qwerty.get('e', 'c')); // neighbours > same finger
+    /// assert!(qwerty.get('e', 'c') > qwerty.get('e', 'p')); // same finger > unrelated
+    /// ```
+    #[must_use]
+    pub fn keyboard(layout: KeyboardLayout) -> Self {
+        // (char, row, column)
+        let keys: Vec<(char, usize, usize)> = layout
+            .rows()
+            .iter()
+            .enumerate()
+            .flat_map(|(row, chars)| {
+                chars
+                    .chars()
+                    .enumerate()
+                    .map(move |(col, ch)| (ch, row, col))
+            })
+            .collect();
+        let x = |row: usize, col: usize| col as f32 + ROW_OFFSETS[row];
+
+        let mut pairs = Vec::new();
+        for &(a, row_a, col_a) in &keys {
+            for &(b, row_b, col_b) in &keys {
+                if a == b {
+                    continue;
+                }
+                let sim = if row_a.abs_diff(row_b) <= 1
+                    && (x(row_a, col_a) - x(row_b, col_b)).abs() <= 1.0
+                {
+                    ADJACENT_KEY_SIMILARITY
+                } else if finger(col_a) == finger(col_b) {
+                    SAME_FINGER_SIMILARITY
+                } else {
+                    continue;
+                };
+                pairs.push(((a, b), sim));
+                // The shifted letters sit on the same keys.
+                if let (Some(upper_a), Some(upper_b)) = (uppercase(a), uppercase(b)) {
+                    pairs.push(((upper_a, upper_b), sim));
+                }
+            }
+        }
+        Similarity::from_map(pairs)
+    }
+}
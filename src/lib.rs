@@ -44,16 +44,38 @@
 //! only re-searches those with the full engine. Results are identical to [`FuzzyAhoCorasick::search`];
 //! it falls back to a plain full search when the configuration can't be reduced to the bit model.
 //!
+//! # Precompiled automata
+//! [`FuzzyAhoCorasick::to_bytes`] saves a built engine to a versioned, checksummed byte buffer and
+//! [`FuzzyAhoCorasick::from_bytes`] loads it back without re-running construction, so large
+//! dictionaries can be compiled once and shipped. [`FuzzyAhoCorasick::from_shared_bytes`] instead
+//! searches the serialized trie in place — e.g. straight from a memory-mapped file shared by many
+//! processes.
+//!
 //! See the [README](https://github.com/kakserpom/fuzzy-aho-corasick-rs) for a full guide.
+mod alignment;
+mod batch;
 mod builder;
+mod cost;
 mod error;
+mod find_iter;
 mod grapheme;
+mod keyboard;
 mod matches;
+mod ocr;
 mod options;
+mod parallel;
+mod pattern_list;
+mod payload;
+mod phonetic;
 mod prefilter;
 mod query;
 mod replacer;
 mod search;
+mod searcher;
+#[cfg(feature = "serde")]
+mod serde_support;
+mod serialize;
+mod snippet;
 mod stream;
 /// The crate's public data types (patterns, limits, penalties, matches, segments, …). Everything
 /// here is also re-exported at the crate root, so `use fuzzy_aho_corasick::Pattern` and
@@ -61,6 +83,7 @@ mod stream;
 pub mod structs;
 #[cfg(test)]
 mod tests;
+mod trie;
 
 // Compile-check every example in the mdBook guide as a doctest, so the docs can't drift from the
 // API. `#[cfg(doctest)]` keeps these out of the generated docs and normal builds; they run under
@@ -84,20 +107,40 @@ mod book_doctests {
     chapter!(search, "../book/src/searching/search.md");
     chapter!(replacement, "../book/src/searching/replacement.md");
     chapter!(segmentation, "../book/src/searching/segmentation.md");
+    chapter!(snippets, "../book/src/searching/snippets.md");
     chapter!(custom, "../book/src/similarity/custom.md");
+    chapter!(
+        substitution_cost,
+        "../book/src/similarity/substitution-cost.md"
+    );
     chapter!(floor, "../book/src/similarity/floor.md");
     chapter!(mappings, "../book/src/similarity/mappings.md");
+    chapter!(ocr, "../book/src/similarity/ocr.md");
+    chapter!(phonetic, "../book/src/similarity/phonetic.md");
     chapter!(bounding, "../book/src/performance/bounding.md");
     chapter!(prefilter, "../book/src/performance/prefilter.md");
+    chapter!(serialization, "../book/src/performance/serialization.md");
+    chapter!(tips, "../book/src/performance/tips.md");
     chapter!(stream_search, "../book/src/streaming/search.md");
     chapter!(stream_replace, "../book/src/streaming/replace.md");
 }
 
+pub use alignment::EditOp;
 pub use builder::FuzzyAhoCorasickBuilder;
-pub use error::SearchError;
-pub use options::{DEFAULT_THRESHOLD, Order, Overlap, SearchOptions};
+pub use cost::SubstitutionCost;
+pub use error::{DeserializeError, PatternListError, PatternListErrorKind, SearchError};
+pub use find_iter::FindIter;
+pub use keyboard::{ADJACENT_KEY_SIMILARITY, KeyboardLayout, SAME_FINGER_SIMILARITY};
+pub use ocr::{OCR_CONFUSIONS, OCR_MAPPINGS};
+pub use options::{
+    Anchored, Boundary, DEFAULT_THRESHOLD, MatchKind, Order, Overlap, SearchOptions,
+};
+pub use payload::PayloadAhoCorasick;
+pub use phonetic::{DEFAULT_PHONETIC_SIMILARITY, PhoneticAlgorithm};
 pub use prefilter::Prefiltered;
 pub use replacer::FuzzyReplacer;
+pub use searcher::Searcher;
+pub use snippet::{Markup, Snippet, SnippetOptions};
 pub use stream::{StreamMatch, StreamMatches};
 /// Index of a pattern within the automaton's pattern list — the `pattern_index` on a
 /// [`FuzzyMatch`], and the position of a pattern in the slice passed to `build`.
@@ -1,6 +1,81 @@
-use crate::{FuzzyMatch, FuzzyMatches, Order, Overlap, Segment, UniqueId, UnmatchedSegment};
+use crate::{
+    FuzzyAhoCorasick, FuzzyMatch, FuzzyMatches, MatchKind, Order, Overlap, OwnedFuzzyMatch,
+    SearchOptions, Segment, UniqueId, UnmatchedSegment,
+};
 use std::borrow::Cow;
+use std::cmp::Ordering;
 use std::collections::{BTreeSet, VecDeque};
+
+impl FuzzyMatch<'_> {
+    /// The match's pattern identity for pattern-unique overlap resolution: its
+    /// `custom_unique_id`, else its index.
+    pub(crate) fn unique_id(&self) -> UniqueId {
+        match self.pattern.custom_unique_id {
+            Some(custom_unique_id) => UniqueId::Custom(custom_unique_id),
+            None => UniqueId::Automatic(self.pattern_index),
+        }
+    }
+
+    /// An [`OwnedFuzzyMatch`] copy of this match, borrowing neither the engine nor the haystack.
+    #[must_use]
+    pub fn into_owned(self) -> OwnedFuzzyMatch {
+        self.into()
+    }
+}
+
+impl OwnedFuzzyMatch {
+    /// Re-attach the match to the `engine` and `haystack` it was found with, giving back the
+    /// borrowed [`FuzzyMatch`] — e.g. to render it with [`FuzzyMatches::snippets`].
+    ///
+    /// Returns `None` when the match can't belong to them: its `pattern_index` is out of range
+    /// for `engine`, or `haystack[start..end]` isn't its `text`.
+    #[must_use]
+    pub fn to_match<'a>(
+        &self,
+        engine: &'a FuzzyAhoCorasick,
+        haystack: &'a str,
+    ) -> Option<FuzzyMatch<'a>> {
+        let pattern = engine.patterns.get(self.pattern_index)?;
+        let text = haystack.get(self.start..self.end)?;
+        if text != self.text {
+            return None;
+        }
+        Some(FuzzyMatch {
+            insertions: self.insertions,
+            deletions: self.deletions,
+            substitutions: self.substitutions,
+            swaps: self.swaps,
+            edits: self.edits,
+            pattern_index: self.pattern_index,
+            pattern,
+            start: self.start,
+            end: self.end,
+            similarity: self.similarity,
+            text,
+            alignment: self.alignment.clone(),
+        })
+    }
+}
+
+/// Preference between two matches starting at the same position under a leftmost `kind`; the
+/// preferred one sorts first. `(end, pattern_index)` identifies a match at a fixed start, so the
+/// order is total.
+fn leftmost_cmp(kind: MatchKind, left: &FuzzyMatch, right: &FuzzyMatch) -> Ordering {
+    let by_similarity = right.similarity.total_cmp(&left.similarity);
+    match kind {
+        MatchKind::LeftmostLongest => right
+            .end
+            .cmp(&left.end)
+            .then(by_similarity)
+            .then_with(|| left.pattern_index.cmp(&right.pattern_index)),
+        MatchKind::Ranked | MatchKind::LeftmostFirst => left
+            .pattern_index
+            .cmp(&right.pattern_index)
+            .then(by_similarity)
+            .then_with(|| right.end.cmp(&left.end)),
+    }
+}
+
 impl<'a> FuzzyMatches<'a> {
     /// Apply a ranking `order` then an overlap `resolution` in place. Used by the options-driven
     /// [`FuzzyAhoCorasick::search`](crate::FuzzyAhoCorasick::search).
@@ -15,9 +90,76 @@ impl<'a> FuzzyMatches<'a> {
             Overlap::Keep => {}
             Overlap::NonOverlapping => self.non_overlapping(),
             Overlap::NonOverlappingUnique => self.non_overlapping_unique(),
+            Overlap::Optimal => self.non_overlapping_optimal(),
+        }
+    }
+
+    /// Select per `opts`: its order and overlap resolution, or the leftmost scan of its
+    /// [`MatchKind`].
+    pub(crate) fn apply_options(&mut self, opts: &SearchOptions) {
+        match opts.match_kind {
+            MatchKind::Ranked => self.apply(opts.order, opts.overlap),
+            MatchKind::LeftmostFirst => self.leftmost_first(),
+            MatchKind::LeftmostLongest => self.leftmost_longest(),
         }
     }
 
+    /// Rank matches that all start at the same position, best first: by the leftmost
+    /// [`MatchKind`]'s preference, else by `opts.order` ([`Order::Default`] when
+    /// [`Order::Unsorted`]).
+    pub(crate) fn rank_at_start(&mut self, opts: &SearchOptions) {
+        match opts.match_kind {
+            MatchKind::Ranked if opts.order == Order::Unsorted => self.default_sort(),
+            MatchKind::Ranked => self.apply(opts.order, Overlap::Keep),
+            kind => self
+                .inner
+                .sort_unstable_by(|left, right| leftmost_cmp(kind, left, right)),
+        }
+    }
+
+    /// The best of the matches starting leftmost, per [`rank_at_start`](Self::rank_at_start). Used
+    /// by the early-exit `find_first` entry points.
+    pub(crate) fn into_first(mut self, opts: &SearchOptions) -> Option<FuzzyMatch<'a>> {
+        let start = self.inner.iter().map(|m| m.start).min()?;
+        self.inner.retain(|m| m.start == start);
+        self.rank_at_start(opts);
+        self.inner.into_iter().next()
+    }
+
+    /// Leftmost-first selection ([`MatchKind::LeftmostFirst`]): scanning left to right, keep the
+    /// earliest-starting match whose pattern was given first, then continue after its end. The
+    /// result is non-overlapping and sorted by position.
+    pub fn leftmost_first(&mut self) {
+        self.select_leftmost(MatchKind::LeftmostFirst);
+    }
+
+    /// Leftmost-longest selection ([`MatchKind::LeftmostLongest`]): scanning left to right, keep the
+    /// longest of the earliest-starting matches, then continue after its end. The result is
+    /// non-overlapping and sorted by position.
+    pub fn leftmost_longest(&mut self) {
+        self.select_leftmost(MatchKind::LeftmostLongest);
+    }
+
+    fn select_leftmost(&mut self, kind: MatchKind) {
+        self.inner.sort_unstable_by(|left, right| {
+            left.start
+                .cmp(&right.start)
+                .then_with(|| leftmost_cmp(kind, left, right))
+        });
+        let mut resume = 0;
+        let mut taken_start = None;
+        self.inner.retain(|m| {
+            // The first of each start position is its preferred match; an empty span still
+            // claims its position.
+            if m.start < resume || taken_start == Some(m.start) {
+                return false;
+            }
+            taken_start = Some(m.start);
+            resume = m.end;
+            true
+        });
+    }
+
     /// Default ranking: prefers higher similarity, then longer pattern, then
     /// longer matched text, then earlier occurrence.
     #[inline]
@@ -117,11 +259,7 @@ impl<'a> FuzzyMatches<'a> {
         let mut used_patterns = BTreeSet::new();
         let mut occupied: Vec<(usize, usize)> = Vec::with_capacity(self.inner.len());
         self.inner.retain(|m| {
-            let unique_id = if let Some(custom_unique_id) = m.pattern.custom_unique_id {
-                UniqueId::Custom(custom_unique_id)
-            } else {
-                UniqueId::Automatic(m.pattern_index)
-            };
+            let unique_id = m.unique_id();
             if used_patterns.contains(&unique_id) {
                 #[cfg(test)]
                 println!("DISCARDING OVERLAPPING: {m:?}");
@@ -148,6 +286,66 @@ impl<'a> FuzzyMatches<'a> {
         self.inner.sort_unstable_by_key(|m| m.start);
     }
 
+    /// Retain the non-overlapping subset with the highest total `similarity * pattern.grapheme_len`,
+    /// the scoring behind [`Overlap::Optimal`]. The kept matches are sorted by `start`.
+    pub fn non_overlapping_optimal(&mut self) {
+        self.non_overlapping_optimal_by(|m| m.similarity * m.pattern.grapheme_len as f32);
+    }
+
+    /// Retain the non-overlapping subset maximizing the sum of `objective` over the kept matches,
+    /// by weighted interval scheduling in `O(n log n)`. The current order is irrelevant; matches
+    /// scoring zero or less (or NaN) are never kept, and among equally good subsets the one
+    /// ending earliest wins. The kept matches are finally sorted by `start`.
+    pub fn non_overlapping_optimal_by<F>(&mut self, objective: F)
+    where
+        F: Fn(&FuzzyMatch<'a>) -> f32,
+    {
+        let mut scored: Vec<(FuzzyMatch<'a>, f64)> = std::mem::take(&mut self.inner)
+            .into_iter()
+            .map(|m| {
+                let score = f64::from(objective(&m));
+                (m, score)
+            })
+            .collect();
+        // `(end, start, pattern_index)` identifies a match, so the DP is deterministic.
+        scored.sort_unstable_by(|(left, _), (right, _)| {
+            (left.end, left.start, left.pattern_index).cmp(&(
+                right.end,
+                right.start,
+                right.pattern_index,
+            ))
+        });
+
+        // `best[i]`: the highest total over the first `i` matches. Taking match `i` leaves the
+        // ones ending by its start — a prefix, `compatible[i]` long, as ends are ascending.
+        let mut best = vec![0.0_f64; scored.len() + 1];
+        let mut compatible = vec![0; scored.len()];
+        let mut taken = vec![false; scored.len()];
+        for (i, (m, score)) in scored.iter().enumerate() {
+            compatible[i] = scored[..i].partition_point(|(prev, _)| prev.end <= m.start);
+            let with = best[compatible[i]] + score;
+            taken[i] = *score > 0.0 && with > best[i];
+            best[i + 1] = if taken[i] { with } else { best[i] };
+        }
+
+        let mut keep = vec![false; scored.len()];
+        let mut i = scored.len();
+        while i > 0 {
+            if taken[i - 1] {
+                keep[i - 1] = true;
+                i = compatible[i - 1];
+            } else {
+                i -= 1;
+            }
+        }
+        self.inner = scored
+            .into_iter()
+            .zip(keep)
+            .filter_map(|((m, _), keep)| keep.then_some(m))
+            .collect();
+        self.inner.sort_unstable_by_key(|m| m.start);
+    }
+
     /// Performs a **fuzzy** find-and-replace using the current match list.
     /// You may return either a borrowed `&str` or an owned `String` from your callback.
     ///
@@ -388,6 +586,50 @@ impl<'a> FuzzyMatches<'a> {
         self.inner.is_empty()
     }
 
+    /// Detach the matches from the engine and the haystack, e.g. to return them from a function
+    /// that owns the haystack or to send them to another thread. Bring them back with
+    /// [`from_owned`](Self::from_owned).
+    ///
+    /// ```
+    /// use fuzzy_aho_corasick::{
+    ///     FuzzyAhoCorasick, FuzzyAhoCorasickBuilder, FuzzyLimits, OwnedFuzzyMatch, SearchOptions,
+    /// };
+    ///
+    /// fn find(engine: &FuzzyAhoCorasick, line: &[u8]) -> Vec<OwnedFuzzyMatch> {
+    ///     let haystack = String::from_utf8_lossy(line).into_owned();
+    ///     let opts = SearchOptions::new().threshold(0.8).sorted();
+    ///     engine.search(&haystack, &opts).unwrap().into_owned()
+    /// }
+    ///
+    /// let engine = FuzzyAhoCorasickBuilder::new()
+    ///     .fuzzy(FuzzyLimits::new().edits(1))
+    ///     .build(["hello"]);
+    /// let found = find(&engine, b"say helo");
+    /// assert_eq!((found[0].start, found[0].text.as_str()), (4, "helo"));
+    /// ```
+    #[must_use]
+    pub fn into_owned(self) -> Vec<OwnedFuzzyMatch> {
+        self.inner.into_iter().map(OwnedFuzzyMatch::from).collect()
+    }
+
+    /// Re-attach matches detached with [`into_owned`](Self::into_owned) to the `engine` and
+    /// `haystack` they were found with, so the collection methods (segmentation, replacement,
+    /// snippets, …) work on them again.
+    ///
+    /// Returns `None` if any match doesn't belong to them (see [`OwnedFuzzyMatch::to_match`]).
+    #[must_use]
+    pub fn from_owned<'m>(
+        engine: &'a FuzzyAhoCorasick,
+        haystack: &'a str,
+        matches: impl IntoIterator<Item = &'m OwnedFuzzyMatch>,
+    ) -> Option<Self> {
+        let inner = matches
+            .into_iter()
+            .map(|m| m.to_match(engine, haystack))
+            .collect::<Option<_>>()?;
+        Some(Self { haystack, inner })
+    }
+
     /// Retains only the fuzzy matches for which the predicate returns `true`,
     /// removing all others in place.
     ///
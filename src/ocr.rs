@@ -0,0 +1,124 @@
+//! A curated preset for matching OCR output: single-glyph confusions as a similarity table, and
+//! glyph sequences that scan as one another (`"rn"` ↔ `"m"`) as multi-character mappings. Installed
+//! together by [`FuzzyAhoCorasickBuilder::ocr_preset`](crate::FuzzyAhoCorasickBuilder::ocr_preset).
+
+use crate::structs::Similarity;
+use std::sync::{Arc, LazyLock};
+
+/// Single-glyph OCR confusions `(a, b, similarity)`, applied in both directions by
+/// [`Similarity::ocr`]. Scores reflect how often a recogniser mixes the two shapes up: `0.9` for
+/// near-identical glyphs, down to `0.5` for shapes that only collide in poor scans.
+pub const OCR_CONFUSIONS: &[(char, char, f32)] = &[
+    // Digits and letters.
+    ('0', 'O', 0.9),
+    ('0', 'o', 0.8),
+    ('0', 'D', 0.5),
+    ('O', 'D', 0.5),
+    ('O', 'Q', 0.6),
+    ('1', 'l', 0.9),
+    ('1', 'I', 0.9),
+    ('1', 'i', 0.6),
+    ('1', '7', 0.5),
+    ('l', 'I', 0.9),
+    ('l', 'i', 0.6),
+    ('I', 'i', 0.6),
+    ('|', '1', 0.8),
+    ('|', 'l', 0.8),
+    ('|', 'I', 0.8),
+    ('!', 'l', 0.5),
+    ('!', 'i', 0.6),
+    ('2', 'Z', 0.7),
+    ('2', 'z', 0.6),
+    ('5', 'S', 0.8),
+    ('5', 's', 0.7),
+    ('6', 'b', 0.6),
+    ('6', 'G', 0.6),
+    ('8', 'B', 0.7),
+    ('9', 'g', 0.6),
+    ('9', 'q', 0.6),
+    ('4', 'A', 0.5),
+    ('7', 'T', 0.5),
+    // Lowercase shapes.
+    ('c', 'e', 0.6),
+    ('a', 'o', 0.5),
+    ('e', 'o', 0.5),
+    ('c', 'o', 0.5),
+    ('n', 'h', 0.6),
+    ('n', 'r', 0.5),
+    ('n', 'u', 0.5),
+    ('m', 'n', 0.5),
+    ('u', 'v', 0.6),
+    ('v', 'y', 0.5),
+    ('i', 'j', 0.6),
+    ('f', 't', 0.6),
+    ('g', 'q', 0.6),
+    ('b', 'h', 0.5),
+    // Uppercase shapes.
+    ('C', 'G', 0.6),
+    ('E', 'F', 0.6),
+    ('P', 'R', 0.6),
+    ('U', 'V', 0.6),
+    ('V', 'Y', 0.5),
+    // Letters whose two cases share a shape.
+    ('c', 'C', 0.8),
+    ('o', 'O', 0.8),
+    ('s', 'S', 0.8),
+    ('u', 'U', 0.8),
+    ('v', 'V', 0.8),
+    ('w', 'W', 0.8),
+    ('x', 'X', 0.8),
+    ('z', 'Z', 0.8),
+    // Punctuation.
+    ('.', ',', 0.7),
+    (':', ';', 0.7),
+    ('\'', '`', 0.7),
+    ('\'', '’', 0.8),
+    ('"', '”', 0.8),
+    ('-', '–', 0.8),
+];
+
+/// Multi-glyph OCR confusions `(a, b, score)`, registered as
+/// [`mapping_scored`](crate::FuzzyAhoCorasickBuilder::mapping_scored) rules (so either side may
+/// stand in for the other, as one substitution costing `substitution * (1 - score)`). Ligatures
+/// the recogniser emits as a single code point map to their letters at `1.0`.
+pub const OCR_MAPPINGS: &[(&str, &str, f32)] = &[
+    ("m", "rn", 0.9),
+    ("w", "vv", 0.9),
+    ("W", "VV", 0.9),
+    ("d", "cl", 0.85),
+    ("m", "nn", 0.7),
+    ("n", "ri", 0.7),
+    ("h", "li", 0.7),
+    ("k", "lc", 0.7),
+    ("u", "ii", 0.6),
+    ("fi", "ﬁ", 1.0),
+    ("fl", "ﬂ", 1.0),
+    ("ff", "ﬀ", 1.0),
+    ("ffi", "ﬃ", 1.0),
+    ("ffl", "ﬄ", 1.0),
+];
+
+impl Similarity {
+    /// A similarity table of the single-glyph OCR confusions in [`OCR_CONFUSIONS`], in both
+    /// directions; every other substitution scores `0.0`.
+    ///
+    /// ```
+    /// use fuzzy_aho_corasick::Similarity;
+    ///
+    /// let ocr = Similarity::ocr();
+    /// assert_eq!(ocr.get('0', 'O'), ocr.get('O', '0'));
+    /// assert!(ocr.get('1', 'l') > ocr.get('a', 'o'));
+    /// ```
+    #[must_use]
+    pub fn ocr() -> Self {
+        Similarity::from_map(
+            OCR_CONFUSIONS
+                .iter()
+                .flat_map(|&(a, b, sim)| [((a, b), sim), ((b, a), sim)]),
+        )
+    }
+}
+
+/// The shared [`Similarity::ocr`] table, built once.
+pub(crate) static OCR_SIMILARITY: LazyLock<Arc<Similarity>> =
+    LazyLock::new(|| Arc::new(Similarity::ocr()));
@@ -1,5 +1,7 @@
 //! Options controlling a [`search`](crate::FuzzyAhoCorasick::search) / related call: the similarity
-//! threshold, how matches are ranked, and how overlaps are resolved.
+//! threshold, how matches are ranked, how overlaps are resolved, and where matches may start and end.
+
+use unicode_segmentation::UnicodeSegmentation;
 
 /// Default similarity threshold used when [`SearchOptions`] doesn't set one. `0.0` keeps every match
 /// the configured edit limits allow — the limits are the real quality gate, so an unset threshold
@@ -8,6 +10,7 @@ pub const DEFAULT_THRESHOLD: f32 = 0.0;
 
 /// How the raw matches are ranked before they're returned (and before overlap resolution).
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Order {
     /// No ranking — the raw best-per-span matches in no particular order (fastest).
     #[default]
@@ -22,6 +25,7 @@ pub enum Order {
 
 /// How overlapping matches are resolved after ranking.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Overlap {
     /// Keep every match, including ones whose spans overlap.
     #[default]
@@ -31,17 +35,156 @@ pub enum Overlap {
     /// Like [`NonOverlapping`](Overlap::NonOverlapping), and additionally at most one match per
     /// pattern identity (its `custom_unique_id`, else its index).
     NonOverlappingUnique,
+    /// Keep the non-overlapping subset with the highest total `similarity × pattern length` (in
+    /// graphemes), ignoring the order: two good adjacent matches beat one slightly better match
+    /// spanning both, which the greedy [`NonOverlapping`](Overlap::NonOverlapping) would keep
+    /// instead. See
+    /// [`FuzzyMatches::non_overlapping_optimal_by`](crate::FuzzyMatches::non_overlapping_optimal_by)
+    /// for a caller-provided objective.
+    Optimal,
+}
+
+/// How matches are selected: ranked globally, or scanned left to right like the `aho-corasick`
+/// crate's leftmost match kinds.
+///
+/// The leftmost kinds take the earliest-starting match, resume after its end, and repeat, so the
+/// result never overlaps and comes back in position order; they replace [`Order`] and [`Overlap`],
+/// which are not consulted. On an engine without fuzzy limits (exact matching) they agree with
+/// `aho-corasick`'s `MatchKind::LeftmostFirst` / `LeftmostLongest` on the same patterns.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MatchKind {
+    /// Rank every match by [`SearchOptions::order`], then resolve overlaps by
+    /// [`SearchOptions::overlap`].
+    #[default]
+    Ranked,
+    /// Among the matches starting leftmost, the one whose pattern was given first (lowest pattern
+    /// index); several spans of that pattern are ranked by similarity, then length.
+    LeftmostFirst,
+    /// Among the matches starting leftmost, the longest span; ties go to the higher similarity,
+    /// then the pattern given first. Note that a fuzzy span can grow by insertions, so pair this
+    /// with a threshold that keeps padded matches out.
+    LeftmostLongest,
+}
+
+/// Where a match may start and end. Enforced inside the search, so a match that would start or end
+/// mid-word — fuzzy ones included, e.g. when an insertion would carry it into the next word — is
+/// never produced, rather than filtered out afterwards.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Boundary {
+    /// Anywhere: `"cat"` matches inside `"concatenate"`.
+    #[default]
+    None,
+    /// At Unicode word boundaries (UAX #29): `"can't"` and `"3.14"` are single words, and every
+    /// ideograph is a word of its own.
+    Word,
+    /// Where a letter or digit meets anything else (whitespace, punctuation, symbols) or the ends of
+    /// the haystack: `"can"` matches in `"can't"`.
+    Separator,
+}
+
+/// Whether matches must touch the ends of the haystack. Enforced inside the search: a start anchor
+/// runs a single start window instead of one per grapheme, and edits at an anchored edge count
+/// towards the match (`"cat"` anchored at both ends matches all of `"cats"`, with one insertion).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Anchored {
+    /// Matches may start and end anywhere.
+    #[default]
+    No,
+    /// Matches must start at the beginning of the haystack.
+    Start,
+    /// Matches must end at the end of the haystack.
+    End,
+    /// Matches must span the whole haystack — a fuzzy whole-string lookup.
+    Both,
+}
+
+impl Anchored {
+    /// Whether matches must start at the beginning of the haystack.
+    pub(crate) const fn start(self) -> bool {
+        matches!(self, Anchored::Start | Anchored::Both)
+    }
+
+    /// Whether matches must end at the end of the haystack.
+    pub(crate) const fn end(self) -> bool {
+        matches!(self, Anchored::End | Anchored::Both)
+    }
+}
+
+/// The [`Boundary`] and [`Anchored`] restrictions of one search, resolved against its haystack and
+/// threaded down to the automaton walk, along with how much of the haystack the search must cover.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct SpanConstraints<'b> {
+    /// Per-byte flags from [`Boundary::byte_flags`], or `None` when a match may start and end
+    /// anywhere.
+    pub(crate) boundaries: Option<&'b [bool]>,
+    /// Which ends of the haystack matches must touch.
+    pub(crate) anchored: Anchored,
+    /// Stop after the first start window that yields a match: only the leftmost matches are
+    /// wanted (early-exit entry points such as `is_match`).
+    pub(crate) leftmost_only: bool,
+    /// Grapheme index of the first start window; matches starting earlier aren't searched for.
+    pub(crate) first_window: usize,
+    /// Trace each match's path through the automaton into its [`FuzzyMatch::alignment`].
+    ///
+    /// [`FuzzyMatch::alignment`]: crate::FuzzyMatch::alignment
+    pub(crate) alignment: bool,
+}
+
+impl Boundary {
+    /// Whether a match may start or end at each byte offset of `haystack` (`haystack.len() + 1`
+    /// entries; only grapheme starts are meaningful), or `None` when every offset is allowed.
+    pub(crate) fn byte_flags(self, haystack: &str) -> Option<Vec<bool>> {
+        let mut flags = Vec::new();
+        self.fill_byte_flags(haystack, &mut flags).then_some(flags)
+    }
+
+    /// [`byte_flags`](Self::byte_flags) into a reused buffer; returns whether `flags` was filled
+    /// (`false` when every offset is allowed, leaving it untouched).
+    pub(crate) fn fill_byte_flags(self, haystack: &str, flags: &mut Vec<bool>) -> bool {
+        if self == Boundary::None {
+            return false;
+        }
+        flags.clear();
+        flags.resize(haystack.len() + 1, false);
+        match self {
+            Boundary::None => {}
+            Boundary::Word => {
+                for (i, _) in haystack.split_word_bound_indices() {
+                    flags[i] = true;
+                }
+            }
+            Boundary::Separator => {
+                let is_word = |g: &str| g.chars().next().is_some_and(char::is_alphanumeric);
+                let mut prev_word = false;
+                for (i, g) in haystack.grapheme_indices(true) {
+                    let word = is_word(g);
+                    flags[i] = i == 0 || !prev_word || !word;
+                    prev_word = word;
+                }
+            }
+        }
+        flags[0] = true;
+        flags[haystack.len()] = true;
+        true
+    }
 }
 
-/// Configuration for a search: the similarity `threshold`, the ranking `order`, and the `overlap`
-/// resolution. Construct with [`SearchOptions::new`] (all defaults) and refine with the chainable
-/// setters; or build one literally.
+/// Configuration for a search: the similarity `threshold`, the ranking `order`, the `overlap`
+/// resolution (or a leftmost `match_kind` in their place), and where matches may lie (`boundary`,
+/// `anchored`). Construct with
+/// [`SearchOptions::new`] (all defaults) and refine with the chainable setters; or build one
+/// literally.
 ///
 /// ```
 /// use fuzzy_aho_corasick::SearchOptions;
 /// let opts = SearchOptions::new().threshold(0.8).greedy().non_overlapping_unique();
 /// ```
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct SearchOptions {
     /// Minimum similarity a match must reach to be kept (`0.0..=1.0`). Defaults to
     /// [`DEFAULT_THRESHOLD`].
@@ -50,6 +193,14 @@ pub struct SearchOptions {
     pub order: Order,
     /// How overlaps are resolved.
     pub overlap: Overlap,
+    /// Where matches may start and end.
+    pub boundary: Boundary,
+    /// Which ends of the haystack matches must touch.
+    pub anchored: Anchored,
+    /// Ranked selection, or a leftmost scan.
+    pub match_kind: MatchKind,
+    /// Whether to report each match's [`alignment`](crate::FuzzyMatch::alignment).
+    pub alignment: bool,
 }
 
 impl Default for SearchOptions {
@@ -58,12 +209,17 @@ impl Default for SearchOptions {
             threshold: DEFAULT_THRESHOLD,
             order: Order::Unsorted,
             overlap: Overlap::Keep,
+            boundary: Boundary::None,
+            anchored: Anchored::No,
+            match_kind: MatchKind::Ranked,
+            alignment: false,
         }
     }
 }
 
 impl SearchOptions {
-    /// All defaults: [`DEFAULT_THRESHOLD`], [`Order::Unsorted`], [`Overlap::Keep`].
+    /// All defaults: [`DEFAULT_THRESHOLD`], [`Order::Unsorted`], [`Overlap::Keep`],
+    /// [`Boundary::None`], [`Anchored::No`], [`MatchKind::Ranked`], no alignment.
     ///
     /// `const`, so options can be built once as a `const`/`static`:
     /// ```
@@ -76,6 +232,10 @@ impl SearchOptions {
             threshold: DEFAULT_THRESHOLD,
             order: Order::Unsorted,
             overlap: Overlap::Keep,
+            boundary: Boundary::None,
+            anchored: Anchored::No,
+            match_kind: MatchKind::Ranked,
+            alignment: false,
         }
     }
 
@@ -100,6 +260,78 @@ impl SearchOptions {
         self
     }
 
+    /// Restrict where matches may start and end.
+    ///
+    /// ```
+    /// use fuzzy_aho_corasick::{Boundary, FuzzyAhoCorasickBuilder, SearchOptions};
+    /// let engine = FuzzyAhoCorasickBuilder::new().build(["cat"]);
+    /// let opts = SearchOptions::new().boundary(Boundary::Word);
+    /// assert!(engine.search("concatenate", &opts).unwrap().is_empty());
+    /// assert_eq!(engine.search("a cat.", &opts).unwrap().len(), 1);
+    /// ```
+    #[must_use]
+    pub const fn boundary(mut self, boundary: Boundary) -> Self {
+        self.boundary = boundary;
+        self
+    }
+
+    /// Require matches to touch the given ends of the haystack.
+    ///
+    /// ```
+    /// use fuzzy_aho_corasick::{Anchored, FuzzyAhoCorasickBuilder, FuzzyLimits, SearchOptions};
+    /// let engine = FuzzyAhoCorasickBuilder::new()
+    ///     .fuzzy(FuzzyLimits::new().edits(1))
+    ///     .build(["colour"]);
+    /// let opts = SearchOptions::new().anchored(Anchored::Both);
+    /// assert_eq!(engine.search("color", &opts).unwrap().len(), 1);
+    /// assert!(engine.search("colour chart", &opts).unwrap().is_empty());
+    /// ```
+    #[must_use]
+    pub const fn anchored(mut self, anchored: Anchored) -> Self {
+        self.anchored = anchored;
+        self
+    }
+
+    /// Set the match kind: [`MatchKind::Ranked`] (the default) or a leftmost scan.
+    ///
+    /// ```
+    /// use fuzzy_aho_corasick::{FuzzyAhoCorasickBuilder, MatchKind, SearchOptions};
+    /// let engine = FuzzyAhoCorasickBuilder::new().build(["Sam", "Samwise"]);
+    /// let found = |kind| {
+    ///     let opts = SearchOptions::new().match_kind(kind);
+    ///     engine.search("Samwise", &opts).unwrap().matched_strings()
+    /// };
+    /// assert_eq!(found(MatchKind::LeftmostFirst), ["Sam"]);
+    /// assert_eq!(found(MatchKind::LeftmostLongest), ["Samwise"]);
+    /// ```
+    #[must_use]
+    pub const fn match_kind(mut self, match_kind: MatchKind) -> Self {
+        self.match_kind = match_kind;
+        self
+    }
+
+    /// Report how each match lines up with its pattern, as a list of [`EditOp`](crate::EditOp)s in
+    /// [`FuzzyMatch::alignment`], reconstructed from the path the search took. Off by default:
+    /// tracing costs a record per explored state and runs the search without its fast paths.
+    ///
+    /// ```
+    /// use fuzzy_aho_corasick::{EditOp, FuzzyAhoCorasickBuilder, FuzzyLimits, SearchOptions};
+    /// let engine = FuzzyAhoCorasickBuilder::new()
+    ///     .fuzzy(FuzzyLimits::new().edits(1))
+    ///     .build(["receive"]);
+    /// let opts = SearchOptions::new().alignment(true);
+    /// let matches = engine.search("I recieve it", &opts).unwrap();
+    /// let ops = matches[0].alignment.as_ref().unwrap();
+    /// assert!(ops.contains(&EditOp::Swap { pattern: 3..5, text: 5..7 }));
+    /// ```
+    ///
+    /// [`FuzzyMatch::alignment`]: crate::FuzzyMatch::alignment
+    #[must_use]
+    pub const fn alignment(mut self, alignment: bool) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
     /// Shortcut for [`Order::Default`].
     #[must_use]
     pub const fn sorted(self) -> Self {
@@ -129,4 +361,22 @@ impl SearchOptions {
     pub const fn non_overlapping_unique(self) -> Self {
         self.overlap(Overlap::NonOverlappingUnique)
     }
+
+    /// Shortcut for [`Overlap::Optimal`].
+    ///
+    /// ```
+    /// use fuzzy_aho_corasick::{FuzzyAhoCorasickBuilder, SearchOptions};
+    /// let engine = FuzzyAhoCorasickBuilder::new().build(["new", "york", "newyo"]);
+    /// let text = |opts: &SearchOptions| -> Vec<&str> {
+    ///     let matches = engine.search("newyork", opts).unwrap();
+    ///     matches.iter().map(|m| m.text).collect()
+    /// };
+    /// // Greedy: the longest pattern first, which leaves no room for either word.
+    /// assert_eq!(text(&SearchOptions::new().sorted().non_overlapping()), ["newyo"]);
+    /// assert_eq!(text(&SearchOptions::new().non_overlapping_optimal()), ["new", "york"]);
+    /// ```
+    #[must_use]
+    pub const fn non_overlapping_optimal(self) -> Self {
+        self.overlap(Overlap::Optimal)
+    }
 }
@@ -0,0 +1,175 @@
+//! Parallel search of one in-memory haystack. The haystack is cut into overlapping windows the way
+//! the streaming search cuts a reader — each window carries the longest possible match as overlap
+//! and owns the matches starting before its commit boundary — but the windows borrow the haystack
+//! instead of copying it. Worker threads search the windows, and the matches they own are merged
+//! into a single [`FuzzyMatches`] before ranking and overlap resolution.
+
+use crate::options::SpanConstraints;
+use crate::search::Scratch;
+use crate::stream::{DEFAULT_WINDOW, SliceWindow, slice_windows};
+use crate::{Anchored, FuzzyAhoCorasick, FuzzyMatch, FuzzyMatches, SearchError, SearchOptions};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Smallest window [`search_parallel`](FuzzyAhoCorasick::search_parallel) cuts, so a short
+/// haystack isn't split into windows that are mostly overlap.
+const MIN_WINDOW: usize = 16 * 1024;
+
+/// Bytes before a window that its boundary flags are computed from, so a word cut by the window
+/// start isn't taken to begin there.
+const BOUNDARY_CONTEXT: usize = 256;
+
+impl FuzzyAhoCorasick {
+    /// Parallel [`search`](Self::search) of one large in-memory haystack (e.g. a memory-mapped
+    /// file): `threads` workers search overlapping windows of it, sharing this immutable engine,
+    /// and the result is ranked and overlap-resolved per [`opts`](SearchOptions) like `search`'s.
+    /// The windows borrow the haystack, so nothing is copied, and the matches borrow it as usual.
+    /// Uses `std::thread` only — no runtime dependencies.
+    ///
+    /// Windows overlap by the [longest possible match](Self::max_match_graphemes), so none is split,
+    /// and each match is taken from the one window that owns its start: the raw matches are the
+    /// same as `search`'s, though not in the same order. So the result equals `search`'s whenever
+    /// it doesn't depend on that order — a ranked [`Order`](crate::Order), overlap
+    /// [`Keep`](crate::Overlap::Keep) or [`Optimal`](crate::Overlap::Optimal), or a leftmost
+    /// [`MatchKind`](crate::MatchKind). Greedy overlap resolution of
+    /// [unsorted](crate::Order::Unsorted) matches may keep a different (still non-overlapping)
+    /// set. Also unlike `search`:
+    /// * An [anchored](SearchOptions::anchored) search isn't split: it runs on the calling thread.
+    /// * With an [`auto_beam`](crate::FuzzyAhoCorasickBuilder::auto_beam), the state budget applies
+    ///   to each window rather than to the whole haystack.
+    ///
+    /// `threads` is clamped to at least 1; pass [`std::thread::available_parallelism`] for "all
+    /// cores".
+    ///
+    /// ```
+    /// use fuzzy_aho_corasick::{FuzzyAhoCorasickBuilder, FuzzyLimits, SearchOptions};
+    /// let engine = FuzzyAhoCorasickBuilder::new()
+    ///     .fuzzy(FuzzyLimits::new().edits(1))
+    ///     .build(["error", "timeout"]);
+    /// let log = "GET /api ok\nPOST /api eror: timout\n".repeat(10_000);
+    /// let opts = SearchOptions::new().threshold(0.8).sorted().non_overlapping();
+    /// let matches = engine.search_parallel(&log, &opts, 4).unwrap();
+    /// assert_eq!(matches.inner, engine.search(&log, &opts).unwrap().inner);
+    /// assert_eq!(matches.len(), 20_000);
+    /// ```
+    ///
+    /// # Errors
+    /// Returns [`SearchError::HaystackTooLarge`] only for an anchored search of a haystack too
+    /// large for [`search`](Self::search); the windows of an unanchored one have no size limit.
+    ///
+    /// # Panics
+    /// Propagates a panic from a worker thread, re-raised on the calling thread.
+    pub fn search_parallel<'a>(
+        &'a self,
+        haystack: &'a str,
+        opts: &SearchOptions,
+        threads: usize,
+    ) -> Result<FuzzyMatches<'a>, SearchError> {
+        // A start anchor needs only the first window anyway; an end-anchored match may start
+        // anywhere yet must reach the haystack's end, which no window but the last can see.
+        if opts.anchored != Anchored::No {
+            return self.search_in(haystack, opts, &mut Scratch::default());
+        }
+        let threads = threads.max(1);
+        // Several windows per worker, so one that draws dense text doesn't hold up the rest.
+        let window = haystack
+            .len()
+            .div_ceil(threads * 4)
+            .clamp(MIN_WINDOW, DEFAULT_WINDOW);
+        let windows = slice_windows(haystack, window, self.stream_overlap());
+
+        let next = AtomicUsize::new(0);
+        let search_windows = || {
+            let mut scratch = Scratch::default();
+            let mut found = Vec::new();
+            loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some(window) = windows.get(i) else {
+                    break;
+                };
+                found.push((i, self.owned_matches(haystack, window, opts, &mut scratch)));
+            }
+            found
+        };
+        let workers = threads.min(windows.len());
+        let mut found: Vec<(usize, Vec<FuzzyMatch<'a>>)> = if workers == 1 {
+            search_windows()
+        } else {
+            std::thread::scope(|scope| {
+                let handles: Vec<_> = (0..workers).map(|_| scope.spawn(search_windows)).collect();
+                handles
+                    .into_iter()
+                    .flat_map(|handle| handle.join().expect("parallel search worker panicked"))
+                    .collect()
+            })
+        };
+        found.sort_unstable_by_key(|&(i, _)| i);
+        let mut matches = FuzzyMatches {
+            haystack,
+            inner: found.into_iter().flat_map(|(_, inner)| inner).collect(),
+        };
+
+        // Phonetic matches come from a word index rather than the automaton walk, so they are
+        // found over the whole haystack: a window start could cut a word in two.
+        if let Some(phonetic) = &self.phonetic {
+            let boundaries = opts.boundary.byte_flags(haystack);
+            let constraints = SpanConstraints {
+                boundaries: boundaries.as_deref(),
+                alignment: opts.alignment,
+                ..SpanConstraints::default()
+            };
+            phonetic.merge_into(
+                self,
+                haystack,
+                opts.threshold,
+                constraints,
+                &mut matches.inner,
+            );
+        }
+        matches.apply_options(opts);
+        Ok(matches)
+    }
+
+    /// The raw edit-distance matches of `window` that it owns, at haystack offsets.
+    #[allow(clippy::trivially_copy_pass_by_ref)] // uniform with the `&SearchOptions` public API
+    fn owned_matches<'a>(
+        &'a self,
+        haystack: &'a str,
+        window: &SliceWindow<'a>,
+        opts: &SearchOptions,
+        scratch: &mut Scratch,
+    ) -> Vec<FuzzyMatch<'a>> {
+        let mut context = window.base.saturating_sub(BOUNDARY_CONTEXT);
+        while !haystack.is_char_boundary(context) {
+            context -= 1;
+        }
+        let end = window.base + window.text.len();
+        let mut flags = std::mem::take(&mut scratch.boundaries);
+        let bounded = opts
+            .boundary
+            .fill_byte_flags(&haystack[context..end], &mut flags);
+        let constraints = SpanConstraints {
+            boundaries: bounded.then(|| &flags[window.base - context..]),
+            alignment: opts.alignment,
+            ..SpanConstraints::default()
+        };
+        // Windows are bounded by the window size (far below `u32::MAX` graphemes), so the size
+        // guard can never trip here.
+        let found = self
+            .edit_matches_in(window.text, opts.threshold, constraints, scratch)
+            .expect("a window fits the u32 position space");
+        scratch.boundaries = flags;
+        found
+            .inner
+            .into_iter()
+            .filter(|m| m.start < window.commit)
+            .map(|mut m| {
+                m.start += window.base;
+                m.end += window.base;
+                for op in m.alignment.iter_mut().flatten() {
+                    op.shift_text(window.base);
+                }
+                m
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,154 @@
+//! The pattern-list text format: one pattern per line with its per-pattern settings, so a
+//! dictionary can be kept in a plain file (or a spreadsheet exported as TSV) instead of in code.
+//!
+//! ```text
+//! # comment
+//! pattern<TAB>key=value<TAB>key=value…
+//! ```
+//!
+//! The options are `weight`, `id` (the [custom unique id](Pattern::custom_unique_id)), the limits
+//! `edits`, `insertions`, `deletions`, `substitutions` and `swaps` (any of them makes up the
+//! pattern's own [`FuzzyLimits`]), and `meta.NAME` for [metadata](Pattern::metadata). Every field
+//! decodes the escapes `\t`, `\n`, `\#` and `\\`.
+
+use crate::{FuzzyLimits, NumEdits, Pattern, PatternListError, PatternListErrorKind};
+
+impl Pattern {
+    /// Parse a pattern list: one pattern per line, optionally followed by tab-separated
+    /// `key=value` options.
+    ///
+    /// - Blank lines (empty or whitespace only) and lines starting with `#` are skipped; write `\#`
+    ///   for a pattern that starts with `#`.
+    /// - The pattern is everything before the first tab, spaces included.
+    /// - `weight=F` sets the [weight](Self::weight) and `id=N` the
+    ///   [custom unique id](Self::custom_unique_id).
+    /// - `edits=N`, `insertions=N`, `deletions=N`, `substitutions=N` and `swaps=N` set the
+    ///   pattern's own [`FuzzyLimits`], with the same meaning as the builder methods of those names.
+    ///   Patterns without any of them use the builder's global limits.
+    /// - `meta.NAME=VALUE` adds a [metadata](Self::metadata) entry.
+    /// - Every field decodes the escapes `\t` (tab), `\n` (line break), `\#` and `\\`. Empty
+    ///   fields (e.g. trailing tabs) are ignored.
+    ///
+    /// ```
+    /// use fuzzy_aho_corasick::{FuzzyAhoCorasickBuilder, FuzzyLimits, Pattern, SearchOptions};
+    ///
+    /// let patterns = Pattern::parse_list(
+    ///     "# product names\n\
+    ///      Wireless Mouse\tweight=1.2\tedits=2\tmeta.sku=WM-100\n\
+    ///      USB Hub\tid=7\n",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(patterns[0].weight, 1.2);
+    /// assert_eq!(patterns[0].metadata["sku"], "WM-100");
+    /// assert_eq!(patterns[1].custom_unique_id, Some(7));
+    ///
+    /// let engine = FuzzyAhoCorasickBuilder::new()
+    ///     .fuzzy(FuzzyLimits::new().edits(1))
+    ///     .build(patterns);
+    /// let found = engine
+    ///     .search("a Wireles Mouse", &SearchOptions::new().threshold(0.5))
+    ///     .unwrap();
+    /// let pattern = &engine.patterns()[found.inner[0].pattern_index];
+    /// assert_eq!(pattern.metadata["sku"], "WM-100");
+    /// ```
+    ///
+    /// # Errors
+    /// Returns a [`PatternListError`] naming the first line that doesn't follow the format: an
+    /// empty pattern, an option without `=`, an unknown or repeated option, a value that doesn't
+    /// parse, or an unknown escape.
+    pub fn parse_list(text: &str) -> Result<Vec<Pattern>, PatternListError> {
+        let mut patterns = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let pattern =
+                parse_line(line).map_err(|kind| PatternListError { line: i + 1, kind })?;
+            patterns.push(pattern);
+        }
+        Ok(patterns)
+    }
+}
+
+/// One non-comment line of a pattern list.
+fn parse_line(line: &str) -> Result<Pattern, PatternListErrorKind> {
+    let mut fields = line.split('\t');
+    let text = unescape(fields.next().unwrap_or_default())?;
+    if text.is_empty() {
+        return Err(PatternListErrorKind::EmptyPattern);
+    }
+    let mut pattern = Pattern::from(text);
+    let mut limits: Option<FuzzyLimits> = None;
+    let mut seen: Vec<String> = Vec::new();
+    for field in fields.filter(|field| !field.is_empty()) {
+        let (key, value) =
+            field
+                .split_once('=')
+                .ok_or_else(|| PatternListErrorKind::MissingValue {
+                    field: field.to_owned(),
+                })?;
+        let key = unescape(key)?;
+        let value = unescape(value)?;
+        if seen.contains(&key) {
+            return Err(PatternListErrorKind::DuplicateOption { key });
+        }
+        let invalid = || PatternListErrorKind::InvalidValue {
+            key: key.clone(),
+            value: value.clone(),
+        };
+        if let Some(name) = key.strip_prefix("meta.") {
+            pattern.metadata.insert(name.to_owned(), value.clone());
+        } else if key == "weight" {
+            pattern.weight = value
+                .parse::<f32>()
+                .ok()
+                .filter(|weight| weight.is_finite() && *weight >= 0.0)
+                .ok_or_else(invalid)?;
+        } else if key == "id" {
+            pattern.custom_unique_id = Some(value.parse().map_err(|_| invalid())?);
+        } else {
+            let setter: fn(FuzzyLimits, NumEdits) -> FuzzyLimits = match key.as_str() {
+                "edits" => FuzzyLimits::edits,
+                "insertions" => FuzzyLimits::insertions,
+                "deletions" => FuzzyLimits::deletions,
+                "substitutions" => FuzzyLimits::substitutions,
+                "swaps" => FuzzyLimits::swaps,
+                _ => return Err(PatternListErrorKind::UnknownOption { key }),
+            };
+            let num = value.parse().map_err(|_| invalid())?;
+            limits = Some(setter(limits.unwrap_or_default(), num));
+        }
+        seen.push(key);
+    }
+    if let Some(limits) = limits {
+        pattern = pattern.fuzzy(limits);
+    }
+    Ok(pattern)
+}
+
+/// Decode the `\t`, `\n`, `\#` and `\\` escapes of a field.
+fn unescape(field: &str) -> Result<String, PatternListErrorKind> {
+    if !field.contains('\\') {
+        return Ok(field.to_owned());
+    }
+    let mut out = String::with_capacity(field.len());
+    let mut chars = field.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => out.push('\t'),
+            Some('n') => out.push('\n'),
+            Some('#') => out.push('#'),
+            Some('\\') => out.push('\\'),
+            other => {
+                return Err(PatternListErrorKind::InvalidEscape {
+                    escape: other.map_or_else(|| "\\".to_owned(), |c| format!("\\{c}")),
+                });
+            }
+        }
+    }
+    Ok(out)
+}
@@ -0,0 +1,126 @@
+use crate::{FuzzyAhoCorasick, FuzzyMatch, SearchError, SearchOptions, StreamMatch};
+use std::io::{self, Read};
+
+/// A [`FuzzyAhoCorasick`] whose patterns each carry a caller-defined payload `P` — a category id, a
+/// canonical form, a replacement template, … — handed back alongside every match.
+///
+/// Pairs the automaton with a parallel list of payloads (one per pattern), so a match of pattern
+/// *i* comes with payload *i*. Build one with
+/// [`FuzzyAhoCorasickBuilder::build_with_payloads`](crate::FuzzyAhoCorasickBuilder::build_with_payloads).
+///
+/// ```
+/// use fuzzy_aho_corasick::{FuzzyAhoCorasickBuilder, FuzzyLimits, SearchOptions};
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Kind {
+///     Fruit,
+///     Vegetable,
+/// }
+///
+/// let engine = FuzzyAhoCorasickBuilder::new()
+///     .fuzzy(FuzzyLimits::new().edits(1))
+///     .build_with_payloads([("apple", Kind::Fruit), ("carrot", Kind::Vegetable)]);
+/// let opts = SearchOptions::new().threshold(0.8).sorted().non_overlapping();
+/// let found = engine.search("an aple and a carot", &opts).unwrap();
+/// let kinds: Vec<_> = found.iter().map(|(m, kind)| (m.text, *kind)).collect();
+/// assert_eq!(kinds, [("aple", &Kind::Fruit), ("carot", &Kind::Vegetable)]);
+/// ```
+#[derive(Debug)]
+pub struct PayloadAhoCorasick<P> {
+    pub(crate) engine: FuzzyAhoCorasick,
+    pub(crate) payloads: Vec<P>,
+}
+
+impl<P> PayloadAhoCorasick<P> {
+    /// [`FuzzyAhoCorasick::search`], pairing each match with its pattern's payload. The matches
+    /// keep the order and overlap resolution `opts` asks for.
+    ///
+    /// # Errors
+    /// Propagates [`SearchError`] when the haystack is too large to index — see
+    /// [`FuzzyAhoCorasick::search`].
+    pub fn search<'a>(
+        &'a self,
+        haystack: &'a str,
+        opts: &SearchOptions,
+    ) -> Result<Vec<(FuzzyMatch<'a>, &'a P)>, SearchError> {
+        Ok(self
+            .engine
+            .search(haystack, opts)?
+            .inner
+            .into_iter()
+            .map(|m| {
+                let payload = &self.payloads[m.pattern_index];
+                (m, payload)
+            })
+            .collect())
+    }
+
+    /// [`FuzzyAhoCorasick::search_stream`], passing each match's payload to `on_match` with it.
+    /// Returns the total number of bytes read from `reader`.
+    ///
+    /// # Errors
+    /// Propagates any [`io::Error`] from `reader`.
+    pub fn search_stream<R: Read>(
+        &self,
+        reader: R,
+        threshold: f32,
+        mut on_match: impl FnMut(StreamMatch, &P),
+    ) -> io::Result<u64> {
+        self.engine.search_stream(reader, threshold, |m| {
+            let payload = &self.payloads[m.pattern_index];
+            on_match(m, payload);
+        })
+    }
+
+    /// [`FuzzyAhoCorasick::search_stream_parallel`], passing each match's payload to `on_match`
+    /// with it. `on_match` runs on the calling thread, so `P` needn't be `Sync`.
+    ///
+    /// # Errors
+    /// Propagates any [`io::Error`] from `reader`.
+    ///
+    /// # Panics
+    /// Propagates a panic from a worker or the producer thread, as
+    /// [`FuzzyAhoCorasick::search_stream_parallel`] does.
+    pub fn search_stream_parallel<R: Read + Send>(
+        &self,
+        reader: R,
+        threshold: f32,
+        threads: usize,
+        mut on_match: impl FnMut(StreamMatch, &P),
+    ) -> io::Result<u64> {
+        self.engine
+            .search_stream_parallel(reader, threshold, threads, |m| {
+                let payload = &self.payloads[m.pattern_index];
+                on_match(m, payload);
+            })
+    }
+
+    /// The payload of the pattern at `pattern_index` — e.g. for a match obtained from
+    /// [`engine`](Self::engine) directly.
+    ///
+    /// # Panics
+    /// Panics if `pattern_index` is out of range (it can't be for a match of this engine).
+    #[must_use]
+    pub fn payload(&self, pattern_index: usize) -> &P {
+        &self.payloads[pattern_index]
+    }
+
+    /// Every payload, indexed like [`FuzzyAhoCorasick::patterns`].
+    #[must_use]
+    pub fn payloads(&self) -> &[P] {
+        &self.payloads
+    }
+
+    /// Borrow the underlying [`FuzzyAhoCorasick`], e.g. for the segmentation helpers or
+    /// [`to_bytes`](FuzzyAhoCorasick::to_bytes).
+    #[must_use]
+    pub fn engine(&self) -> &FuzzyAhoCorasick {
+        &self.engine
+    }
+
+    /// Split into the engine and its payloads.
+    #[must_use]
+    pub fn into_parts(self) -> (FuzzyAhoCorasick, Vec<P>) {
+        (self.engine, self.payloads)
+    }
+}
@@ -0,0 +1,546 @@
+//! Sound-alike matching: patterns are indexed by a phonetic encoding of their words, and haystack
+//! words with the same encoding are reported as matches alongside the edit-distance ones (see
+//! [`FuzzyAhoCorasickBuilder::phonetic`](crate::FuzzyAhoCorasickBuilder::phonetic)).
+//!
+//! Matching is word-level: a pattern of `n` words matches a run of `n` consecutive haystack words
+//! (Unicode word boundaries) whose codes equal the pattern words' codes, in order.
+
+use crate::options::SpanConstraints;
+use crate::structs::FxHashMap;
+use crate::{FuzzyAhoCorasick, FuzzyMatch, Pattern};
+use std::collections::VecDeque;
+use std::collections::hash_map::Entry;
+use unicode_segmentation::{UnicodeSegmentation, UnicodeWordIndices};
+
+/// Similarity of a phonetic match under [`phonetic`](crate::FuzzyAhoCorasickBuilder::phonetic),
+/// before the pattern weight is applied.
+pub const DEFAULT_PHONETIC_SIMILARITY: f32 = 0.8;
+
+/// A phonetic encoding for [`FuzzyAhoCorasickBuilder::phonetic`](crate::FuzzyAhoCorasickBuilder::phonetic).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PhoneticAlgorithm {
+    /// American Soundex: first letter plus three consonant-class digits (`"Smith"` → `S530`).
+    /// Coarse, and blind to a changed first letter.
+    Soundex,
+    /// Original Metaphone (Philips, 1990): English pronunciation rules (`"Smith"` → `SM0`).
+    Metaphone,
+    /// Kölner Phonetik, tuned for German names (`"Schmidt"` and `"Smith"` → `862`). Folds `ä`, `ö`,
+    /// `ü` and `ß`.
+    Cologne,
+}
+
+impl PhoneticAlgorithm {
+    /// Encode a single word, or `None` if it has no letters the algorithm encodes. Letters are
+    /// case-folded and everything else (digits, apostrophes, …) is skipped.
+    ///
+    /// ```
+    /// use fuzzy_aho_corasick::PhoneticAlgorithm;
+    ///
+    /// assert_eq!(PhoneticAlgorithm::Soundex.encode("Smyth").as_deref(), Some("S530"));
+    /// assert_eq!(PhoneticAlgorithm::Metaphone.encode("Smyth").as_deref(), Some("SM0"));
+    /// assert_eq!(PhoneticAlgorithm::Cologne.encode("Schmidt").as_deref(), Some("862"));
+    /// assert_eq!(PhoneticAlgorithm::Soundex.encode("42"), None);
+    /// ```
+    #[must_use]
+    pub fn encode(self, word: &str) -> Option<String> {
+        match self {
+            PhoneticAlgorithm::Soundex => soundex(word),
+            PhoneticAlgorithm::Metaphone => metaphone(word),
+            PhoneticAlgorithm::Cologne => cologne(word),
+        }
+    }
+
+    /// Stable tag used by the serialized format.
+    pub(crate) fn tag(self) -> u8 {
+        match self {
+            PhoneticAlgorithm::Soundex => 0,
+            PhoneticAlgorithm::Metaphone => 1,
+            PhoneticAlgorithm::Cologne => 2,
+        }
+    }
+
+    /// Inverse of [`tag`](Self::tag).
+    pub(crate) fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            0 => Some(PhoneticAlgorithm::Soundex),
+            1 => Some(PhoneticAlgorithm::Metaphone),
+            2 => Some(PhoneticAlgorithm::Cologne),
+            _ => None,
+        }
+    }
+}
+
+/// The ASCII letters of `word`, uppercased.
+fn ascii_letters(word: &str) -> Vec<char> {
+    word.chars()
+        .filter(char::is_ascii_alphabetic)
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+/// Soundex digit of an uppercase letter; `None` for vowels and `H`, `W`, `Y`.
+fn soundex_digit(c: char) -> Option<char> {
+    match c {
+        'B' | 'F' | 'P' | 'V' => Some('1'),
+        'C' | 'G' | 'J' | 'K' | 'Q' | 'S' | 'X' | 'Z' => Some('2'),
+        'D' | 'T' => Some('3'),
+        'L' => Some('4'),
+        'M' | 'N' => Some('5'),
+        'R' => Some('6'),
+        _ => None,
+    }
+}
+
+fn soundex(word: &str) -> Option<String> {
+    let letters = ascii_letters(word);
+    let (&first, rest) = letters.split_first()?;
+    let mut code = String::with_capacity(4);
+    code.push(first);
+    let mut last = soundex_digit(first);
+    for &c in rest {
+        let digit = soundex_digit(c);
+        if let Some(d) = digit
+            && last != digit
+        {
+            code.push(d);
+            if code.len() == 4 {
+                break;
+            }
+        }
+        // Vowels separate letters with the same digit; `H` and `W` don't.
+        if c != 'H' && c != 'W' {
+            last = digit;
+        }
+    }
+    while code.len() < 4 {
+        code.push('0');
+    }
+    Some(code)
+}
+
+fn metaphone(word: &str) -> Option<String> {
+    let w = ascii_letters(word);
+    if w.is_empty() {
+        return None;
+    }
+    let at = |i: usize| w.get(i).copied();
+    let is_vowel = |c: Option<char>| matches!(c, Some('A' | 'E' | 'I' | 'O' | 'U'));
+
+    let mut out = String::new();
+    // Initial-letter exceptions. `lead` is the only position a vowel is kept at.
+    let (mut i, lead) = match (w[0], at(1)) {
+        ('A', Some('E')) | ('G' | 'K' | 'P', Some('N')) | ('W', Some('R')) => (1, Some(1)),
+        ('X', _) => {
+            out.push('S');
+            (1, None)
+        }
+        ('W', Some('H')) => {
+            out.push('W');
+            (2, None)
+        }
+        _ => (0, Some(0)),
+    };
+
+    while i < w.len() {
+        let c = w[i];
+        let prev = i.checked_sub(1).map(|p| w[p]);
+        let next = at(i + 1);
+        // Doubled letters sound once, except `C` (`"accept"`).
+        if prev == Some(c) && c != 'C' {
+            i += 1;
+            continue;
+        }
+        match c {
+            'A' | 'E' | 'I' | 'O' | 'U' => {
+                if lead == Some(i) {
+                    out.push(c);
+                }
+            }
+            'B' => {
+                // Silent in a final "MB".
+                if !(prev == Some('M') && next.is_none()) {
+                    out.push('B');
+                }
+            }
+            'C' => {
+                if prev == Some('S') && matches!(next, Some('I' | 'E' | 'Y')) {
+                    // "SCI", "SCE", "SCY": silent.
+                } else if next == Some('I') && at(i + 2) == Some('A') {
+                    out.push('X');
+                } else if next == Some('H') {
+                    out.push(if prev == Some('S') { 'K' } else { 'X' });
+                } else if matches!(next, Some('I' | 'E' | 'Y')) {
+                    out.push('S');
+                } else {
+                    out.push('K');
+                }
+            }
+            'D' => {
+                if next == Some('G') && matches!(at(i + 2), Some('E' | 'I' | 'Y')) {
+                    out.push('J');
+                } else {
+                    out.push('T');
+                }
+            }
+            'G' => {
+                // Silent in "GH" before a consonant, and in a final "GN" or "GNED".
+                let before_h = next == Some('H') && i + 2 < w.len() && !is_vowel(at(i + 2));
+                let final_n = next == Some('N')
+                    && (i + 2 == w.len()
+                        || (at(i + 2) == Some('E') && at(i + 3) == Some('D') && i + 4 == w.len()));
+                if !(before_h || final_n) {
+                    out.push(if matches!(next, Some('I' | 'E' | 'Y')) {
+                        'J'
+                    } else {
+                        'K'
+                    });
+                }
+            }
+            'H' => {
+                let after_digraph = matches!(prev, Some('C' | 'S' | 'P' | 'T' | 'G'));
+                // Silent after a vowel unless another vowel follows.
+                if !after_digraph && (!is_vowel(prev) || is_vowel(next)) {
+                    out.push('H');
+                }
+            }
+            'K' => {
+                if prev != Some('C') {
+                    out.push('K');
+                }
+            }
+            'P' => out.push(if next == Some('H') { 'F' } else { 'P' }),
+            'Q' => out.push('K'),
+            'S' => {
+                if next == Some('H') || (next == Some('I') && matches!(at(i + 2), Some('O' | 'A')))
+                {
+                    out.push('X');
+                } else {
+                    out.push('S');
+                }
+            }
+            'T' => {
+                if next == Some('I') && matches!(at(i + 2), Some('O' | 'A')) {
+                    out.push('X');
+                } else if next == Some('H') {
+                    out.push('0');
+                } else if !(next == Some('C') && at(i + 2) == Some('H')) {
+                    out.push('T');
+                }
+            }
+            'V' => out.push('F'),
+            'W' | 'Y' => {
+                if is_vowel(next) {
+                    out.push(c);
+                }
+            }
+            'X' => out.push_str("KS"),
+            'Z' => out.push('S'),
+            // F, J, L, M, N, R
+            _ => out.push(c),
+        }
+        i += 1;
+    }
+    (!out.is_empty()).then_some(out)
+}
+
+fn cologne(word: &str) -> Option<String> {
+    let w: Vec<char> = word
+        .chars()
+        .flat_map(char::to_uppercase)
+        .filter_map(|c| match c {
+            'Ä' => Some('A'),
+            'Ö' => Some('O'),
+            'Ü' => Some('U'),
+            'ẞ' => Some('S'),
+            c if c.is_ascii_uppercase() => Some(c),
+            _ => None,
+        })
+        .collect();
+
+    let mut raw = String::with_capacity(w.len());
+    for (i, &c) in w.iter().enumerate() {
+        let prev = i.checked_sub(1).map(|p| w[p]);
+        let next = w.get(i + 1).copied();
+        raw.push_str(match c {
+            'A' | 'E' | 'I' | 'J' | 'O' | 'U' | 'Y' => "0",
+            'B' => "1",
+            'P' => {
+                if next == Some('H') {
+                    "3"
+                } else {
+                    "1"
+                }
+            }
+            'D' | 'T' => {
+                if matches!(next, Some('C' | 'S' | 'Z')) {
+                    "8"
+                } else {
+                    "2"
+                }
+            }
+            'F' | 'V' | 'W' => "3",
+            'G' | 'K' | 'Q' => "4",
+            'C' => {
+                let hard = if i == 0 {
+                    matches!(
+                        next,
+                        Some('A' | 'H' | 'K' | 'L' | 'O' | 'Q' | 'R' | 'U' | 'X')
+                    )
+                } else {
+                    matches!(next, Some('A' | 'H' | 'K' | 'O' | 'Q' | 'U' | 'X'))
+                        && !matches!(prev, Some('S' | 'Z'))
+                };
+                if hard { "4" } else { "8" }
+            }
+            'X' => {
+                if matches!(prev, Some('C' | 'K' | 'Q')) {
+                    "8"
+                } else {
+                    "48"
+                }
+            }
+            'L' => "5",
+            'M' | 'N' => "6",
+            'R' => "7",
+            'S' | 'Z' => "8",
+            // H
+            _ => "",
+        });
+    }
+
+    // Collapse repeated digits, then drop every `0` but a leading one.
+    let mut code = String::with_capacity(raw.len());
+    let mut last = None;
+    for (i, d) in raw.chars().enumerate() {
+        if last != Some(d) && (d != '0' || i == 0) {
+            code.push(d);
+        }
+        last = Some(d);
+    }
+    (!code.is_empty()).then_some(code)
+}
+
+/// Patterns indexed by the phonetic codes of their words.
+#[derive(Clone, Debug)]
+pub(crate) struct PhoneticIndex {
+    pub(crate) algorithm: PhoneticAlgorithm,
+    /// Similarity of a phonetic match, before the pattern weight.
+    pub(crate) score: f32,
+    /// Pattern indices keyed by their words' codes, space-separated.
+    keys: FxHashMap<String, Vec<usize>>,
+    /// Distinct word counts of the indexed patterns, ascending.
+    word_counts: Vec<usize>,
+}
+
+impl PhoneticIndex {
+    /// Index every pattern whose words all encode; others are left to edit-distance matching.
+    pub(crate) fn new(algorithm: PhoneticAlgorithm, score: f32, patterns: &[Pattern]) -> Self {
+        let mut keys: FxHashMap<String, Vec<usize>> = FxHashMap::default();
+        let mut word_counts = Vec::new();
+        for (i, pattern) in patterns.iter().enumerate() {
+            let codes: Option<Vec<String>> = pattern
+                .pattern
+                .unicode_words()
+                .map(|word| algorithm.encode(word))
+                .collect();
+            let Some(codes) = codes.filter(|codes| !codes.is_empty()) else {
+                continue;
+            };
+            if !word_counts.contains(&codes.len()) {
+                word_counts.push(codes.len());
+            }
+            keys.entry(codes.join(" ")).or_default().push(i);
+        }
+        word_counts.sort_unstable();
+        Self {
+            algorithm,
+            score,
+            keys,
+            word_counts,
+        }
+    }
+
+    /// Add the phonetic matches in `haystack` scoring at least `threshold` (and spanning what
+    /// `constraints` allows) to `matches`, keeping the better of a phonetic and an edit-distance
+    /// match over the same span and pattern.
+    pub(crate) fn merge_into<'a>(
+        &self,
+        engine: &'a FuzzyAhoCorasick,
+        haystack: &'a str,
+        threshold: f32,
+        constraints: SpanConstraints<'_>,
+        matches: &mut Vec<FuzzyMatch<'a>>,
+    ) {
+        let mut words = PhoneticWords::new(haystack);
+        self.merge_words_into(
+            engine,
+            haystack,
+            &mut words,
+            threshold,
+            constraints,
+            matches,
+        );
+    }
+
+    /// [`merge_into`](Self::merge_into) over the words of `words` from its current position on.
+    ///
+    /// With `constraints.leftmost_only`, only the leftmost matches are wanted: the scan stops at
+    /// the first word that starts a phonetic match, or past the leftmost match already in
+    /// `matches`, whichever comes first. `words` is left at the word it stopped on.
+    pub(crate) fn merge_words_into<'a>(
+        &self,
+        engine: &'a FuzzyAhoCorasick,
+        haystack: &'a str,
+        words: &mut PhoneticWords<'a>,
+        threshold: f32,
+        constraints: SpanConstraints<'_>,
+        matches: &mut Vec<FuzzyMatch<'a>>,
+    ) {
+        let Some(&longest) = self.word_counts.last() else {
+            return;
+        };
+        let limit = if constraints.leftmost_only {
+            matches.iter().map(|m| m.start).min().unwrap_or(usize::MAX)
+        } else {
+            usize::MAX
+        };
+
+        let mut seen: FxHashMap<(usize, usize, usize), usize> = matches
+            .iter()
+            .enumerate()
+            .map(|(i, m)| ((m.start, m.end, m.pattern_index), i))
+            .collect();
+        let mut key = String::new();
+        loop {
+            words.fill(self.algorithm, longest);
+            let Some(&(start, _, _)) = words.ahead.front() else {
+                break;
+            };
+            if start > limit {
+                break;
+            }
+            let mut found = false;
+            for &count in &self.word_counts {
+                if words.ahead.len() < count {
+                    break;
+                }
+                let run = || words.ahead.range(..count).map(|(_, _, code)| code);
+                if run().any(Option::is_none) {
+                    continue;
+                }
+                key.clear();
+                for code in run().flatten() {
+                    if !key.is_empty() {
+                        key.push(' ');
+                    }
+                    key.push_str(code);
+                }
+                let Some(pattern_indices) = self.keys.get(&key) else {
+                    continue;
+                };
+                let (last_start, last, _) = &words.ahead[count - 1];
+                let end = last_start + last.len();
+                if constraints
+                    .boundaries
+                    .is_some_and(|boundaries| !(boundaries[start] && boundaries[end]))
+                    || (constraints.anchored.start() && start != 0)
+                    || (constraints.anchored.end() && end != haystack.len())
+                {
+                    continue;
+                }
+                for &pattern_index in pattern_indices {
+                    let pattern = &engine.patterns[pattern_index];
+                    let similarity = self.score * pattern.weight;
+                    if similarity < threshold {
+                        continue;
+                    }
+                    found = true;
+                    let hit = FuzzyMatch {
+                        insertions: 0,
+                        deletions: 0,
+                        substitutions: 0,
+                        swaps: 0,
+                        edits: 0,
+                        pattern_index,
+                        pattern,
+                        start,
+                        end,
+                        similarity,
+                        text: &haystack[start..end],
+                        alignment: None,
+                    };
+                    match seen.entry((start, end, pattern_index)) {
+                        Entry::Occupied(entry) => {
+                            let existing = &mut matches[*entry.get()];
+                            if similarity > existing.similarity {
+                                *existing = hit;
+                            }
+                        }
+                        Entry::Vacant(entry) => {
+                            entry.insert(matches.len());
+                            matches.push(hit);
+                        }
+                    }
+                }
+            }
+            if found && constraints.leftmost_only {
+                break;
+            }
+            words.ahead.pop_front();
+        }
+    }
+}
+
+/// The words of a haystack with their phonetic codes, encoded only as a scan reaches them, so a
+/// scan that stops early (or resumes further on, as [`FindIter`](crate::FindIter) does) never
+/// encodes the rest.
+pub(crate) struct PhoneticWords<'a> {
+    words: UnicodeWordIndices<'a>,
+    /// Byte offset before which words are skipped unencoded.
+    from: usize,
+    /// The words read ahead of the scan position — the one it is at first — with their codes.
+    ahead: VecDeque<(usize, &'a str, Option<String>)>,
+}
+
+impl<'a> PhoneticWords<'a> {
+    pub(crate) fn new(haystack: &'a str) -> Self {
+        Self {
+            words: haystack.unicode_word_indices(),
+            from: 0,
+            ahead: VecDeque::new(),
+        }
+    }
+
+    /// Move past the words starting before byte `offset`, without encoding those not read yet.
+    pub(crate) fn skip_to(&mut self, offset: usize) {
+        self.from = self.from.max(offset);
+        while self
+            .ahead
+            .front()
+            .is_some_and(|&(start, _, _)| start < offset)
+        {
+            self.ahead.pop_front();
+        }
+    }
+
+    /// Read ahead until `count` words are buffered, or the haystack ends.
+    fn fill(&mut self, algorithm: PhoneticAlgorithm, count: usize) {
+        while self.ahead.len() < count {
+            let Some((start, word)) = self.words.next() else {
+                break;
+            };
+            if start < self.from {
+                continue;
+            }
+            #[cfg(test)]
+            ENCODED_WORDS.set(ENCODED_WORDS.get() + 1);
+            self.ahead.push_back((start, word, algorithm.encode(word)));
+        }
+    }
+}
+
+#[cfg(test)]
+thread_local! {
+    /// Words encoded by [`PhoneticWords`] on this thread, for tests of how far a scan reads.
+    pub(crate) static ENCODED_WORDS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
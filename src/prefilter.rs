@@ -22,8 +22,10 @@
 //!
 //! See `examples/bitap_prototype.rs` for the standalone algorithm + a fuzzed correctness check.
 
+use crate::options::{Anchored, SpanConstraints};
 use crate::structs::FxHashMap;
 use crate::{FuzzyAhoCorasick, FuzzyLimits, FuzzyMatch, FuzzyMatches, SearchError, SearchOptions};
+use std::collections::hash_map::Entry;
 use unicode_segmentation::UnicodeSegmentation;
 
 /// Longest pattern (in graphemes) the `u64` bit-vectors can hold.
@@ -137,20 +139,72 @@ impl Prefiltered<'_> {
         haystack: &'a str,
         opts: &SearchOptions,
     ) -> Result<FuzzyMatches<'a>, SearchError> {
-        let mut matches = self.raw(haystack, opts.threshold)?;
-        matches.apply(opts.order, opts.overlap);
+        let boundaries = opts.boundary.byte_flags(haystack);
+        let constraints = SpanConstraints {
+            boundaries: boundaries.as_deref(),
+            anchored: opts.anchored,
+            leftmost_only: false,
+            first_window: 0,
+            alignment: opts.alignment,
+        };
+        let mut matches = self.raw(haystack, opts.threshold, constraints)?;
+        matches.apply_options(opts);
         Ok(matches)
     }
 
+    /// [`FuzzyAhoCorasick::is_match`] with the pre-filter applied.
+    ///
+    /// # Errors
+    /// Propagates [`SearchError`] when the haystack is too large to index — see
+    /// [`FuzzyAhoCorasick::search`].
+    pub fn is_match(&self, haystack: &str, opts: &SearchOptions) -> Result<bool, SearchError> {
+        Ok(!self.leftmost(haystack, opts)?.is_empty())
+    }
+
+    /// [`FuzzyAhoCorasick::find_first`] with the pre-filter applied.
+    ///
+    /// # Errors
+    /// Propagates [`SearchError`] when the haystack is too large to index — see
+    /// [`FuzzyAhoCorasick::search`].
+    pub fn find_first<'a>(
+        &'a self,
+        haystack: &'a str,
+        opts: &SearchOptions,
+    ) -> Result<Option<FuzzyMatch<'a>>, SearchError> {
+        Ok(self.leftmost(haystack, opts)?.into_first(opts))
+    }
+
+    /// Raw matches up to the leftmost start position that yields one.
+    #[allow(clippy::trivially_copy_pass_by_ref)] // uniform with the `&SearchOptions` public API
+    fn leftmost<'a>(
+        &'a self,
+        haystack: &'a str,
+        opts: &SearchOptions,
+    ) -> Result<FuzzyMatches<'a>, SearchError> {
+        let boundaries = opts.boundary.byte_flags(haystack);
+        let constraints = SpanConstraints {
+            boundaries: boundaries.as_deref(),
+            anchored: opts.anchored,
+            leftmost_only: true,
+            first_window: 0,
+            alignment: opts.alignment,
+        };
+        self.raw(haystack, opts.threshold, constraints)
+    }
+
     /// Raw best-per-span matches (pre-filtered when a bit model was built), before ranking/overlap.
     fn raw<'a>(
         &'a self,
         haystack: &'a str,
         threshold: f32,
+        constraints: SpanConstraints<'_>,
     ) -> Result<FuzzyMatches<'a>, SearchError> {
         match &self.filter {
-            Some(filter) => filter.search_unsorted(self.engine, haystack, threshold),
-            None => self.engine.search_raw(haystack, threshold),
+            // Windows would anchor to their own slices; an anchored search is cheap unfiltered.
+            Some(filter) if constraints.anchored == Anchored::No => {
+                filter.search_unsorted(self.engine, haystack, threshold, constraints)
+            }
+            _ => self.engine.search_raw(haystack, threshold, constraints),
         }
     }
 }
@@ -160,7 +214,15 @@ impl BitapFilter {
     /// model (see the module docs).
     fn build(engine: &FuzzyAhoCorasick) -> Option<Self> {
         // Multi-character mappings are block edits that don't map cleanly to unit Levenshtein.
-        if !engine.mappings.is_empty() {
+        if engine.has_mappings() {
+            return None;
+        }
+        // A custom substitution model scores whole graphemes; its cheapest substitution is unknown.
+        if engine.substitution_cost.is_some() {
+            return None;
+        }
+        // Phonetic matches aren't bounded by edit distance at all.
+        if engine.phonetic.is_some() {
             return None;
         }
         if engine.patterns.is_empty() {
@@ -306,13 +368,14 @@ impl BitapFilter {
         engine: &'a FuzzyAhoCorasick,
         haystack: &'a str,
         threshold: f32,
+        constraints: SpanConstraints<'_>,
     ) -> Result<FuzzyMatches<'a>, SearchError> {
         // Decide budgets up front; any pattern needing an unbounded/huge k forces a full search.
         let mut ks = Vec::with_capacity(self.patterns.len());
         for pat in &self.patterns {
             match self.k_for(pat, threshold) {
                 Some(k) => ks.push(k),
-                None => return engine.search_raw(haystack, threshold),
+                None => return engine.search_raw(haystack, threshold, constraints),
             }
         }
 
@@ -347,25 +410,35 @@ impl BitapFilter {
             let bstart = offsets.byte(gs);
             let bend = offsets.byte(ge.min(n));
             let sub = &haystack[bstart..bend];
-            for m in engine.search_raw(sub, threshold)? {
-                let start = bstart + m.start;
-                let end = bstart + m.end;
-                let key = (start, end, m.pattern_index);
-                let entry = best.entry(key).or_insert_with(|| FuzzyMatch {
-                    start,
-                    end,
-                    text: &haystack[start..end],
-                    ..m.clone()
-                });
-                if m.similarity > entry.similarity {
-                    *entry = FuzzyMatch {
-                        start,
-                        end,
-                        text: &haystack[start..end],
-                        ..m.clone()
-                    };
+            // The window's slice of the haystack-wide boundary flags, so its edges aren't mistaken
+            // for boundaries.
+            let sub_constraints = SpanConstraints {
+                boundaries: constraints.boundaries.map(|b| &b[bstart..=bend]),
+                ..constraints
+            };
+            for mut m in engine.search_raw(sub, threshold, sub_constraints)? {
+                m.start += bstart;
+                m.end += bstart;
+                m.text = &haystack[m.start..m.end];
+                for op in m.alignment.iter_mut().flatten() {
+                    op.shift_text(bstart);
+                }
+                let key = (m.start, m.end, m.pattern_index);
+                match best.entry(key) {
+                    Entry::Occupied(mut entry) => {
+                        if m.similarity > entry.get().similarity {
+                            entry.insert(m);
+                        }
+                    }
+                    Entry::Vacant(entry) => {
+                        entry.insert(m);
+                    }
                 }
             }
+            // Windows are disjoint and in order, so later ones only hold matches further right.
+            if constraints.leftmost_only && !best.is_empty() {
+                break;
+            }
         }
 
         let mut inner: Vec<FuzzyMatch<'a>> = best.into_values().collect();
@@ -1,6 +1,9 @@
 //! Public search / replace / segmentation entry points, driven by [`SearchOptions`].
+use crate::options::SpanConstraints;
+use crate::search::Scratch;
 use crate::{
-    FuzzyAhoCorasick, FuzzyMatch, FuzzyMatches, Order, Overlap, SearchError, SearchOptions, Segment,
+    Anchored, DEFAULT_THRESHOLD, FuzzyAhoCorasick, FuzzyMatch, FuzzyMatches, Order, Overlap,
+    SearchError, SearchOptions, Segment,
 };
 use std::borrow::Cow;
 
@@ -32,8 +35,137 @@ impl FuzzyAhoCorasick {
         haystack: &'a str,
         opts: &SearchOptions,
     ) -> Result<FuzzyMatches<'a>, SearchError> {
-        let mut matches = self.search_raw(haystack, opts.threshold)?;
-        matches.apply(opts.order, opts.overlap);
+        self.search_in(haystack, opts, &mut Scratch::default())
+    }
+
+    /// [`search`](Self::search) with the buffers of `scratch`.
+    pub(crate) fn search_in<'a>(
+        &'a self,
+        haystack: &'a str,
+        opts: &SearchOptions,
+        scratch: &mut Scratch,
+    ) -> Result<FuzzyMatches<'a>, SearchError> {
+        let mut matches = self.raw_in(haystack, opts, false, scratch)?;
+        matches.apply_options(opts);
+        Ok(matches)
+    }
+
+    /// Whether `haystack` contains any match accepted by [`opts`](SearchOptions). Stops at the
+    /// first start position that yields a match instead of exploring the whole haystack — the cheap
+    /// yes/no for gating checks.
+    ///
+    /// # Errors
+    /// Propagates [`SearchError`] when the haystack is too large to index — see
+    /// [`search`](Self::search).
+    ///
+    /// # Example
+    /// ```
+    /// use fuzzy_aho_corasick::{FuzzyAhoCorasickBuilder, FuzzyLimits, SearchOptions};
+    /// let banned = FuzzyAhoCorasickBuilder::new()
+    ///     .fuzzy(FuzzyLimits::new().edits(1))
+    ///     .build(["spam", "scam"]);
+    /// let opts = SearchOptions::new().threshold(0.6);
+    /// assert!(banned.is_match("totally not a sc4m", &opts).unwrap());
+    /// assert!(!banned.is_match("hello there", &opts).unwrap());
+    /// ```
+    pub fn is_match(&self, haystack: &str, opts: &SearchOptions) -> Result<bool, SearchError> {
+        Ok(!self
+            .raw_in(haystack, opts, true, &mut Scratch::default())?
+            .is_empty())
+    }
+
+    /// The leftmost match accepted by [`opts`](SearchOptions), or `None`. Stops at the first start
+    /// position that yields a match; several matches starting there (different patterns or
+    /// lengths) are ranked by `opts.order` ([`Order::Default`] when left unsorted) — or by the
+    /// preference of a leftmost [`MatchKind`](crate::MatchKind) — and the best one returned.
+    ///
+    /// # Errors
+    /// Propagates [`SearchError`] when the haystack is too large to index — see
+    /// [`search`](Self::search).
+    ///
+    /// # Example
+    /// ```
+    /// use fuzzy_aho_corasick::{FuzzyAhoCorasickBuilder, FuzzyLimits, SearchOptions};
+    /// let engine = FuzzyAhoCorasickBuilder::new()
+    ///     .fuzzy(FuzzyLimits::new().edits(1))
+    ///     .build(["world", "hello"]);
+    /// let first = engine.find_first("say helo world", &SearchOptions::new().threshold(0.7));
+    /// assert_eq!(first.unwrap().unwrap().text, "helo");
+    /// ```
+    pub fn find_first<'a>(
+        &'a self,
+        haystack: &'a str,
+        opts: &SearchOptions,
+    ) -> Result<Option<FuzzyMatch<'a>>, SearchError> {
+        Ok(self
+            .raw_in(haystack, opts, true, &mut Scratch::default())?
+            .into_first(opts))
+    }
+
+    /// Raw best-per-span matches under `opts`' threshold, boundary, anchoring and alignment, before
+    /// ranking/overlap. With `leftmost_only`, searches only as far as the leftmost start position
+    /// that yields a match, for the early-exit entry points.
+    #[allow(clippy::trivially_copy_pass_by_ref)] // uniform with the `&SearchOptions` public API
+    pub(crate) fn raw_in<'a>(
+        &'a self,
+        haystack: &'a str,
+        opts: &SearchOptions,
+        leftmost_only: bool,
+        scratch: &mut Scratch,
+    ) -> Result<FuzzyMatches<'a>, SearchError> {
+        let mut flags = std::mem::take(&mut scratch.boundaries);
+        let bounded = opts.boundary.fill_byte_flags(haystack, &mut flags);
+        let constraints = SpanConstraints {
+            boundaries: bounded.then_some(&flags[..]),
+            anchored: opts.anchored,
+            leftmost_only,
+            first_window: 0,
+            alignment: opts.alignment,
+        };
+        let matches = self.search_raw_in(haystack, opts.threshold, constraints, scratch);
+        scratch.boundaries = flags;
+        matches
+    }
+
+    /// Fuzzy dictionary lookup: the (up to) `k` patterns most similar to the **whole** `query`,
+    /// best first. Matches are anchored at both ends ([`Anchored::Both`]), so junk before or after
+    /// the entry counts as insertions instead of being skipped; each result carries its
+    /// [`pattern`](FuzzyMatch::pattern) and score ([`similarity`](FuzzyMatch::similarity)), ranked
+    /// by [`Order::Default`]. Candidates are bounded by the configured edit limits — a pattern
+    /// further away than those isn't returned at all.
+    ///
+    /// # Errors
+    /// Propagates [`SearchError`] when the query is too large to index — see
+    /// [`search`](Self::search).
+    ///
+    /// # Example
+    /// ```
+    /// use fuzzy_aho_corasick::{FuzzyAhoCorasickBuilder, FuzzyLimits};
+    /// let products = FuzzyAhoCorasickBuilder::new()
+    ///     .fuzzy(FuzzyLimits::new().edits(2))
+    ///     .case_insensitive(true)
+    ///     .build(["Pixel 8", "Pixel 8 Pro", "Pixel Fold"]);
+    /// let best = products.lookup("pixle 8", 2).unwrap();
+    /// assert_eq!(best[0].pattern.as_str(), "Pixel 8");
+    /// ```
+    pub fn lookup<'a>(&'a self, query: &'a str, k: usize) -> Result<FuzzyMatches<'a>, SearchError> {
+        self.lookup_in(query, k, &mut Scratch::default())
+    }
+
+    /// [`lookup`](Self::lookup) with the buffers of `scratch`.
+    pub(crate) fn lookup_in<'a>(
+        &'a self,
+        query: &'a str,
+        k: usize,
+        scratch: &mut Scratch,
+    ) -> Result<FuzzyMatches<'a>, SearchError> {
+        let constraints = SpanConstraints {
+            anchored: Anchored::Both,
+            ..SpanConstraints::default()
+        };
+        let mut matches = self.search_raw_in(query, DEFAULT_THRESHOLD, constraints, scratch)?;
+        matches.default_sort();
+        matches.inner.truncate(k);
         Ok(matches)
     }
 
@@ -41,12 +173,24 @@ impl FuzzyAhoCorasick {
     /// require a non-overlapping, deterministically-ordered match set. Honors `opts.threshold` and
     /// `opts.order` (falling back to [`Order::Default`] when it's left [`Order::Unsorted`], so
     /// results are deterministic) and always resolves overlaps (`Keep` is upgraded to
-    /// [`Overlap::NonOverlapping`]; an explicit unique mode is preserved).
+    /// [`Overlap::NonOverlapping`]; an explicit unique or optimal mode is preserved). A leftmost `match_kind`
+    /// is non-overlapping and position-ordered already, and is applied as is.
     #[allow(clippy::trivially_copy_pass_by_ref)] // uniform with the `&SearchOptions` public API
     fn segmented<'a>(
         &'a self,
         haystack: &'a str,
         opts: &SearchOptions,
+    ) -> Result<FuzzyMatches<'a>, SearchError> {
+        self.segmented_in(haystack, opts, &mut Scratch::default())
+    }
+
+    /// [`segmented`](Self::segmented) with the buffers of `scratch`.
+    #[allow(clippy::trivially_copy_pass_by_ref)] // uniform with the `&SearchOptions` public API
+    pub(crate) fn segmented_in<'a>(
+        &'a self,
+        haystack: &'a str,
+        opts: &SearchOptions,
+        scratch: &mut Scratch,
     ) -> Result<FuzzyMatches<'a>, SearchError> {
         let order = if opts.order == Order::Unsorted {
             Order::Default
@@ -58,8 +202,12 @@ impl FuzzyAhoCorasick {
         } else {
             opts.overlap
         };
-        let mut matches = self.search_raw(haystack, opts.threshold)?;
-        matches.apply(order, overlap);
+        let mut matches = self.raw_in(haystack, opts, false, scratch)?;
+        matches.apply_options(&SearchOptions {
+            order,
+            overlap,
+            ..*opts
+        });
         Ok(matches)
     }
 
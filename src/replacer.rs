@@ -1,4 +1,4 @@
-use crate::{FuzzyAhoCorasick, SearchError, SearchOptions};
+use crate::{FuzzyAhoCorasick, FuzzyMatch, SearchError, SearchOptions};
 use std::io::{self, Read, Write};
 
 /// A turnkey fuzzy find-and-replace built from `(pattern → replacement)` pairs.
@@ -37,9 +37,44 @@ impl FuzzyReplacer {
         reader: R,
         writer: W,
         threshold: f32,
+    ) -> io::Result<u64> {
+        self.replace_stream_with(reader, writer, threshold, |_| {})
+    }
+
+    /// [`replace_stream`](Self::replace_stream), also calling `on_replace` with each match it
+    /// substitutes, in stream order — e.g. to count the replacements made per pattern.
+    ///
+    /// ```
+    /// use fuzzy_aho_corasick::{FuzzyAhoCorasickBuilder, FuzzyLimits};
+    ///
+    /// let replacer = FuzzyAhoCorasickBuilder::new()
+    ///     .fuzzy(FuzzyLimits::new().edits(1))
+    ///     .build_replacer([("teh", "the"), ("recieve", "receive")]);
+    /// let mut counts = [0; 2];
+    /// let mut out = Vec::new();
+    /// replacer
+    ///     .replace_stream_with("tehy recieve teh".as_bytes(), &mut out, 0.8, |m| {
+    ///         counts[m.pattern_index] += 1;
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!(counts, [2, 1]);
+    /// ```
+    ///
+    /// # Errors
+    /// Propagates any [`io::Error`] from `reader` or `writer`.
+    pub fn replace_stream_with<R: Read, W: Write>(
+        &self,
+        reader: R,
+        writer: W,
+        threshold: f32,
+        mut on_replace: impl FnMut(&FuzzyMatch),
     ) -> io::Result<u64> {
         self.engine.replace_stream(reader, writer, threshold, |m| {
-            self.replacements.get(m.pattern_index)
+            let replacement = self.replacements.get(m.pattern_index);
+            if replacement.is_some() {
+                on_replace(m);
+            }
+            replacement
         })
     }
 
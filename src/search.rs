@@ -1,12 +1,17 @@
 //! Core fuzzy search: the monomorphized BFS over grapheme storage and its helpers.
-use crate::grapheme::{AsciiGraphemes, GraphemeStorage};
-use crate::structs::{FxHashMap, Node, State};
+use crate::EditOp;
+use crate::alignment::{self, Step, TraceStep};
+use crate::grapheme::{AsciiGraphemes, GraphemeStorage, PreparedHaystack};
+use crate::options::SpanConstraints;
+use crate::structs::{FxHashMap, Similarity, State};
+use crate::trie::{MappingView, OwnedTrie, TrieNode, TrieView};
 use crate::{
     FuzzyAhoCorasick, FuzzyLimits, FuzzyMatch, FuzzyMatches, NumEdits, Pattern, SearchError,
 };
 use std::borrow::Cow;
 use std::collections::hash_map::Entry;
 use std::hash::{Hash, Hasher};
+use std::sync::Arc;
 use unicode_segmentation::UnicodeSegmentation;
 
 /// Automaton node index (u32 for compact struct packing; >4B nodes is unrealistic).
@@ -49,6 +54,56 @@ impl Hash for VisitedKey {
     }
 }
 
+/// The best match so far for one `(start_byte, end_byte, pattern_index)` span. It borrows neither
+/// the engine nor the haystack, so the map holding it can be kept in a [`Scratch`] between searches;
+/// it becomes a [`FuzzyMatch`] when the search returns.
+struct Candidate {
+    insertions: NumEdits,
+    deletions: NumEdits,
+    substitutions: NumEdits,
+    swaps: NumEdits,
+    edits: NumEdits,
+    similarity: f32,
+    alignment: Option<Vec<EditOp>>,
+}
+
+/// The buffers a search fills and throws away: the per-grapheme `char`s, the grapheme list of a
+/// non-ASCII haystack, the boundary flags, and the BFS state. A [`Searcher`](crate::Searcher) keeps
+/// one between calls so that, once warm, a search allocates little beyond its results; a fresh
+/// (empty) one allocates nothing until it is used.
+#[derive(Default)]
+pub(crate) struct Scratch {
+    text_chars: Vec<char>,
+    /// Always empty between searches; only its allocation is kept (see [`recycle_graphemes`]).
+    graphemes: Vec<(usize, Cow<'static, str>)>,
+    /// Filled by [`Boundary::fill_byte_flags`](crate::Boundary::fill_byte_flags).
+    pub(crate) boundaries: Vec<bool>,
+    buffers: SearchBuffers,
+}
+
+/// The part of a [`Scratch`] used by [`search_unsorted_impl`](FuzzyAhoCorasick::search_unsorted_impl)
+/// itself — all a search of a [prepared](FuzzyAhoCorasick::prepare) haystack needs, which
+/// [`FindIter`](crate::FindIter) keeps across its steps.
+#[derive(Default)]
+pub(crate) struct SearchBuffers {
+    /// The BFS frontier of the current start window.
+    queue: Vec<State>,
+    /// Per-window state dedup (see [`VisitedKey`]).
+    visited: FxHashMap<VisitedKey, f32>,
+    /// Best match per span, drained into the results at the end of each search.
+    best: FxHashMap<(usize, usize, usize), Candidate>,
+    /// Steps of the states enqueued in the current window, for an alignment search.
+    arena: Vec<TraceStep>,
+}
+
+/// Empty `graphemes` and hand its allocation back for graphemes of another lifetime. Reuse is
+/// best-effort: the standard library currently collects in place when the element layout is
+/// unchanged, keeping the capacity, but doesn't guarantee it — a fresh allocation is still correct.
+fn recycle_graphemes<'b>(mut graphemes: Vec<(usize, Cow<'_, str>)>) -> Vec<(usize, Cow<'b, str>)> {
+    graphemes.clear();
+    graphemes.into_iter().map(|_| unreachable!()).collect()
+}
+
 #[allow(unused_macros)]
 #[cfg(test)]
 macro_rules! trace {
@@ -64,9 +119,9 @@ impl FuzzyAhoCorasick {
     /// Get the per-node limits if this node corresponds to a pattern that has
     /// its own `FuzzyLimits`.
     #[inline]
-    fn get_node_limits(&self, node: u32) -> Option<&FuzzyLimits> {
-        self.nodes[node as usize]
-            .pattern_index
+    fn get_node_limits<T: TrieView>(&self, trie: T, node: u32) -> Option<&FuzzyLimits> {
+        trie.node(node)
+            .pattern_index()
             .and_then(|i| self.patterns.get(i).and_then(|p| p.limits.as_ref()))
     }
 
@@ -174,11 +229,20 @@ impl FuzzyAhoCorasick {
         &self.patterns
     }
 
+    /// Returns the similarity table the automaton scores substitutions with. Cloning the `Arc`
+    /// shares the table with another engine (see
+    /// [`FuzzyAhoCorasickBuilder::similarity`](crate::FuzzyAhoCorasickBuilder::similarity)).
+    #[must_use]
+    pub fn similarity(&self) -> &Arc<Similarity> {
+        &self.similarity
+    }
+
     /// Core fuzzy search over the haystack producing raw matches without any global ordering or
     /// overlap resolution. Explores all state transitions (substitutions, swaps, insertions,
     /// deletions) from each grapheme position, keeping the best match per unique
-    /// (`start_byte`, `end_byte`, `pattern_index`) span above `similarity_threshold`. The public
-    /// [`search`](Self::search) applies ranking/overlap on top of this.
+    /// (`start_byte`, `end_byte`, `pattern_index`) span above `similarity_threshold`, merged with
+    /// any phonetic matches. The public [`search`](Self::search) applies ranking/overlap on top of
+    /// this.
     ///
     /// # Errors
     /// Returns [`SearchError::HaystackTooLarge`] if `haystack` has more than `u32::MAX` grapheme
@@ -188,215 +252,390 @@ impl FuzzyAhoCorasick {
         &'a self,
         haystack: &'a str,
         similarity_threshold: f32,
+        constraints: SpanConstraints<'_>,
     ) -> Result<FuzzyMatches<'a>, SearchError> {
+        self.search_raw_in(
+            haystack,
+            similarity_threshold,
+            constraints,
+            &mut Scratch::default(),
+        )
+    }
+
+    /// [`search_raw`](Self::search_raw) using (and leaving behind, for the next call) the buffers
+    /// of `scratch`.
+    ///
+    /// # Errors
+    /// Returns [`SearchError::HaystackTooLarge`] if `haystack` has more than `u32::MAX` grapheme
+    /// clusters.
+    pub(crate) fn search_raw_in<'a>(
+        &'a self,
+        haystack: &'a str,
+        similarity_threshold: f32,
+        constraints: SpanConstraints<'_>,
+        scratch: &mut Scratch,
+    ) -> Result<FuzzyMatches<'a>, SearchError> {
+        let mut matches =
+            self.edit_matches_in(haystack, similarity_threshold, constraints, scratch)?;
+        if let Some(phonetic) = &self.phonetic {
+            phonetic.merge_into(
+                self,
+                haystack,
+                similarity_threshold,
+                constraints,
+                &mut matches.inner,
+            );
+        }
+        Ok(matches)
+    }
+
+    /// The edit-distance part of [`search_raw_in`](Self::search_raw_in), without the phonetic
+    /// matches.
+    ///
+    /// # Errors
+    /// Returns [`SearchError::HaystackTooLarge`] if `haystack` has more than `u32::MAX` grapheme
+    /// clusters.
+    pub(crate) fn edit_matches_in<'a>(
+        &'a self,
+        haystack: &'a str,
+        similarity_threshold: f32,
+        constraints: SpanConstraints<'_>,
+        scratch: &mut Scratch,
+    ) -> Result<FuzzyMatches<'a>, SearchError> {
+        let Scratch {
+            text_chars,
+            graphemes,
+            buffers,
+            ..
+        } = scratch;
         // Precompute a Vec<char> for the text so search_unsorted_impl can use direct slice
         // indexing instead of the GraphemeStorage::gs_first_char method (which has a match on
         // the enum discriminant, albeit predictable). This eliminates the enum dispatch overhead
         // in the hot loop (~2 calls per expanded state).
-        Ok(if haystack.is_ascii() {
+        text_chars.clear();
+        let matches = if haystack.is_ascii() {
             let g = AsciiGraphemes::new(haystack, self.case_insensitive);
             if u32::try_from(g.gs_len()).is_err() {
                 return Err(SearchError::HaystackTooLarge {
                     graphemes: g.gs_len(),
                 });
             }
-            let text_chars: Vec<char> = (0..g.gs_len()).map(|i| g.gs_first_char(i)).collect();
-            if self.mappings.is_empty() {
-                match self.max_edits_fast {
-                    1 => self.search_unsorted_impl::<false, true, 1, _>(
-                        haystack,
-                        similarity_threshold,
-                        &g,
-                        &text_chars,
-                    ),
-                    2 => self.search_unsorted_impl::<false, false, 2, _>(
-                        haystack,
-                        similarity_threshold,
-                        &g,
-                        &text_chars,
-                    ),
-                    3 => self.search_unsorted_impl::<false, false, 3, _>(
-                        haystack,
-                        similarity_threshold,
-                        &g,
-                        &text_chars,
-                    ),
-                    4 => self.search_unsorted_impl::<false, false, 4, _>(
-                        haystack,
-                        similarity_threshold,
-                        &g,
-                        &text_chars,
-                    ),
-                    5 => self.search_unsorted_impl::<false, false, 5, _>(
-                        haystack,
-                        similarity_threshold,
-                        &g,
-                        &text_chars,
-                    ),
-                    6 => self.search_unsorted_impl::<false, false, 6, _>(
-                        haystack,
-                        similarity_threshold,
-                        &g,
-                        &text_chars,
-                    ),
-                    _ => self.search_unsorted_impl::<false, false, 255, _>(
-                        haystack,
-                        similarity_threshold,
-                        &g,
-                        &text_chars,
-                    ),
-                }
-            } else {
-                match self.max_edits_fast {
-                    1 => self.search_unsorted_impl::<true, true, 1, _>(
-                        haystack,
-                        similarity_threshold,
-                        &g,
-                        &text_chars,
-                    ),
-                    2 => self.search_unsorted_impl::<true, false, 2, _>(
-                        haystack,
-                        similarity_threshold,
-                        &g,
-                        &text_chars,
-                    ),
-                    3 => self.search_unsorted_impl::<true, false, 3, _>(
-                        haystack,
-                        similarity_threshold,
-                        &g,
-                        &text_chars,
-                    ),
-                    4 => self.search_unsorted_impl::<true, false, 4, _>(
-                        haystack,
-                        similarity_threshold,
-                        &g,
-                        &text_chars,
-                    ),
-                    5 => self.search_unsorted_impl::<true, false, 5, _>(
-                        haystack,
-                        similarity_threshold,
-                        &g,
-                        &text_chars,
-                    ),
-                    6 => self.search_unsorted_impl::<true, false, 6, _>(
-                        haystack,
-                        similarity_threshold,
-                        &g,
-                        &text_chars,
-                    ),
-                    _ => self.search_unsorted_impl::<true, false, 255, _>(
-                        haystack,
-                        similarity_threshold,
-                        &g,
-                        &text_chars,
-                    ),
-                }
+            text_chars.extend((0..g.gs_len()).map(|i| g.gs_first_char(i)));
+            self.search_graphemes(
+                haystack,
+                similarity_threshold,
+                &g,
+                text_chars,
+                constraints,
+                buffers,
+            )
+        } else {
+            let g = self
+                .build_unicode_graphemes(haystack, recycle_graphemes(std::mem::take(graphemes)));
+            if u32::try_from(g.gs_len()).is_err() {
+                return Err(SearchError::HaystackTooLarge {
+                    graphemes: g.gs_len(),
+                });
+            }
+            text_chars.extend((0..g.gs_len()).map(|i| g.gs_first_char(i)));
+            let matches = self.search_graphemes(
+                haystack,
+                similarity_threshold,
+                &g,
+                text_chars,
+                constraints,
+                buffers,
+            );
+            *graphemes = recycle_graphemes(g);
+            matches
+        };
+        Ok(matches)
+    }
+
+    /// Segment `haystack` once for repeated [`search_prepared`](Self::search_prepared) calls, as
+    /// [`search_raw`](Self::search_raw) does inline for a single search.
+    ///
+    /// # Errors
+    /// Returns [`SearchError::HaystackTooLarge`] if `haystack` has more than `u32::MAX` grapheme
+    /// clusters.
+    pub(crate) fn prepare<'a>(
+        &'a self,
+        haystack: &'a str,
+    ) -> Result<PreparedHaystack<'a>, SearchError> {
+        if haystack.is_ascii() {
+            let g = AsciiGraphemes::new(haystack, self.case_insensitive);
+            if u32::try_from(g.gs_len()).is_err() {
+                return Err(SearchError::HaystackTooLarge {
+                    graphemes: g.gs_len(),
+                });
             }
+            let text_chars: Vec<char> = (0..g.gs_len()).map(|i| g.gs_first_char(i)).collect();
+            Ok(PreparedHaystack::Ascii(g, text_chars))
         } else {
-            let g = self.build_unicode_graphemes(haystack);
+            let g = self.build_unicode_graphemes(haystack, Vec::new());
             if u32::try_from(g.gs_len()).is_err() {
                 return Err(SearchError::HaystackTooLarge {
                     graphemes: g.gs_len(),
                 });
             }
             let text_chars: Vec<char> = (0..g.gs_len()).map(|i| g.gs_first_char(i)).collect();
-            if self.mappings.is_empty() {
-                match self.max_edits_fast {
-                    1 => self.search_unsorted_impl::<false, true, 1, _>(
-                        haystack,
-                        similarity_threshold,
-                        &g,
-                        &text_chars,
-                    ),
-                    2 => self.search_unsorted_impl::<false, false, 2, _>(
-                        haystack,
-                        similarity_threshold,
-                        &g,
-                        &text_chars,
-                    ),
-                    3 => self.search_unsorted_impl::<false, false, 3, _>(
-                        haystack,
-                        similarity_threshold,
-                        &g,
-                        &text_chars,
-                    ),
-                    4 => self.search_unsorted_impl::<false, false, 4, _>(
-                        haystack,
-                        similarity_threshold,
-                        &g,
-                        &text_chars,
-                    ),
-                    5 => self.search_unsorted_impl::<false, false, 5, _>(
-                        haystack,
-                        similarity_threshold,
-                        &g,
-                        &text_chars,
-                    ),
-                    6 => self.search_unsorted_impl::<false, false, 6, _>(
-                        haystack,
-                        similarity_threshold,
-                        &g,
-                        &text_chars,
-                    ),
-                    _ => self.search_unsorted_impl::<false, false, 255, _>(
-                        haystack,
-                        similarity_threshold,
-                        &g,
-                        &text_chars,
-                    ),
-                }
+            Ok(PreparedHaystack::Unicode(g, text_chars))
+        }
+    }
+
+    /// The edit-distance matches in a haystack segmented by [`prepare`](Self::prepare) (no
+    /// phonetic matches), using (and leaving behind, for the next call) `buffers`.
+    pub(crate) fn search_prepared<'a>(
+        &'a self,
+        prepared: &PreparedHaystack<'_>,
+        haystack: &'a str,
+        similarity_threshold: f32,
+        constraints: SpanConstraints<'_>,
+        buffers: &mut SearchBuffers,
+    ) -> FuzzyMatches<'a> {
+        match prepared {
+            PreparedHaystack::Ascii(g, text_chars) => self.search_graphemes(
+                haystack,
+                similarity_threshold,
+                g,
+                text_chars,
+                constraints,
+                buffers,
+            ),
+            PreparedHaystack::Unicode(g, text_chars) => self.search_graphemes(
+                haystack,
+                similarity_threshold,
+                g,
+                text_chars,
+                constraints,
+                buffers,
+            ),
+        }
+    }
+
+    /// Pick the trie storage for [`search_unsorted_impl`](Self::search_unsorted_impl): the owned
+    /// nodes, or the serialized records of an engine loaded with
+    /// [`from_shared_bytes`](Self::from_shared_bytes).
+    #[inline]
+    #[allow(clippy::too_many_arguments)] // the haystack's three views plus the scratch buffers
+    fn search_graphemes<'a, G: GraphemeStorage>(
+        &'a self,
+        haystack: &'a str,
+        similarity_threshold: f32,
+        graphemes: &G,
+        text_chars: &[char],
+        constraints: SpanConstraints<'_>,
+        buffers: &mut SearchBuffers,
+    ) -> FuzzyMatches<'a> {
+        match &self.shared {
+            Some(shared) => self.search_trie(
+                shared.view(),
+                haystack,
+                similarity_threshold,
+                graphemes,
+                text_chars,
+                constraints,
+                buffers,
+            ),
+            None => self.search_trie(
+                OwnedTrie {
+                    nodes: &self.nodes,
+                    mappings: &self.mappings,
+                },
+                haystack,
+                similarity_threshold,
+                graphemes,
+                text_chars,
+                constraints,
+                buffers,
+            ),
+        }
+    }
+
+    /// Select the monomorphized [`search_unsorted_impl`](Self::search_unsorted_impl) for the
+    /// engine's configuration: mappings on/off and the fast-path edit ceiling. A custom substitution
+    /// model also takes the mappings path, whose exact transitions compare whole graphemes rather
+    /// than first `char`s.
+    #[inline]
+    #[allow(clippy::too_many_arguments)] // the haystack's three views plus the scratch buffers
+    fn search_trie<'a, T: TrieView, G: GraphemeStorage>(
+        &'a self,
+        trie: T,
+        haystack: &'a str,
+        similarity_threshold: f32,
+        g: &G,
+        text_chars: &[char],
+        constraints: SpanConstraints<'_>,
+        buffers: &mut SearchBuffers,
+    ) -> FuzzyMatches<'a> {
+        let mappings = self.has_mappings() || self.substitution_cost.is_some();
+        // An alignment search traces every state it enqueues. It only takes the general
+        // (`within_limits`) instance, so the fast-path ones carry no tracing code at all.
+        if constraints.alignment {
+            return if mappings {
+                self.search_unsorted_impl::<true, false, 255, true, _, _>(
+                    trie,
+                    haystack,
+                    similarity_threshold,
+                    g,
+                    text_chars,
+                    constraints,
+                    buffers,
+                )
             } else {
-                match self.max_edits_fast {
-                    1 => self.search_unsorted_impl::<true, true, 1, _>(
-                        haystack,
-                        similarity_threshold,
-                        &g,
-                        &text_chars,
-                    ),
-                    2 => self.search_unsorted_impl::<true, false, 2, _>(
-                        haystack,
-                        similarity_threshold,
-                        &g,
-                        &text_chars,
-                    ),
-                    3 => self.search_unsorted_impl::<true, false, 3, _>(
-                        haystack,
-                        similarity_threshold,
-                        &g,
-                        &text_chars,
-                    ),
-                    4 => self.search_unsorted_impl::<true, false, 4, _>(
-                        haystack,
-                        similarity_threshold,
-                        &g,
-                        &text_chars,
-                    ),
-                    5 => self.search_unsorted_impl::<true, false, 5, _>(
-                        haystack,
-                        similarity_threshold,
-                        &g,
-                        &text_chars,
-                    ),
-                    6 => self.search_unsorted_impl::<true, false, 6, _>(
-                        haystack,
-                        similarity_threshold,
-                        &g,
-                        &text_chars,
-                    ),
-                    _ => self.search_unsorted_impl::<true, false, 255, _>(
-                        haystack,
-                        similarity_threshold,
-                        &g,
-                        &text_chars,
-                    ),
-                }
+                self.search_unsorted_impl::<false, false, 255, true, _, _>(
+                    trie,
+                    haystack,
+                    similarity_threshold,
+                    g,
+                    text_chars,
+                    constraints,
+                    buffers,
+                )
+            };
+        }
+        if mappings {
+            match self.max_edits_fast {
+                1 => self.search_unsorted_impl::<true, true, 1, false, _, _>(
+                    trie,
+                    haystack,
+                    similarity_threshold,
+                    g,
+                    text_chars,
+                    constraints,
+                    buffers,
+                ),
+                2 => self.search_unsorted_impl::<true, false, 2, false, _, _>(
+                    trie,
+                    haystack,
+                    similarity_threshold,
+                    g,
+                    text_chars,
+                    constraints,
+                    buffers,
+                ),
+                3 => self.search_unsorted_impl::<true, false, 3, false, _, _>(
+                    trie,
+                    haystack,
+                    similarity_threshold,
+                    g,
+                    text_chars,
+                    constraints,
+                    buffers,
+                ),
+                4 => self.search_unsorted_impl::<true, false, 4, false, _, _>(
+                    trie,
+                    haystack,
+                    similarity_threshold,
+                    g,
+                    text_chars,
+                    constraints,
+                    buffers,
+                ),
+                5 => self.search_unsorted_impl::<true, false, 5, false, _, _>(
+                    trie,
+                    haystack,
+                    similarity_threshold,
+                    g,
+                    text_chars,
+                    constraints,
+                    buffers,
+                ),
+                6 => self.search_unsorted_impl::<true, false, 6, false, _, _>(
+                    trie,
+                    haystack,
+                    similarity_threshold,
+                    g,
+                    text_chars,
+                    constraints,
+                    buffers,
+                ),
+                _ => self.search_unsorted_impl::<true, false, 255, false, _, _>(
+                    trie,
+                    haystack,
+                    similarity_threshold,
+                    g,
+                    text_chars,
+                    constraints,
+                    buffers,
+                ),
+            }
+        } else {
+            match self.max_edits_fast {
+                1 => self.search_unsorted_impl::<false, true, 1, false, _, _>(
+                    trie,
+                    haystack,
+                    similarity_threshold,
+                    g,
+                    text_chars,
+                    constraints,
+                    buffers,
+                ),
+                2 => self.search_unsorted_impl::<false, false, 2, false, _, _>(
+                    trie,
+                    haystack,
+                    similarity_threshold,
+                    g,
+                    text_chars,
+                    constraints,
+                    buffers,
+                ),
+                3 => self.search_unsorted_impl::<false, false, 3, false, _, _>(
+                    trie,
+                    haystack,
+                    similarity_threshold,
+                    g,
+                    text_chars,
+                    constraints,
+                    buffers,
+                ),
+                4 => self.search_unsorted_impl::<false, false, 4, false, _, _>(
+                    trie,
+                    haystack,
+                    similarity_threshold,
+                    g,
+                    text_chars,
+                    constraints,
+                    buffers,
+                ),
+                5 => self.search_unsorted_impl::<false, false, 5, false, _, _>(
+                    trie,
+                    haystack,
+                    similarity_threshold,
+                    g,
+                    text_chars,
+                    constraints,
+                    buffers,
+                ),
+                6 => self.search_unsorted_impl::<false, false, 6, false, _, _>(
+                    trie,
+                    haystack,
+                    similarity_threshold,
+                    g,
+                    text_chars,
+                    constraints,
+                    buffers,
+                ),
+                _ => self.search_unsorted_impl::<false, false, 255, false, _, _>(
+                    trie,
+                    haystack,
+                    similarity_threshold,
+                    g,
+                    text_chars,
+                    constraints,
+                    buffers,
+                ),
             }
-        })
+        }
     }
 
-    /// Build the `Vec<(usize, Cow<str>)>` grapheme list for non-ASCII haystacks.
-    fn build_unicode_graphemes<'a>(&'a self, haystack: &'a str) -> Vec<(usize, Cow<'a, str>)> {
-        let mut vec = Vec::new();
+    /// Build the `Vec<(usize, Cow<str>)>` grapheme list for non-ASCII haystacks, into the (empty)
+    /// `vec`.
+    fn build_unicode_graphemes<'a>(
+        &'a self,
+        haystack: &'a str,
+        mut vec: Vec<(usize, Cow<'a, str>)>,
+    ) -> Vec<(usize, Cow<'a, str>)> {
         vec.extend(haystack.grapheme_indices(true).map(|(byte, g)| {
             // Only allocate a lowercased copy when the grapheme could actually change. For
             // an all-ASCII grapheme with no uppercase byte (spaces, digits, punctuation, and
@@ -415,18 +654,24 @@ impl FuzzyAhoCorasick {
         vec
     }
 
+    #[allow(clippy::too_many_arguments)] // the haystack's three views plus the scratch buffers
     fn search_unsorted_impl<
         'a,
         const MAPPINGS: bool,
         const WINDOW_SKIP: bool,
         const MAX_EDITS_FAST: u8,
+        const TRACE: bool,
+        T: TrieView,
         G: GraphemeStorage,
     >(
         &'a self,
+        trie: T,
         haystack: &'a str,
         similarity_threshold: f32,
         graphemes: &G,
         text_chars: &[char],
+        constraints: SpanConstraints<'_>,
+        buffers: &mut SearchBuffers,
     ) -> FuzzyMatches<'a> {
         if text_chars.is_empty() {
             return FuzzyMatches {
@@ -441,7 +686,12 @@ impl FuzzyAhoCorasick {
 
         // Keyed by (start_byte, end_byte, pattern_index). Uses the fast FxHash hasher instead of
         // the default SipHash: keys are small integer tuples looked up on every accepted match.
-        let mut best: FxHashMap<(usize, usize, usize), FuzzyMatch> = FxHashMap::default();
+        let SearchBuffers {
+            queue,
+            visited,
+            best,
+            arena,
+        } = buffers;
         // Cap the reservation: `best` only ever holds one entry per accepted match span (typically a
         // handful), yet `patterns.len() * 4` pre-sizes it to ~4x the automaton's total pattern count.
         // On a large automaton that is megabytes of untouched table per search; the cap keeps the
@@ -451,7 +701,7 @@ impl FuzzyAhoCorasick {
 
         // Pre-allocate queue - size based on beam width or a generous default. The default
         // of 128 avoids the first-window realloc (profiled at ~0.3% of search time with 64).
-        let mut queue: Vec<State> = Vec::with_capacity(self.beam_width.unwrap_or(128));
+        queue.reserve(self.beam_width.unwrap_or(128));
 
         // Visited set for state deduplication, reused (cleared) per start window. Insertions and
         // deletions can reach the same automaton position via exponentially many distinct paths;
@@ -459,7 +709,8 @@ impl FuzzyAhoCorasick {
         // agree on automaton position, matched span, and per-edit-type counts behave identically
         // in the future, so only the lowest-penalty one needs to be expanded. FxHash is used
         // because the key is an integer tuple hashed once per expanded state (the hottest map).
-        let mut visited: FxHashMap<VisitedKey, f32> = FxHashMap::default();
+        // A reused map still holds the last window of the previous search.
+        visited.clear();
         // Pre-warm the visited map to avoid incremental rehashing (0→4→8→16…) during the
         // first few windows. Profiling showed `reserve_rehash` at ~3.5% of search time for
         // texts under 128 graphemes because the map started at capacity 0 and grew on every
@@ -483,10 +734,12 @@ impl FuzzyAhoCorasick {
         // penalty than this can never reach the threshold. The root reaches every pattern, so its
         // per-node coefficients give exactly the global bound (longest/heaviest pattern). See
         // `Node::prune_len` for the derivation.
-        let root = &self.nodes[0];
-        let max_penalties = root.prune_len - root.prune_len_over_weight * similarity_threshold;
+        let root = trie.node(0);
+        let max_penalties = root.prune_len() - root.prune_len_over_weight() * similarity_threshold;
         // Per-substitution similarity floor (0.0 = no floor); hoisted out of the hot loop.
         let min_symbol_similarity = self.min_symbol_similarity;
+        // Custom substitution model, if any; `None` keeps the inlined similarity-table lookup.
+        let substitution_cost = self.substitution_cost.as_ref();
         // Fast-path edit ceiling: MAX_EDITS_FAST is a const generic so the compiler can
         // eliminate the `!= 255` checks and dead-code the `else` (within_limits) branches.
         // `255` disables the fast path; otherwise the hot loop checks `edits <= MAX_EDITS_FAST`
@@ -500,18 +753,19 @@ impl FuzzyAhoCorasick {
         // skips ~70% of windows for typical inputs, saving the visited-check + edge-scan
         // overhead for non-matching windows. Only applies when: exactly 1 edit, no
         // multi-char mappings, root has no output (no empty patterns), and no root child
-        // has an output (no 1-char patterns).
+        // has an output (no 1-char patterns), and not under a start anchor (whose single window may
+        // open with insertions).
         let window_skip: Option<(u128, u128)> =
-            if WINDOW_SKIP && !MAPPINGS && root.output.is_empty() {
+            if WINDOW_SKIP && !MAPPINGS && !root.has_output() && !constraints.anchored.start() {
                 let mut first = root.single_char_edge_bits();
                 let mut second = 0u128;
                 let mut child_output = false;
-                for edge in &root.edges {
-                    let child = &self.nodes[edge.next() as usize];
+                for edge in root.edges() {
+                    let child = trie.node(edge.next());
                     let child_bits = child.single_char_edge_bits();
                     second |= child_bits;
                     first |= child_bits;
-                    if !child.output.is_empty() {
+                    if child.has_output() {
                         child_output = true;
                     }
                 }
@@ -527,10 +781,21 @@ impl FuzzyAhoCorasick {
         let mut effective_beam = self.beam_width;
         let mut states_expanded = 0usize;
 
+        let SpanConstraints {
+            boundaries,
+            anchored,
+            leftmost_only,
+            first_window,
+            ..
+        } = constraints;
+        let (anchored_start, anchored_end) = (anchored.start(), anchored.end());
+
         trace!(
             "=== fuzzy_search on {haystack:?} (similarity_threshold {similarity_threshold:.2}) ===",
         );
-        for start in 0..text_chars.len() {
+        // A start anchor needs only the first window.
+        let windows = if anchored_start { 1 } else { text_chars.len() };
+        for start in first_window..windows {
             // 2-gram window skip: cheaply reject windows that cannot produce a match.
             if let Some((first_bits, second_bits)) = window_skip {
                 let ch = text_chars[start];
@@ -551,7 +816,6 @@ impl FuzzyAhoCorasick {
                 }
                 // text[start] in first_chars or non-ASCII: don't skip
             }
-
             trace!(
                 "=== new window at grapheme #{start} ({:?}) ===",
                 graphemes.gs_text(start)
@@ -559,6 +823,7 @@ impl FuzzyAhoCorasick {
 
             queue.clear();
             visited.clear();
+            arena.clear();
             let start = start as u32;
             queue.push(State {
                 node: 0,
@@ -568,6 +833,11 @@ impl FuzzyAhoCorasick {
                 penalties: 0.,
                 edits: 0,
                 packed_counts: 0,
+                trace: if TRACE {
+                    alignment::record(arena, 0, None, 0, start)
+                } else {
+                    0
+                },
                 #[cfg(debug_assertions)]
                 notes: vec![],
             });
@@ -595,6 +865,7 @@ impl FuzzyAhoCorasick {
                     penalties,
                     edits,
                     packed_counts,
+                    trace,
                     ..
                 } = queue[q_idx];
                 #[cfg(debug_assertions)]
@@ -627,7 +898,7 @@ impl FuzzyAhoCorasick {
                     }
                 }
 
-                let node_ref = &self.nodes[node as usize];
+                let node_ref = trie.node(node);
 
                 // Early pruning against this node's own (tight) ceiling: a state whose penalties
                 // exceed what the longest/heaviest pattern still reachable from here allows cannot
@@ -636,13 +907,11 @@ impl FuzzyAhoCorasick {
                 // global `max_penalties` used for the push guards, and it reuses the node reference
                 // already loaded below, so it costs nothing extra on the hot path.
                 if penalties
-                    > node_ref.prune_len - node_ref.prune_len_over_weight * similarity_threshold
+                    > node_ref.prune_len() - node_ref.prune_len_over_weight() * similarity_threshold
                 {
                     continue;
                 }
 
-                let Node { output, edges, .. } = node_ref;
-
                 // Remaining penalty budget for push-time guards. Computing this once saves
                 // an FP add per guard (substitution, swap, insertion, deletion).
                 let remaining = max_penalties - penalties;
@@ -651,31 +920,45 @@ impl FuzzyAhoCorasick {
                 // of re-deriving them (a pattern lookup) up to four times per state. Skip the lookup
                 // entirely in the common case where no pattern has its own limits.
                 let node_limits = if has_pattern_limits {
-                    self.get_node_limits(node)
+                    self.get_node_limits(trie, node)
                 } else {
                     None
                 };
 
-                if !output.is_empty() {
+                // Under a `Boundary`, a match starting or ending mid-word is dropped; the state still
+                // expands, since a longer span may end on a boundary. Checked here rather than per
+                // window to keep the unbounded path's loop unchanged. An end anchor only accepts a
+                // state that has consumed the whole haystack, its trailing insertions included.
+                if node_ref.has_output()
+                    && (!anchored_end || j == text_len)
+                    && boundaries.is_none_or(|boundaries| {
+                        let end = if anchored_end { j } else { matched_end };
+                        boundaries[graphemes.gs_byte_offset(start as usize)]
+                            && (end as usize >= text_chars.len()
+                                || boundaries[graphemes.gs_byte_offset(end as usize)])
+                    })
+                {
                     let insertions = (packed_counts & 0xFF) as NumEdits;
                     let deletions = ((packed_counts >> 8) & 0xFF) as NumEdits;
                     let substitutions = ((packed_counts >> 16) & 0xFF) as NumEdits;
                     let swaps = ((packed_counts >> 24) & 0xFF) as NumEdits;
                     // The matched span (and hence its byte offsets and text slice) is a property of
                     // the state, not of the individual pattern ending here, so compute it once for
-                    // the whole `output` list instead of per pattern.
-                    let start_byte = if (matched_start as usize) < text_chars.len() {
-                        graphemes.gs_byte_offset(matched_start as usize)
+                    // the whole `output` list instead of per pattern. Anchored edges cover the
+                    // insertions made there.
+                    let span_start = if anchored_start { start } else { matched_start };
+                    let span_end = if anchored_end { j } else { matched_end };
+                    let start_byte = if (span_start as usize) < text_chars.len() {
+                        graphemes.gs_byte_offset(span_start as usize)
                     } else {
                         0
                     };
-                    let end_byte = if (matched_end as usize) < text_chars.len() {
-                        graphemes.gs_byte_offset(matched_end as usize)
+                    let end_byte = if (span_end as usize) < text_chars.len() {
+                        graphemes.gs_byte_offset(span_end as usize)
                     } else {
                         haystack.len()
                     };
-                    let text = &haystack[start_byte..end_byte];
-                    for &pattern_index in output {
+                    for pattern_index in node_ref.output() {
                         let pattern_index = pattern_index as usize;
                         if MAX_EDITS_FAST != 255 {
                             if edits > MAX_EDITS_FAST {
@@ -702,37 +985,35 @@ impl FuzzyAhoCorasick {
                             continue;
                         }
 
+                        let alignment = || {
+                            TRACE.then(|| {
+                                alignment::reconstruct(
+                                    trie,
+                                    arena,
+                                    trace,
+                                    &self.patterns[pattern_index],
+                                    self.case_insensitive,
+                                    graphemes,
+                                    haystack.len(),
+                                )
+                            })
+                        };
+                        let candidate = || Candidate {
+                            insertions,
+                            deletions,
+                            substitutions,
+                            swaps,
+                            edits,
+                            similarity,
+                            alignment: alignment(),
+                        };
                         best.entry(key)
                             .and_modify(|entry| {
                                 if similarity > entry.similarity {
-                                    *entry = FuzzyMatch {
-                                        insertions,
-                                        deletions,
-                                        substitutions,
-                                        edits,
-                                        swaps,
-                                        pattern_index,
-                                        start: start_byte,
-                                        end: end_byte,
-                                        pattern: &self.patterns[pattern_index],
-                                        similarity,
-                                        text,
-                                    };
+                                    *entry = candidate();
                                 }
                             })
-                            .or_insert_with(|| FuzzyMatch {
-                                insertions,
-                                deletions,
-                                substitutions,
-                                edits,
-                                swaps,
-                                pattern_index,
-                                start: start_byte,
-                                end: end_byte,
-                                pattern: &self.patterns[pattern_index],
-                                similarity,
-                                text,
-                            });
+                            .or_insert_with(candidate);
                     }
                 }
 
@@ -792,6 +1073,12 @@ impl FuzzyAhoCorasick {
                             penalties,
                             edits,
                             packed_counts,
+                            trace: if TRACE {
+                                let step = Some(Step::Match);
+                                alignment::record(arena, trace, step, next_node, j + 1)
+                            } else {
+                                0
+                            },
                             #[cfg(debug_assertions)]
                             notes: notes.clone(),
                         });
@@ -811,7 +1098,7 @@ impl FuzzyAhoCorasick {
                     };
                     if subst_ok {
                         // `current_ch` was already computed above from `gs_first_char(j)`.
-                        for edge in edges {
+                        for (edge_index, edge) in node_ref.edges().enumerate() {
                             let next_node = edge.next();
                             // Skip the exact transition (already enqueued above). Its target is
                             // reached with zero penalty and no extra edit, so any edge leading to
@@ -821,7 +1108,15 @@ impl FuzzyAhoCorasick {
                                 continue;
                             }
                             // substitution
-                            let sim = self.get_similarity(edge.first_char, current_ch);
+                            let sim = match substitution_cost {
+                                None => self.get_similarity(edge.first_char, current_ch),
+                                Some(cost) => node_ref.edge_similarity(
+                                    edge_index,
+                                    cost,
+                                    graphemes.gs_text(j as usize),
+                                    current_ch,
+                                ),
+                            };
                             // Weakest-link floor: reject a too-dissimilar character outright.
                             if sim < min_symbol_similarity {
                                 continue;
@@ -837,8 +1132,8 @@ impl FuzzyAhoCorasick {
                             // only do exact match and output check. If the child has no
                             // output and no edge matching text[j+1], skip the push.
                             if is_last_edit {
-                                let child = &self.nodes[next_node as usize];
-                                if child.output.is_empty()
+                                let child = trie.node(next_node);
+                                if !child.has_output()
                                     && next_ch_opt
                                         .is_none_or(|ch| !child.has_matching_edge_char(ch))
                                 {
@@ -868,6 +1163,12 @@ impl FuzzyAhoCorasick {
                                 penalties: penalties + penalty,
                                 edits: edits + 1,
                                 packed_counts: packed_counts + 0x1_0000,
+                                trace: if TRACE {
+                                    let step = Some(Step::Sub);
+                                    alignment::record(arena, trace, step, next_node, j + 1)
+                                } else {
+                                    0
+                                },
                                 #[cfg(debug_assertions)]
                                 notes,
                             });
@@ -880,21 +1181,19 @@ impl FuzzyAhoCorasick {
                         // loop is unchanged for callers without mappings. Each precomputed mapping
                         // consumes a fixed haystack grapheme sequence and jumps to the node the
                         // mapping's pattern-side reaches, counting as one substitution.
-                        if MAPPINGS && let Some(mapping_transitions) = self.mappings.get(&node) {
-                            for mt in mapping_transitions {
+                        if MAPPINGS {
+                            for mt in trie.mappings(node) {
                                 // A mapping's haystack side is a handful of graphemes at most.
-                                let hlen = mt.haystack.len() as u32;
+                                let hlen = mt.haystack_len() as u32;
                                 if j + hlen > text_len {
                                     continue;
                                 }
-                                let hay_matches =
-                                    mt.haystack.iter().enumerate().all(|(k, g)| {
-                                        graphemes.gs_text(j as usize + k) == g.as_ref()
-                                    });
+                                let hay_matches = (0..hlen as usize)
+                                    .all(|k| mt.haystack_eq(k, graphemes.gs_text(j as usize + k)));
                                 if !hay_matches {
                                     continue;
                                 }
-                                let new_penalties = penalties + mt.penalty;
+                                let new_penalties = penalties + mt.penalty();
                                 if new_penalties > max_penalties {
                                     continue;
                                 }
@@ -903,19 +1202,27 @@ impl FuzzyAhoCorasick {
                                 #[cfg(debug_assertions)]
                                 notes.push(format!(
                                     "map {:?} (pen={:.2}) (subst->{}, edits->{})",
-                                    mt.haystack,
-                                    mt.penalty,
+                                    (0..hlen as usize)
+                                        .map(|k| graphemes.gs_text(j as usize + k))
+                                        .collect::<Vec<_>>(),
+                                    mt.penalty(),
                                     ((packed_counts >> 16) & 0xFF) + 1,
                                     edits + 1
                                 ));
                                 queue.push(State {
-                                    node: mt.next,
+                                    node: mt.next(),
                                     j: j + hlen,
                                     matched_start: matched_start_next,
                                     matched_end: j + hlen,
                                     penalties: new_penalties,
                                     edits: edits + 1,
                                     packed_counts: packed_counts + 0x1_0000,
+                                    trace: if TRACE {
+                                        let step = Some(Step::Sub);
+                                        alignment::record(arena, trace, step, mt.next(), j + hlen)
+                                    } else {
+                                        0
+                                    },
                                     #[cfg(debug_assertions)]
                                     notes,
                                 });
@@ -947,7 +1254,7 @@ impl FuzzyAhoCorasick {
                                 .gs_find_transition(node_ref, (j + 1) as usize, next_ch)
                                 .and_then(|x| {
                                     graphemes.gs_find_transition(
-                                        &self.nodes[x as usize],
+                                        trie.node(x),
                                         j as usize,
                                         current_ch,
                                     )
@@ -956,12 +1263,11 @@ impl FuzzyAhoCorasick {
                             node_ref
                                 .find_transition_char_no_mappings(next_ch)
                                 .and_then(|x| {
-                                    self.nodes[x as usize]
-                                        .find_transition_char_no_mappings(current_ch)
+                                    trie.node(x).find_transition_char_no_mappings(current_ch)
                                 })
                         } && (MAX_EDITS_FAST != 255
                             || self.within_limits_swap_ahead(
-                                self.get_node_limits(node2),
+                                self.get_node_limits(trie, node2),
                                 edits,
                                 (packed_counts >> 24) as NumEdits,
                             ))
@@ -982,6 +1288,12 @@ impl FuzzyAhoCorasick {
                                 penalties: penalties + self.penalties.swap,
                                 edits: edits + 1,
                                 packed_counts: packed_counts + 0x100_0000,
+                                trace: if TRACE {
+                                    let step = Some(Step::Swap);
+                                    alignment::record(arena, trace, step, node2, j + 2)
+                                } else {
+                                    0
+                                },
                                 #[cfg(debug_assertions)]
                                 notes,
                             });
@@ -991,7 +1303,7 @@ impl FuzzyAhoCorasick {
                     //
                     // 3a) Insertion (skip a haystack character)
                     //
-                    if (matched_start != matched_end || matched_start != j)
+                    if (matched_start != matched_end || matched_start != j || anchored_start)
                         && self.penalties.insertion <= remaining
                         && if MAX_EDITS_FAST == 255 {
                             self.within_limits_insertion_ahead(
@@ -1003,7 +1315,7 @@ impl FuzzyAhoCorasick {
                             edits < MAX_EDITS_FAST
                         }
                         && !(is_last_edit
-                            && output.is_empty()
+                            && !node_ref.has_output()
                             && next_ch_opt.is_none_or(|ch| !node_ref.has_matching_edge_char(ch)))
                     {
                         #[cfg(debug_assertions)]
@@ -1023,6 +1335,12 @@ impl FuzzyAhoCorasick {
                             penalties: penalties + self.penalties.insertion,
                             edits: edits + 1,
                             packed_counts: packed_counts + 1,
+                            trace: if TRACE {
+                                let step = Some(Step::Ins);
+                                alignment::record(arena, trace, step, node, j + 1)
+                            } else {
+                                0
+                            },
                             #[cfg(debug_assertions)]
                             notes,
                         });
@@ -1052,11 +1370,11 @@ impl FuzzyAhoCorasick {
                     } else {
                         None
                     };
-                    for edge in edges {
+                    for edge in node_ref.edges() {
                         let next_node2 = edge.next();
                         if is_last_edit {
-                            let child = &self.nodes[next_node2 as usize];
-                            if child.output.is_empty()
+                            let child = trie.node(next_node2);
+                            if !child.has_output()
                                 && current_ch_opt.is_none_or(|ch| !child.has_matching_edge_char(ch))
                             {
                                 continue;
@@ -1082,6 +1400,12 @@ impl FuzzyAhoCorasick {
                             penalties: penalties + self.penalties.deletion,
                             edits: edits + 1,
                             packed_counts: packed_counts + 0x100,
+                            trace: if TRACE {
+                                let step = Some(Step::Del);
+                                alignment::record(arena, trace, step, next_node2, j)
+                            } else {
+                                0
+                            },
                             #[cfg(debug_assertions)]
                             notes,
                         });
@@ -1101,6 +1425,12 @@ impl FuzzyAhoCorasick {
                     effective_beam = Some(width);
                 }
             }
+
+            // Every match from a later window starts further right, so once a window has produced
+            // one the leftmost matches are all known.
+            if leftmost_only && !best.is_empty() {
+                break;
+            }
         }
         // Collect matches from the `best` map. The order is the hash-bucket order of FxHashMap,
         // which is deterministic (FxHash has no random seed) but unrelated to match position.
@@ -1109,10 +1439,20 @@ impl FuzzyAhoCorasick {
         // is needed here. Users of `search_unsorted` are documented to receive matches "in no
         // particular order."
         let inner: Vec<FuzzyMatch> = best
-            .into_values()
-            .map(|mut m| {
-                m.text = &haystack[m.start..m.end];
-                m
+            .drain()
+            .map(|((start, end, pattern_index), candidate)| FuzzyMatch {
+                insertions: candidate.insertions,
+                deletions: candidate.deletions,
+                substitutions: candidate.substitutions,
+                swaps: candidate.swaps,
+                edits: candidate.edits,
+                pattern_index,
+                pattern: &self.patterns[pattern_index],
+                start,
+                end,
+                similarity: candidate.similarity,
+                text: &haystack[start..end],
+                alignment: candidate.alignment,
             })
             .collect();
         FuzzyMatches { haystack, inner }
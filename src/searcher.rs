@@ -0,0 +1,224 @@
+//! Reusable search buffers: a [`Searcher`] keeps the scratch space of a search (the per-grapheme
+//! arrays, the BFS frontier and its dedup map, the best-per-span table) between calls, so a hot loop
+//! of short searches stops paying for those allocations on every call.
+
+use crate::search::Scratch;
+use crate::{FuzzyAhoCorasick, FuzzyMatch, FuzzyMatches, SearchError, SearchOptions, Segment};
+use std::borrow::Cow;
+
+/// A [`FuzzyAhoCorasick`] paired with scratch buffers that are reused from one search to the next.
+///
+/// The engine's own methods allocate their working buffers afresh on every call. A `Searcher`
+/// offers the same methods but keeps those buffers, grown to the largest haystack seen so far, so
+/// once warm a search allocates little beyond the matches it returns. Results are identical to the
+/// engine's. Searching takes `&mut self`, so keep one `Searcher` per thread; the engine itself is
+/// shared. Get one with [`FuzzyAhoCorasick::searcher`] or [`Searcher::new`].
+///
+/// ```
+/// use fuzzy_aho_corasick::{FuzzyAhoCorasickBuilder, FuzzyLimits, SearchOptions};
+///
+/// let engine = FuzzyAhoCorasickBuilder::new()
+///     .fuzzy(FuzzyLimits::new().edits(1))
+///     .build(["apple", "banana"]);
+/// let opts = SearchOptions::new().threshold(0.8).sorted().non_overlapping();
+/// let mut searcher = engine.searcher();
+/// for (record, expected) in [("an aple", "aple"), ("a bananna", "bananna")] {
+///     let matches = searcher.search(record, &opts).unwrap();
+///     assert_eq!(matches.inner[0].text, expected);
+/// }
+/// ```
+pub struct Searcher<'e> {
+    engine: &'e FuzzyAhoCorasick,
+    scratch: Scratch,
+}
+
+impl FuzzyAhoCorasick {
+    /// A [`Searcher`] for this engine, reusing its buffers across searches.
+    #[must_use]
+    pub fn searcher(&self) -> Searcher<'_> {
+        Searcher::new(self)
+    }
+}
+
+impl<'e> Searcher<'e> {
+    /// A searcher for `engine` with empty buffers; nothing is allocated until the first search.
+    #[must_use]
+    pub fn new(engine: &'e FuzzyAhoCorasick) -> Self {
+        Self {
+            engine,
+            scratch: Scratch::default(),
+        }
+    }
+
+    /// The engine this searcher runs.
+    #[must_use]
+    pub fn engine(&self) -> &'e FuzzyAhoCorasick {
+        self.engine
+    }
+
+    /// [`FuzzyAhoCorasick::search`] with the reused buffers.
+    ///
+    /// # Errors
+    /// Propagates [`SearchError`] when the haystack is too large to index — see
+    /// [`FuzzyAhoCorasick::search`].
+    pub fn search<'a>(
+        &mut self,
+        haystack: &'a str,
+        opts: &SearchOptions,
+    ) -> Result<FuzzyMatches<'a>, SearchError>
+    where
+        'e: 'a,
+    {
+        self.engine.search_in(haystack, opts, &mut self.scratch)
+    }
+
+    /// [`FuzzyAhoCorasick::is_match`] with the reused buffers.
+    ///
+    /// # Errors
+    /// Propagates [`SearchError`] when the haystack is too large to index — see
+    /// [`FuzzyAhoCorasick::search`].
+    pub fn is_match(&mut self, haystack: &str, opts: &SearchOptions) -> Result<bool, SearchError> {
+        Ok(!self
+            .engine
+            .raw_in(haystack, opts, true, &mut self.scratch)?
+            .is_empty())
+    }
+
+    /// [`FuzzyAhoCorasick::find_first`] with the reused buffers.
+    ///
+    /// # Errors
+    /// Propagates [`SearchError`] when the haystack is too large to index — see
+    /// [`FuzzyAhoCorasick::search`].
+    pub fn find_first<'a>(
+        &mut self,
+        haystack: &'a str,
+        opts: &SearchOptions,
+    ) -> Result<Option<FuzzyMatch<'a>>, SearchError>
+    where
+        'e: 'a,
+    {
+        Ok(self
+            .engine
+            .raw_in(haystack, opts, true, &mut self.scratch)?
+            .into_first(opts))
+    }
+
+    /// [`FuzzyAhoCorasick::lookup`] with the reused buffers.
+    ///
+    /// # Errors
+    /// Propagates [`SearchError`] when the query is too large to index — see
+    /// [`FuzzyAhoCorasick::search`].
+    pub fn lookup<'a>(&mut self, query: &'a str, k: usize) -> Result<FuzzyMatches<'a>, SearchError>
+    where
+        'e: 'a,
+    {
+        self.engine.lookup_in(query, k, &mut self.scratch)
+    }
+
+    /// [`FuzzyAhoCorasick::replace`] with the reused buffers.
+    ///
+    /// # Errors
+    /// Propagates [`SearchError`] when the haystack is too large to index — see
+    /// [`FuzzyAhoCorasick::search`].
+    pub fn replace<'a, F, S: Into<Cow<'a, str>>>(
+        &mut self,
+        text: &'a str,
+        opts: &SearchOptions,
+        callback: F,
+    ) -> Result<String, SearchError>
+    where
+        'e: 'a,
+        F: Fn(&FuzzyMatch<'a>) -> Option<S>,
+    {
+        Ok(self
+            .engine
+            .segmented_in(text, opts, &mut self.scratch)?
+            .replace(callback))
+    }
+
+    /// [`FuzzyAhoCorasick::strip_prefix`] with the reused buffers.
+    ///
+    /// # Errors
+    /// Propagates [`SearchError`] when the haystack is too large to index — see
+    /// [`FuzzyAhoCorasick::search`].
+    pub fn strip_prefix(
+        &mut self,
+        haystack: &str,
+        opts: &SearchOptions,
+    ) -> Result<String, SearchError> {
+        Ok(self
+            .engine
+            .segmented_in(haystack, opts, &mut self.scratch)?
+            .strip_prefix())
+    }
+
+    /// [`FuzzyAhoCorasick::strip_suffix`] with the reused buffers.
+    ///
+    /// # Errors
+    /// Propagates [`SearchError`] when the haystack is too large to index — see
+    /// [`FuzzyAhoCorasick::search`].
+    pub fn strip_suffix(
+        &mut self,
+        haystack: &str,
+        opts: &SearchOptions,
+    ) -> Result<String, SearchError> {
+        Ok(self
+            .engine
+            .segmented_in(haystack, opts, &mut self.scratch)?
+            .strip_suffix())
+    }
+
+    /// [`FuzzyAhoCorasick::split`] with the reused buffers.
+    ///
+    /// # Errors
+    /// Propagates [`SearchError`] when the haystack is too large to index — see
+    /// [`FuzzyAhoCorasick::search`].
+    pub fn split<'a>(
+        &mut self,
+        haystack: &'a str,
+        opts: &SearchOptions,
+    ) -> Result<impl Iterator<Item = &'a str> + use<'a, 'e>, SearchError>
+    where
+        'e: 'a,
+    {
+        Ok(self
+            .engine
+            .segmented_in(haystack, opts, &mut self.scratch)?
+            .split())
+    }
+
+    /// [`FuzzyAhoCorasick::segment_iter`] with the reused buffers.
+    ///
+    /// # Errors
+    /// Propagates [`SearchError`] when the haystack is too large to index — see
+    /// [`FuzzyAhoCorasick::search`].
+    pub fn segment_iter<'a>(
+        &mut self,
+        haystack: &'a str,
+        opts: &SearchOptions,
+    ) -> Result<impl Iterator<Item = Segment<'a>> + use<'a, 'e>, SearchError>
+    where
+        'e: 'a,
+    {
+        Ok(self
+            .engine
+            .segmented_in(haystack, opts, &mut self.scratch)?
+            .segment_iter())
+    }
+
+    /// [`FuzzyAhoCorasick::segment_text`] with the reused buffers.
+    ///
+    /// # Errors
+    /// Propagates [`SearchError`] when the haystack is too large to index — see
+    /// [`FuzzyAhoCorasick::search`].
+    pub fn segment_text(
+        &mut self,
+        haystack: &str,
+        opts: &SearchOptions,
+    ) -> Result<String, SearchError> {
+        Ok(self
+            .engine
+            .segmented_in(haystack, opts, &mut self.scratch)?
+            .segment_text())
+    }
+}
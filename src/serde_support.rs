@@ -0,0 +1,35 @@
+//! Helpers for the optional `serde` feature.
+
+use crate::{FuzzyLimits, Pattern};
+use std::collections::BTreeMap;
+
+/// The deserialized form of a [`Pattern`]: everything but the derived `grapheme_len`, with the
+/// `Pattern::from` defaults for whatever is left out.
+#[derive(serde::Deserialize)]
+pub(crate) struct PatternRepr {
+    pattern: String,
+    #[serde(default)]
+    custom_unique_id: Option<usize>,
+    #[serde(default = "default_weight")]
+    weight: f32,
+    #[serde(default)]
+    limits: Option<FuzzyLimits>,
+    #[serde(default)]
+    metadata: BTreeMap<String, String>,
+}
+
+fn default_weight() -> f32 {
+    1.0
+}
+
+impl From<PatternRepr> for Pattern {
+    fn from(repr: PatternRepr) -> Self {
+        Pattern {
+            custom_unique_id: repr.custom_unique_id,
+            weight: repr.weight,
+            limits: repr.limits.map(FuzzyLimits::finalize),
+            metadata: repr.metadata,
+            ..Pattern::from(repr.pattern)
+        }
+    }
+}
@@ -0,0 +1,1154 @@
+//! Versioned binary serialization of a built [`FuzzyAhoCorasick`], so a large automaton can be
+//! compiled once (e.g. at deploy time) and loaded at process start without re-running the builder's
+//! trie construction, failure links, reach-bound fixpoint, and mapping precomputation.
+//!
+//! # Format
+//! Little-endian throughout. A fixed 24-byte header — magic `b"FZAC"`, `u32` format version, `u64`
+//! payload length, `u64` FNV-1a checksum of the payload — followed by the payload sections, in order:
+//!
+//! 1. **config** — case folding, custom substitution model, penalties, global limits, beam settings,
+//!    similarity floor, phonetic encoding;
+//! 2. **similarity** — the substitution table's `(char, char, score)` triples;
+//! 3. **patterns** — text, grapheme length, weight, unique id, per-pattern limits, metadata;
+//! 4. **nodes** — fixed [`NODE_RECORD`]-byte records: edge and output ranges, prune coefficients,
+//!    prefix weight, failure link, first pattern;
+//! 5. **edges** — fixed [`EDGE_RECORD`]-byte records: first char, packed target, grapheme in the pool;
+//! 6. **outputs** — `u32` pattern indices, sliced by the node records;
+//! 7. **mappings** — a node-sorted index of [`MAPPING_INDEX_RECORD`]-byte entries, the transitions
+//!    they point at, and the haystack-side graphemes of those transitions;
+//! 8. **grapheme pool** — the deduplicated UTF-8 text every edge and mapping grapheme points into.
+//!
+//! Every variable-length section is prefixed with a `u32` count. Sections 4–8 are flat arrays of
+//! fixed-size records that refer to each other by index, never by address, so the layout is
+//! position-independent.
+use crate::builder::DEFAULT_SIMILARITY;
+use crate::cost::{CustomCost, SubstitutionCost};
+use crate::phonetic::{PhoneticAlgorithm, PhoneticIndex};
+use crate::structs::{Edge, FxHashMap, MappingTransition, Node, Similarity};
+use crate::trie::{FlatLayout, FlatTrie, SharedTrie};
+use crate::{DeserializeError, FuzzyAhoCorasick, FuzzyLimits, FuzzyPenalties, NumEdits, Pattern};
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::sync::Arc;
+
+/// Leading bytes of every serialized automaton.
+const MAGIC: [u8; 4] = *b"FZAC";
+/// Format version written by [`FuzzyAhoCorasick::to_bytes`]; any other version is rejected on load.
+pub(crate) const FORMAT_VERSION: u32 = 1;
+/// Header size: magic, version, payload length, checksum.
+const HEADER_LEN: usize = 4 + 4 + 8 + 8;
+
+/// `edges_start, edges_len, output_start, output_len, prune_len, prune_len_over_weight, weight, fail,
+/// pattern_index` — nine 4-byte fields.
+pub(crate) const NODE_RECORD: usize = 36;
+/// `first_char, packed_next, grapheme_start, grapheme_len`.
+pub(crate) const EDGE_RECORD: usize = 16;
+/// `node, transitions_start, transitions_len`.
+pub(crate) const MAPPING_INDEX_RECORD: usize = 12;
+/// `next, penalty, graphemes_start, graphemes_len`.
+pub(crate) const MAPPING_RECORD: usize = 16;
+/// `pool_start, pool_len`.
+pub(crate) const MAPPING_GRAPHEME_RECORD: usize = 8;
+
+/// Sentinel for "no first pattern" in a node record's `pattern_index`.
+pub(crate) const NO_PATTERN: u32 = u32::MAX;
+
+/// Config flag bits.
+const FLAG_CASE_INSENSITIVE: u8 = 1;
+const FLAG_PATTERN_LIMITS: u8 = 2;
+/// The automaton was built with a custom [`SubstitutionCost`] model, which must be supplied on load.
+const FLAG_CUSTOM_COST: u8 = 4;
+/// Phonetic matching is enabled; its algorithm tag and score follow the similarity floor.
+const FLAG_PHONETIC: u8 = 8;
+
+/// 64-bit FNV-1a over `bytes`.
+fn checksum(bytes: &[u8]) -> u64 {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+    bytes
+        .iter()
+        .fold(OFFSET, |hash, &b| (hash ^ u64::from(b)).wrapping_mul(PRIME))
+}
+
+/// Read the little-endian `u32` at record field `field` (in 4-byte units) of `record`.
+#[inline]
+pub(crate) fn field_u32(record: &[u8], field: usize) -> u32 {
+    let at = field * 4;
+    u32::from_le_bytes([record[at], record[at + 1], record[at + 2], record[at + 3]])
+}
+
+/// Read the little-endian `f32` at record field `field` (in 4-byte units) of `record`.
+#[inline]
+pub(crate) fn field_f32(record: &[u8], field: usize) -> f32 {
+    f32::from_bits(field_u32(record, field))
+}
+
+/// Append-only little-endian encoder.
+#[derive(Default)]
+struct Writer {
+    buf: Vec<u8>,
+}
+
+impl Writer {
+    fn u8(&mut self, v: u8) {
+        self.buf.push(v);
+    }
+    fn u32(&mut self, v: u32) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+    fn u64(&mut self, v: u64) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+    fn f32(&mut self, v: f32) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+    fn len(&mut self, len: usize) {
+        self.u32(u32::try_from(len).expect("section exceeds the u32 record space"));
+    }
+    fn str(&mut self, s: &str) {
+        self.len(s.len());
+        self.buf.extend_from_slice(s.as_bytes());
+    }
+    fn opt_u8(&mut self, v: Option<u8>) {
+        match v {
+            Some(v) => {
+                self.u8(1);
+                self.u8(v);
+            }
+            None => self.u8(0),
+        }
+    }
+    fn opt_u64(&mut self, v: Option<u64>) {
+        match v {
+            Some(v) => {
+                self.u8(1);
+                self.u64(v);
+            }
+            None => self.u8(0),
+        }
+    }
+    fn limits(&mut self, limits: Option<&FuzzyLimits>) {
+        match limits {
+            Some(l) => {
+                self.u8(1);
+                self.opt_u8(l.insertions);
+                self.opt_u8(l.deletions);
+                self.opt_u8(l.substitutions);
+                self.opt_u8(l.swaps);
+                self.opt_u8(l.edits);
+            }
+            None => self.u8(0),
+        }
+    }
+}
+
+/// Bounds-checked little-endian decoder over a byte slice.
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+    fn take(&mut self, n: usize) -> Result<&'a [u8], DeserializeError> {
+        let end = self.pos.checked_add(n).ok_or(DeserializeError::Truncated)?;
+        let out = self
+            .bytes
+            .get(self.pos..end)
+            .ok_or(DeserializeError::Truncated)?;
+        self.pos = end;
+        Ok(out)
+    }
+    fn u8(&mut self) -> Result<u8, DeserializeError> {
+        Ok(self.take(1)?[0])
+    }
+    fn u32(&mut self) -> Result<u32, DeserializeError> {
+        Ok(field_u32(self.take(4)?, 0))
+    }
+    fn u64(&mut self) -> Result<u64, DeserializeError> {
+        let b = self.take(8)?;
+        Ok(u64::from_le_bytes([
+            b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7],
+        ]))
+    }
+    fn f32(&mut self) -> Result<f32, DeserializeError> {
+        Ok(f32::from_bits(self.u32()?))
+    }
+    fn usize(&mut self) -> Result<usize, DeserializeError> {
+        usize::try_from(self.u64()?)
+            .map_err(|_| DeserializeError::Malformed("size overflows usize"))
+    }
+    fn flag(&mut self) -> Result<bool, DeserializeError> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(DeserializeError::Malformed("invalid presence flag")),
+        }
+    }
+    fn str(&mut self) -> Result<&'a str, DeserializeError> {
+        let len = self.u32()? as usize;
+        std::str::from_utf8(self.take(len)?)
+            .map_err(|_| DeserializeError::Malformed("string is not valid UTF-8"))
+    }
+    fn opt_u8(&mut self) -> Result<Option<u8>, DeserializeError> {
+        Ok(if self.flag()? { Some(self.u8()?) } else { None })
+    }
+    fn opt_u64(&mut self) -> Result<Option<u64>, DeserializeError> {
+        Ok(if self.flag()? {
+            Some(self.u64()?)
+        } else {
+            None
+        })
+    }
+    fn limits(&mut self) -> Result<Option<FuzzyLimits>, DeserializeError> {
+        if !self.flag()? {
+            return Ok(None);
+        }
+        Ok(Some(FuzzyLimits {
+            insertions: self.opt_u8()?,
+            deletions: self.opt_u8()?,
+            substitutions: self.opt_u8()?,
+            swaps: self.opt_u8()?,
+            edits: self.opt_u8()?,
+        }))
+    }
+    /// A `u32`-counted section of `record`-byte records, returned as its byte range in the input.
+    fn records(&mut self, record: usize) -> Result<Range<usize>, DeserializeError> {
+        let count = self.u32()? as usize;
+        let len = count
+            .checked_mul(record)
+            .ok_or(DeserializeError::Truncated)?;
+        let start = self.pos;
+        self.take(len)?;
+        Ok(start..self.pos)
+    }
+}
+
+/// A serialized automaton with its header verified and the variable-length sections decoded; the
+/// flat record sections are left in place, located by `layout`.
+pub(crate) struct Parsed<'a> {
+    pub(crate) case_insensitive: bool,
+    pub(crate) has_pattern_limits: bool,
+    pub(crate) custom_cost: bool,
+    pub(crate) max_edits_fast: NumEdits,
+    pub(crate) penalties: FuzzyPenalties,
+    pub(crate) limits: Option<FuzzyLimits>,
+    pub(crate) beam_width: Option<usize>,
+    pub(crate) auto_beam: Option<(usize, usize)>,
+    pub(crate) min_symbol_similarity: f32,
+    pub(crate) phonetic: Option<(PhoneticAlgorithm, f32)>,
+    pub(crate) similarity: Similarity,
+    pub(crate) patterns: Vec<Pattern>,
+    /// The serialized automaton (header plus payload) at the start of the input.
+    pub(crate) bytes: &'a [u8],
+    pub(crate) layout: FlatLayout,
+}
+
+impl<'a> Parsed<'a> {
+    /// Verify the header and checksum, decode the variable-length sections, and bounds-check every
+    /// cross-reference in the flat sections, so the records can afterwards be indexed without
+    /// further validation.
+    pub(crate) fn parse(bytes: &'a [u8]) -> Result<Self, DeserializeError> {
+        let mut header = Reader::new(bytes);
+        if header.take(4).map_err(|_| DeserializeError::BadMagic)? != MAGIC {
+            return Err(DeserializeError::BadMagic);
+        }
+        let version = header.u32()?;
+        if version != FORMAT_VERSION {
+            return Err(DeserializeError::UnsupportedVersion {
+                found: version,
+                expected: FORMAT_VERSION,
+            });
+        }
+        let len = header.usize()?;
+        let sum = header.u64()?;
+        let payload = header.take(len)?;
+        if checksum(payload) != sum {
+            return Err(DeserializeError::ChecksumMismatch);
+        }
+
+        // Read the payload in place, so section ranges are offsets into `bytes`.
+        let bytes = &bytes[..header.pos];
+        let mut r = Reader {
+            bytes,
+            pos: HEADER_LEN,
+        };
+        let flags = r.u8()?;
+        let max_edits_fast = r.u8()?;
+        let penalties = FuzzyPenalties {
+            insertion: r.f32()?,
+            deletion: r.f32()?,
+            substitution: r.f32()?,
+            swap: r.f32()?,
+        };
+        let limits = r.limits()?;
+        let beam_width = r
+            .opt_u64()?
+            .map(usize::try_from)
+            .transpose()
+            .map_err(|_| DeserializeError::Malformed("beam width overflows usize"))?;
+        let auto_beam = if r.flag()? {
+            Some((r.usize()?, r.usize()?))
+        } else {
+            None
+        };
+        let min_symbol_similarity = r.f32()?;
+        let phonetic = if flags & FLAG_PHONETIC != 0 {
+            let algorithm = PhoneticAlgorithm::from_tag(r.u8()?)
+                .ok_or(DeserializeError::Malformed("unknown phonetic algorithm"))?;
+            Some((algorithm, r.f32()?))
+        } else {
+            None
+        };
+
+        let pair_count = r.u32()? as usize;
+        let mut pairs = Vec::with_capacity(pair_count.min(payload.len() / 12));
+        for _ in 0..pair_count {
+            let a = char::from_u32(r.u32()?);
+            let b = char::from_u32(r.u32()?);
+            let (Some(a), Some(b)) = (a, b) else {
+                return Err(DeserializeError::Malformed("similarity pair is not a char"));
+            };
+            pairs.push(((a, b), r.f32()?));
+        }
+
+        let pattern_count = r.u32()? as usize;
+        let mut patterns = Vec::with_capacity(pattern_count.min(payload.len()));
+        for _ in 0..pattern_count {
+            let pattern = r.str()?.to_owned();
+            let grapheme_len = r.usize()?;
+            let weight = r.f32()?;
+            let custom_unique_id = r
+                .opt_u64()?
+                .map(usize::try_from)
+                .transpose()
+                .map_err(|_| DeserializeError::Malformed("unique id overflows usize"))?;
+            let limits = r.limits()?;
+            let metadata_count = r.u32()? as usize;
+            let mut metadata = BTreeMap::new();
+            for _ in 0..metadata_count {
+                let key = r.str()?.to_owned();
+                metadata.insert(key, r.str()?.to_owned());
+            }
+            patterns.push(Pattern {
+                grapheme_len,
+                pattern,
+                custom_unique_id,
+                weight,
+                limits,
+                metadata,
+            });
+        }
+
+        let layout = FlatLayout {
+            nodes: r.records(NODE_RECORD)?,
+            edges: r.records(EDGE_RECORD)?,
+            outputs: r.records(4)?,
+            mapping_index: r.records(MAPPING_INDEX_RECORD)?,
+            mappings: r.records(MAPPING_RECORD)?,
+            mapping_graphemes: r.records(MAPPING_GRAPHEME_RECORD)?,
+            pool: r.records(1)?,
+        };
+        if r.pos != bytes.len() {
+            return Err(DeserializeError::Malformed(
+                "trailing bytes after the last section",
+            ));
+        }
+
+        let parsed = Self {
+            case_insensitive: flags & FLAG_CASE_INSENSITIVE != 0,
+            has_pattern_limits: flags & FLAG_PATTERN_LIMITS != 0,
+            custom_cost: flags & FLAG_CUSTOM_COST != 0,
+            max_edits_fast,
+            penalties,
+            limits,
+            beam_width,
+            auto_beam,
+            min_symbol_similarity,
+            phonetic,
+            similarity: Similarity::from_map(pairs),
+            patterns,
+            bytes,
+            layout,
+        };
+        parsed.validate()?;
+        Ok(parsed)
+    }
+
+    /// The flat sections, borrowed from the input.
+    pub(crate) fn trie(&self) -> FlatTrie<'a> {
+        self.layout.resolve(self.bytes)
+    }
+
+    /// Check that every index stored in the flat sections points inside its target section.
+    fn validate(&self) -> Result<(), DeserializeError> {
+        let trie = self.trie();
+        let pool = std::str::from_utf8(trie.pool)
+            .map_err(|_| DeserializeError::Malformed("grapheme pool is not valid UTF-8"))?;
+        let in_range = |start: u32, len: u32, section: &[u8], record: usize| {
+            (start as usize)
+                .checked_add(len as usize)
+                .is_some_and(|end| end * record <= section.len())
+        };
+        let in_pool = |start: u32, len: u32| {
+            (start as usize)
+                .checked_add(len as usize)
+                .and_then(|end| pool.get(start as usize..end))
+                .is_some()
+        };
+        let node_count = trie.node_count();
+        if node_count == 0 {
+            return Err(DeserializeError::Malformed("automaton has no root node"));
+        }
+        if node_count > Edge::MAX_NODES {
+            return Err(DeserializeError::Malformed("too many nodes"));
+        }
+        for i in 0..node_count {
+            let n = trie.node_record(i);
+            if !in_range(field_u32(n, 0), field_u32(n, 1), trie.edges, EDGE_RECORD)
+                || !in_range(field_u32(n, 2), field_u32(n, 3), trie.outputs, 4)
+            {
+                return Err(DeserializeError::Malformed("node range out of bounds"));
+            }
+            if field_u32(n, 7) as usize >= node_count {
+                return Err(DeserializeError::Malformed("failure link out of bounds"));
+            }
+            let first = field_u32(n, 8);
+            if first != NO_PATTERN && first as usize >= self.patterns.len() {
+                return Err(DeserializeError::Malformed(
+                    "node pattern index out of bounds",
+                ));
+            }
+        }
+        for e in trie.edge_records() {
+            if char::from_u32(field_u32(e, 0)).is_none() {
+                return Err(DeserializeError::Malformed("edge char is not a char"));
+            }
+            if (field_u32(e, 1) & Edge::NEXT_MASK) as usize >= node_count {
+                return Err(DeserializeError::Malformed("edge target out of bounds"));
+            }
+            if !in_pool(field_u32(e, 2), field_u32(e, 3)) {
+                return Err(DeserializeError::Malformed("edge grapheme out of bounds"));
+            }
+        }
+        if trie
+            .output_records()
+            .any(|out| out as usize >= self.patterns.len())
+        {
+            return Err(DeserializeError::Malformed(
+                "output pattern index out of bounds",
+            ));
+        }
+        let mut prev_node = None;
+        for m in trie.mapping_index_records() {
+            let node = field_u32(m, 0);
+            if node as usize >= node_count || prev_node.is_some_and(|p| p >= node) {
+                return Err(DeserializeError::Malformed("mapping index out of order"));
+            }
+            prev_node = Some(node);
+            if !in_range(
+                field_u32(m, 1),
+                field_u32(m, 2),
+                trie.mappings,
+                MAPPING_RECORD,
+            ) {
+                return Err(DeserializeError::Malformed("mapping range out of bounds"));
+            }
+        }
+        for m in trie.mapping_records() {
+            if field_u32(m, 0) as usize >= node_count {
+                return Err(DeserializeError::Malformed("mapping target out of bounds"));
+            }
+            if !in_range(
+                field_u32(m, 2),
+                field_u32(m, 3),
+                trie.mapping_graphemes,
+                MAPPING_GRAPHEME_RECORD,
+            ) {
+                return Err(DeserializeError::Malformed(
+                    "mapping graphemes out of bounds",
+                ));
+            }
+        }
+        for g in trie.mapping_grapheme_records() {
+            if !in_pool(field_u32(g, 0), field_u32(g, 1)) {
+                return Err(DeserializeError::Malformed(
+                    "mapping grapheme out of bounds",
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// The model to load with: `cost` if supplied, otherwise an error when the automaton was built
+    /// with one.
+    fn substitution_cost(
+        &self,
+        cost: Option<CustomCost>,
+    ) -> Result<Option<CustomCost>, DeserializeError> {
+        if cost.is_none() && self.custom_cost {
+            return Err(DeserializeError::SubstitutionCostRequired);
+        }
+        Ok(cost)
+    }
+
+    /// Rebuild the phonetic index from the patterns; it is derived data, so only the algorithm and
+    /// score are stored.
+    fn phonetic_index(&self) -> Option<PhoneticIndex> {
+        self.phonetic
+            .map(|(algorithm, score)| PhoneticIndex::new(algorithm, score, &self.patterns))
+    }
+
+    /// Materialise the owned automaton, scoring substitutions with `substitution_cost` if given.
+    fn into_automaton(self, substitution_cost: Option<CustomCost>) -> FuzzyAhoCorasick {
+        let phonetic = self.phonetic_index();
+        let trie = self.trie();
+        // Validated in `parse`.
+        let pool = std::str::from_utf8(trie.pool).expect("validated grapheme pool");
+        let pooled = |start: u32, len: u32| &pool[start as usize..(start + len) as usize];
+
+        #[cfg_attr(not(debug_assertions), allow(unused_mut))]
+        let mut nodes: Vec<Node> = (0..trie.node_count())
+            .map(|i| {
+                let rec = trie.node_record(i);
+                let mut node = Node::new(
+                    #[cfg(debug_assertions)]
+                    0,
+                    #[cfg(debug_assertions)]
+                    None,
+                );
+                let edges = &trie.edges[field_u32(rec, 0) as usize * EDGE_RECORD
+                    ..(field_u32(rec, 0) + field_u32(rec, 1)) as usize * EDGE_RECORD];
+                for e in edges.chunks_exact(EDGE_RECORD) {
+                    let packed = field_u32(e, 1);
+                    let grapheme = pooled(field_u32(e, 2), field_u32(e, 3));
+                    if substitution_cost.is_some() {
+                        node.edge_graphemes.push(grapheme.into());
+                    }
+                    node.edges.push(Edge::from_packed(
+                        char::from_u32(field_u32(e, 0)).expect("validated edge char"),
+                        packed,
+                    ));
+                    node.transitions
+                        .insert(grapheme.to_owned(), packed & Edge::NEXT_MASK);
+                }
+                let outputs = &trie.outputs[field_u32(rec, 2) as usize * 4
+                    ..(field_u32(rec, 2) + field_u32(rec, 3)) as usize * 4];
+                node.output = outputs.chunks_exact(4).map(|o| field_u32(o, 0)).collect();
+                node.prune_len = field_f32(rec, 4);
+                node.prune_len_over_weight = field_f32(rec, 5);
+                node.weight = field_f32(rec, 6);
+                node.fail = field_u32(rec, 7);
+                let first = field_u32(rec, 8);
+                node.pattern_index = (first != NO_PATTERN).then_some(first as usize);
+                node
+            })
+            .collect();
+
+        // Debug builds keep each node's parent and incoming grapheme for introspection; rebuild them
+        // from the edges.
+        #[cfg(debug_assertions)]
+        for parent in 0..nodes.len() {
+            let children: Vec<(String, u32)> = nodes[parent]
+                .transitions
+                .iter()
+                .map(|(g, &next)| (g.clone(), next))
+                .collect();
+            for (grapheme, next) in children {
+                nodes[next as usize].parent = parent as u32;
+                nodes[next as usize].grapheme = Some(grapheme);
+            }
+        }
+
+        let mut mappings: FxHashMap<u32, Box<[MappingTransition]>> = FxHashMap::default();
+        for idx in trie.mapping_index_records() {
+            let transitions = trie
+                .mapping_range(field_u32(idx, 1), field_u32(idx, 2))
+                .chunks_exact(MAPPING_RECORD)
+                .map(|m| MappingTransition {
+                    haystack: trie
+                        .mapping_grapheme_range(field_u32(m, 2), field_u32(m, 3))
+                        .chunks_exact(MAPPING_GRAPHEME_RECORD)
+                        .map(|g| pooled(field_u32(g, 0), field_u32(g, 1)).into())
+                        .collect(),
+                    next: field_u32(m, 0),
+                    penalty: field_f32(m, 1),
+                })
+                .collect();
+            mappings.insert(field_u32(idx, 0), transitions);
+        }
+
+        FuzzyAhoCorasick {
+            nodes,
+            patterns: self.patterns,
+            similarity: share_similarity(self.similarity),
+            substitution_cost,
+            limits: self.limits,
+            penalties: self.penalties,
+            case_insensitive: self.case_insensitive,
+            has_pattern_limits: self.has_pattern_limits,
+            max_edits_fast: self.max_edits_fast,
+            mappings,
+            beam_width: self.beam_width,
+            auto_beam: self.auto_beam,
+            min_symbol_similarity: self.min_symbol_similarity,
+            phonetic,
+            shared: None,
+        }
+    }
+
+    /// Wrap the engine configuration around the flat sections of `backing`, searched in place.
+    fn into_shared(
+        self,
+        substitution_cost: Option<CustomCost>,
+        backing: Arc<dyn AsRef<[u8]> + Send + Sync>,
+    ) -> FuzzyAhoCorasick {
+        let phonetic = self.phonetic_index();
+        FuzzyAhoCorasick {
+            nodes: Vec::new(),
+            patterns: self.patterns,
+            similarity: share_similarity(self.similarity),
+            substitution_cost,
+            limits: self.limits,
+            penalties: self.penalties,
+            case_insensitive: self.case_insensitive,
+            has_pattern_limits: self.has_pattern_limits,
+            max_edits_fast: self.max_edits_fast,
+            mappings: FxHashMap::default(),
+            beam_width: self.beam_width,
+            auto_beam: self.auto_beam,
+            min_symbol_similarity: self.min_symbol_similarity,
+            phonetic,
+            shared: Some(SharedTrie {
+                len: self.bytes.len(),
+                layout: self.layout,
+                bytes: backing,
+            }),
+        }
+    }
+}
+
+/// Prefix `payload` with the header.
+fn seal(payload: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(HEADER_LEN + payload.len());
+    out.extend_from_slice(&MAGIC);
+    out.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    out.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+    out.extend_from_slice(&checksum(payload).to_le_bytes());
+    out.extend_from_slice(payload);
+    out
+}
+
+/// Wrap a deserialized similarity table, reusing the shared default table when it matches so
+/// default-configured engines don't each carry a private copy.
+fn share_similarity(table: Similarity) -> Arc<Similarity> {
+    if table == **DEFAULT_SIMILARITY {
+        Arc::clone(&DEFAULT_SIMILARITY)
+    } else {
+        Arc::new(table)
+    }
+}
+
+/// Interns grapheme strings into one deduplicated pool, handing out `(start, len)` byte ranges.
+#[derive(Default)]
+struct Pool {
+    text: String,
+    seen: FxHashMap<String, (u32, u32)>,
+}
+
+impl Pool {
+    fn intern(&mut self, s: &str) -> (u32, u32) {
+        if let Some(&range) = self.seen.get(s) {
+            return range;
+        }
+        let range = (self.text.len() as u32, s.len() as u32);
+        self.text.push_str(s);
+        self.seen.insert(s.to_owned(), range);
+        range
+    }
+}
+
+impl FuzzyAhoCorasick {
+    /// Serialize the built automaton — nodes, edges, patterns, limits, penalties, mappings, and the
+    /// similarity table — into a self-contained, versioned byte buffer. Load it back with
+    /// [`from_bytes`](Self::from_bytes) to skip rebuilding a large dictionary at every start-up.
+    ///
+    /// The output is deterministic: the same automaton always serializes to the same bytes.
+    ///
+    /// ```
+    /// use fuzzy_aho_corasick::{FuzzyAhoCorasick, FuzzyAhoCorasickBuilder, FuzzyLimits, SearchOptions};
+    /// let engine = FuzzyAhoCorasickBuilder::new()
+    ///     .fuzzy(FuzzyLimits::new().edits(1))
+    ///     .build(["hello", "world"]);
+    /// let bytes = engine.to_bytes();
+    ///
+    /// let loaded = FuzzyAhoCorasick::from_bytes(&bytes).unwrap();
+    /// let opts = SearchOptions::new().threshold(0.8).sorted();
+    /// assert_eq!(
+    ///     loaded.search("helo wrld", &opts).unwrap().inner,
+    ///     engine.search("helo wrld", &opts).unwrap().inner,
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if a section holds more than `u32::MAX` records or pool bytes, far beyond what the
+    /// automaton's own `u32` node indices allow.
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut flags = 0;
+        if self.case_insensitive {
+            flags |= FLAG_CASE_INSENSITIVE;
+        }
+        if self.has_pattern_limits {
+            flags |= FLAG_PATTERN_LIMITS;
+        }
+        if self.substitution_cost.is_some() {
+            flags |= FLAG_CUSTOM_COST;
+        }
+        if self.phonetic.is_some() {
+            flags |= FLAG_PHONETIC;
+        }
+
+        // An engine loaded with `from_shared_bytes` already holds its serialized form; only the
+        // custom-model flag can differ from it.
+        if let Some(shared) = &self.shared {
+            let mut payload = (*shared.bytes).as_ref()[HEADER_LEN..shared.len].to_vec();
+            payload[0] = flags;
+            return seal(&payload);
+        }
+        let mut w = Writer::default();
+
+        // 1. config
+        w.u8(flags);
+        w.u8(self.max_edits_fast);
+        w.f32(self.penalties.insertion);
+        w.f32(self.penalties.deletion);
+        w.f32(self.penalties.substitution);
+        w.f32(self.penalties.swap);
+        w.limits(self.limits.as_ref());
+        w.opt_u64(self.beam_width.map(|b| b as u64));
+        match self.auto_beam {
+            Some((budget, width)) => {
+                w.u8(1);
+                w.u64(budget as u64);
+                w.u64(width as u64);
+            }
+            None => w.u8(0),
+        }
+        w.f32(self.min_symbol_similarity);
+        if let Some(phonetic) = &self.phonetic {
+            w.u8(phonetic.algorithm.tag());
+            w.f32(phonetic.score);
+        }
+
+        // 2. similarity, sorted so equal tables serialize identically
+        let mut pairs: Vec<((char, char), f32)> = self.similarity.pairs().collect();
+        pairs.sort_unstable_by_key(|&(pair, _)| pair);
+        w.len(pairs.len());
+        for ((a, b), sim) in pairs {
+            w.u32(a as u32);
+            w.u32(b as u32);
+            w.f32(sim);
+        }
+
+        // 3. patterns
+        w.len(self.patterns.len());
+        for p in &self.patterns {
+            w.str(&p.pattern);
+            w.u64(p.grapheme_len as u64);
+            w.f32(p.weight);
+            w.opt_u64(p.custom_unique_id.map(|id| id as u64));
+            w.limits(p.limits.as_ref());
+            w.len(p.metadata.len());
+            for (key, value) in &p.metadata {
+                w.str(key);
+                w.str(value);
+            }
+        }
+
+        // 4–6. nodes, edges, outputs. A node's edges are written in `edges` order — the order the
+        // search iterates them — so tie-breaking is unchanged after a round trip.
+        let mut pool = Pool::default();
+        let mut edges = Writer::default();
+        let mut outputs = Writer::default();
+        let (mut edge_count, mut output_count) = (0usize, 0usize);
+        // Each node's transitions keyed by target, so finding an edge's grapheme stays linear in
+        // the node's degree (the root of a large CJK dictionary has thousands of edges).
+        let mut graphemes: FxHashMap<u32, &str> = FxHashMap::default();
+        w.len(self.nodes.len());
+        for node in &self.nodes {
+            w.len(edge_count);
+            w.len(node.edges.len());
+            w.len(output_count);
+            w.len(node.output.len());
+            w.f32(node.prune_len);
+            w.f32(node.prune_len_over_weight);
+            w.f32(node.weight);
+            w.u32(node.fail);
+            w.u32(node.pattern_index.map_or(NO_PATTERN, |i| i as u32));
+            graphemes.clear();
+            graphemes.extend(node.transitions.iter().map(|(g, &next)| (next, g.as_str())));
+            for edge in &node.edges {
+                let grapheme = graphemes[&edge.next()]; // every edge has a transition
+                let (start, len) = pool.intern(grapheme);
+                edges.u32(edge.first_char as u32);
+                edges.u32(edge.packed());
+                edges.u32(start);
+                edges.u32(len);
+            }
+            for &out in &node.output {
+                outputs.u32(out);
+            }
+            edge_count += node.edges.len();
+            output_count += node.output.len();
+        }
+        w.len(edge_count);
+        w.buf.extend_from_slice(&edges.buf);
+        w.len(output_count);
+        w.buf.extend_from_slice(&outputs.buf);
+
+        // 7. mappings, indexed by node in ascending order
+        let mut mapped: Vec<(&u32, &Box<[MappingTransition]>)> = self.mappings.iter().collect();
+        mapped.sort_unstable_by_key(|&(&node, _)| node);
+        let mut transitions = Writer::default();
+        let mut graphemes = Writer::default();
+        let (mut transition_count, mut grapheme_count) = (0usize, 0usize);
+        w.len(mapped.len());
+        for (&node, mts) in mapped {
+            w.u32(node);
+            w.len(transition_count);
+            w.len(mts.len());
+            for mt in mts {
+                transitions.u32(mt.next);
+                transitions.f32(mt.penalty);
+                transitions.len(grapheme_count);
+                transitions.len(mt.haystack.len());
+                for g in &mt.haystack {
+                    let (start, len) = pool.intern(g);
+                    graphemes.u32(start);
+                    graphemes.u32(len);
+                }
+                grapheme_count += mt.haystack.len();
+            }
+            transition_count += mts.len();
+        }
+        w.len(transition_count);
+        w.buf.extend_from_slice(&transitions.buf);
+        w.len(grapheme_count);
+        w.buf.extend_from_slice(&graphemes.buf);
+
+        // 8. grapheme pool
+        w.str(&pool.text);
+
+        seal(&w.buf)
+    }
+
+    /// Load an automaton serialized by [`to_bytes`](Self::to_bytes). The result searches exactly like
+    /// the automaton that was serialized, including its similarity table, which is restored from
+    /// the buffer.
+    ///
+    /// # Errors
+    /// - [`DeserializeError::BadMagic`] / [`DeserializeError::UnsupportedVersion`] if `bytes` isn't a
+    ///   serialized automaton in this crate's format version;
+    /// - [`DeserializeError::ChecksumMismatch`], [`DeserializeError::Truncated`], or
+    ///   [`DeserializeError::Malformed`] if it is damaged;
+    /// - [`DeserializeError::SubstitutionCostRequired`] if the automaton was built with a custom
+    ///   [`SubstitutionCost`] model — use
+    ///   [`from_bytes_with_substitution_cost`](Self::from_bytes_with_substitution_cost).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DeserializeError> {
+        let parsed = Parsed::parse(bytes)?;
+        let cost = parsed.substitution_cost(None)?;
+        Ok(parsed.into_automaton(cost))
+    }
+
+    /// Like [`from_bytes`](Self::from_bytes), scoring substitutions with `cost`. Models are code, not
+    /// data, so an automaton built with a [`SubstitutionCost`] stores only the fact that it had one;
+    /// supply the same model again here.
+    ///
+    /// # Errors
+    /// As [`from_bytes`](Self::from_bytes), except that a custom model is never missing.
+    pub fn from_bytes_with_substitution_cost(
+        bytes: &[u8],
+        cost: impl SubstitutionCost + 'static,
+    ) -> Result<Self, DeserializeError> {
+        let parsed = Parsed::parse(bytes)?;
+        Ok(parsed.into_automaton(Some(CustomCost(Arc::new(cost)))))
+    }
+
+    /// Load an automaton serialized by [`to_bytes`](Self::to_bytes) **without copying its trie**:
+    /// the engine keeps `bytes` (a memory-mapped file, a `Vec<u8>`, a `&'static [u8]` from
+    /// `include_bytes!`, …) and searches the flat node, edge, and mapping records in place, instead
+    /// of materialising a `Vec` of nodes with a hash map per node.
+    ///
+    /// Worker processes that map the same file therefore share one copy of the trie through the
+    /// page cache, and loading costs one sequential pass over the buffer (checksum and bounds
+    /// validation) rather than an allocation per node. Only the small per-pattern data (pattern
+    /// text, weights, limits) and the similarity table are copied out. Searches return exactly the
+    /// same results as on the engine that was serialized; a shared trie is somewhat slower to walk
+    /// than an owned one, since multi-byte grapheme transitions are found by comparison rather
+    /// than hashing.
+    ///
+    /// ```
+    /// use fuzzy_aho_corasick::{FuzzyAhoCorasick, FuzzyAhoCorasickBuilder, FuzzyLimits, SearchOptions};
+    /// let bytes = FuzzyAhoCorasickBuilder::new()
+    ///     .fuzzy(FuzzyLimits::new().edits(1))
+    ///     .build(["hello", "world"])
+    ///     .to_bytes();
+    ///
+    /// // In production `bytes` would typically be a `memmap2::Mmap` of the saved file.
+    /// let engine = FuzzyAhoCorasick::from_shared_bytes(bytes).unwrap();
+    /// let hits = engine.search("helo", &SearchOptions::new().threshold(0.8)).unwrap();
+    /// assert_eq!(hits[0].pattern.as_str(), "hello");
+    /// ```
+    ///
+    /// # Errors
+    /// As [`from_bytes`](Self::from_bytes).
+    pub fn from_shared_bytes(
+        bytes: impl AsRef<[u8]> + Send + Sync + 'static,
+    ) -> Result<Self, DeserializeError> {
+        let backing: Arc<dyn AsRef<[u8]> + Send + Sync> = Arc::new(bytes);
+        let parsed = Parsed::parse((*backing).as_ref())?;
+        let cost = parsed.substitution_cost(None)?;
+        Ok(parsed.into_shared(cost, backing.clone()))
+    }
+
+    /// Like [`from_shared_bytes`](Self::from_shared_bytes), scoring substitutions with `cost` — see
+    /// [`from_bytes_with_substitution_cost`](Self::from_bytes_with_substitution_cost).
+    ///
+    /// # Errors
+    /// As [`from_bytes_with_substitution_cost`](Self::from_bytes_with_substitution_cost).
+    pub fn from_shared_bytes_with_substitution_cost(
+        bytes: impl AsRef<[u8]> + Send + Sync + 'static,
+        cost: impl SubstitutionCost + 'static,
+    ) -> Result<Self, DeserializeError> {
+        let backing: Arc<dyn AsRef<[u8]> + Send + Sync> = Arc::new(bytes);
+        let parsed = Parsed::parse((*backing).as_ref())?;
+        Ok(parsed.into_shared(Some(CustomCost(Arc::new(cost))), backing.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::structs::Similarity;
+    use crate::{
+        DeserializeError, FuzzyAhoCorasick, FuzzyAhoCorasickBuilder, FuzzyLimits, FuzzyPenalties,
+        Pattern, PhoneticAlgorithm, SearchOptions,
+    };
+    use std::sync::Arc;
+
+    fn assert_same_results(a: &FuzzyAhoCorasick, b: &FuzzyAhoCorasick, haystacks: &[&str]) {
+        let opts = SearchOptions::new().threshold(0.5).sorted();
+        for hay in haystacks {
+            assert_eq!(
+                a.search(hay, &opts).unwrap().inner,
+                b.search(hay, &opts).unwrap().inner,
+                "results differ on {hay:?}"
+            );
+        }
+    }
+
+    /// An engine exercising every serialized setting: mappings, per-pattern limits, weights, unique
+    /// ids, metadata, beam settings, case folding, and multi-codepoint graphemes.
+    fn sample_engine() -> FuzzyAhoCorasick {
+        FuzzyAhoCorasickBuilder::new()
+            .fuzzy(FuzzyLimits::new().edits(2))
+            .case_insensitive(true)
+            .penalties(FuzzyPenalties::default().swap(0.3))
+            .auto_beam(10_000, 64)
+            .min_symbol_similarity(0.1)
+            .mapping("æ", "ae")
+            .mapping_scored("ks", "x", 0.8)
+            .build([
+                Pattern::from("encyclopaedia").weight(1.5),
+                Pattern::from("alexandr")
+                    .custom_unique_id(7)
+                    .metadata("source", "names"),
+                Pattern::from("Москва").fuzzy(FuzzyLimits::new().substitutions(1)),
+                Pattern::from("e\u{0301}cole"),
+                Pattern::from("hello"),
+            ])
+    }
+
+    const SAMPLE_HAYSTACKS: &[&str] = &[
+        "an encyclopædia entry",
+        "aleksandr and alexandr",
+        "МОСКВА москба",
+        "école e\u{0301}cole",
+        "hlelo helo hello",
+    ];
+
+    #[test]
+    fn round_trip_preserves_search_results() {
+        let engine = sample_engine();
+        let bytes = engine.to_bytes();
+        let loaded = FuzzyAhoCorasick::from_bytes(&bytes).unwrap();
+
+        assert_eq!(loaded.patterns(), engine.patterns());
+        assert_eq!(
+            loaded.to_bytes(),
+            bytes,
+            "re-serializing must be byte-identical"
+        );
+        assert_same_results(&engine, &loaded, SAMPLE_HAYSTACKS);
+    }
+
+    #[test]
+    fn shared_bytes_search_in_place() {
+        let engine = sample_engine();
+        let bytes = engine.to_bytes();
+        let shared = FuzzyAhoCorasick::from_shared_bytes(bytes.clone()).unwrap();
+
+        assert!(shared.nodes.is_empty() && shared.mappings.is_empty());
+        assert_eq!(shared.patterns(), engine.patterns());
+        assert_eq!(shared.to_bytes(), bytes);
+        assert_eq!(shared.max_match_graphemes(), engine.max_match_graphemes());
+        assert_same_results(&engine, &shared, SAMPLE_HAYSTACKS);
+
+        // Higher-level entry points all go through the same trie access.
+        let hay = "aleksandr wrote the encyclopædia";
+        let opts = SearchOptions::new().threshold(0.6);
+        assert_eq!(
+            shared.segment_text(hay, &opts).unwrap(),
+            engine.segment_text(hay, &opts).unwrap()
+        );
+
+        // Trailing bytes past the automaton (e.g. page padding of a mapped file) are ignored.
+        let mut padded = bytes;
+        padded.extend_from_slice(&[0; 64]);
+        let padded = FuzzyAhoCorasick::from_shared_bytes(padded).unwrap();
+        assert_same_results(&engine, &padded, SAMPLE_HAYSTACKS);
+    }
+
+    #[test]
+    fn shared_bytes_without_mappings_keeps_fast_paths() {
+        let engine = FuzzyAhoCorasickBuilder::new()
+            .fuzzy(FuzzyLimits::new().edits(1))
+            .build(["vestibulum", "consectetur", "lorem"]);
+        let bytes: &'static [u8] = Box::leak(engine.to_bytes().into_boxed_slice());
+        let shared = FuzzyAhoCorasick::from_shared_bytes(bytes).unwrap();
+
+        let hay = "lorme ipsum vestibulm, consectetur";
+        assert_same_results(&engine, &shared, &[hay]);
+        let opts = SearchOptions::new().threshold(0.8).sorted();
+        assert_eq!(
+            shared.with_prefilter().search(hay, &opts).unwrap().inner,
+            engine.search(hay, &opts).unwrap().inner
+        );
+    }
+
+    #[test]
+    fn custom_similarity_is_restored() {
+        let ocr = Similarity::from_map([(('@', 'a'), 0.9), (('a', '@'), 0.9)]);
+        let engine = FuzzyAhoCorasickBuilder::new()
+            .fuzzy(FuzzyLimits::new().edits(1))
+            .similarity(ocr.clone())
+            .build(["cat"]);
+        let bytes = engine.to_bytes();
+
+        let loaded = FuzzyAhoCorasick::from_bytes(&bytes).unwrap();
+        assert_eq!(**loaded.similarity(), ocr);
+        assert_same_results(&engine, &loaded, &["c@t", "cat"]);
+        let shared = FuzzyAhoCorasick::from_shared_bytes(bytes).unwrap();
+        assert_same_results(&engine, &shared, &["c@t", "cat"]);
+
+        // Default-table engines keep sharing the one default table after a round trip.
+        let default = FuzzyAhoCorasickBuilder::new().build(["cat"]);
+        let loaded = FuzzyAhoCorasick::from_bytes(&default.to_bytes()).unwrap();
+        assert!(Arc::ptr_eq(loaded.similarity(), default.similarity()));
+    }
+
+    #[test]
+    fn custom_substitution_cost_must_be_supplied() {
+        let table = Similarity::from_map([(('@', 'a'), 0.9), (('a', '@'), 0.9)]);
+        let engine = FuzzyAhoCorasickBuilder::new()
+            .fuzzy(FuzzyLimits::new().edits(1))
+            .substitution_cost(table.clone())
+            .build(["cat", "e\u{301}cole"]);
+        let bytes = engine.to_bytes();
+        let haystacks = ["c@t", "cat", "ecole école"];
+
+        assert_eq!(
+            FuzzyAhoCorasick::from_bytes(&bytes).unwrap_err(),
+            DeserializeError::SubstitutionCostRequired
+        );
+        let loaded =
+            FuzzyAhoCorasick::from_bytes_with_substitution_cost(&bytes, table.clone()).unwrap();
+        assert_same_results(&engine, &loaded, &haystacks);
+        assert_eq!(loaded.to_bytes(), bytes);
+
+        let shared = FuzzyAhoCorasick::from_shared_bytes_with_substitution_cost(
+            bytes.clone(),
+            table.clone(),
+        )
+        .unwrap();
+        assert_same_results(&engine, &shared, &haystacks);
+        assert_eq!(shared.to_bytes(), bytes);
+
+        // Attaching a model to a plain automaton records it in the re-serialized form.
+        let plain = FuzzyAhoCorasickBuilder::new().build(["cat"]).to_bytes();
+        let attached = FuzzyAhoCorasick::from_shared_bytes_with_substitution_cost(plain, table)
+            .unwrap()
+            .to_bytes();
+        assert_eq!(
+            FuzzyAhoCorasick::from_bytes(&attached).unwrap_err(),
+            DeserializeError::SubstitutionCostRequired
+        );
+    }
+
+    #[test]
+    fn phonetic_matching_is_restored() {
+        let engine = FuzzyAhoCorasickBuilder::new()
+            .fuzzy(FuzzyLimits::new().edits(1))
+            .phonetic(PhoneticAlgorithm::Cologne)
+            .build(["Smith", "Meyer"]);
+        let bytes = engine.to_bytes();
+        let haystacks = ["Schmidt and Maier", "Smyth", "Mayr"];
+
+        let loaded = FuzzyAhoCorasick::from_bytes(&bytes).unwrap();
+        assert_same_results(&engine, &loaded, &haystacks);
+        assert_eq!(
+            loaded
+                .search("Schmidt", &SearchOptions::new())
+                .unwrap()
+                .len(),
+            1
+        );
+        let shared = FuzzyAhoCorasick::from_shared_bytes(bytes.clone()).unwrap();
+        assert_same_results(&engine, &shared, &haystacks);
+        assert_eq!(shared.to_bytes(), bytes);
+    }
+
+    #[test]
+    fn rejects_damaged_or_foreign_input() {
+        let bytes = FuzzyAhoCorasickBuilder::new().build(["hello"]).to_bytes();
+
+        assert_eq!(
+            FuzzyAhoCorasick::from_bytes(b"not an automaton").unwrap_err(),
+            DeserializeError::BadMagic
+        );
+
+        let mut wrong_version = bytes.clone();
+        wrong_version[4] = 99;
+        assert_eq!(
+            FuzzyAhoCorasick::from_bytes(&wrong_version).unwrap_err(),
+            DeserializeError::UnsupportedVersion {
+                found: 99,
+                expected: super::FORMAT_VERSION
+            }
+        );
+
+        let mut flipped = bytes.clone();
+        *flipped.last_mut().unwrap() ^= 0xFF;
+        assert_eq!(
+            FuzzyAhoCorasick::from_bytes(&flipped).unwrap_err(),
+            DeserializeError::ChecksumMismatch
+        );
+
+        assert_eq!(
+            FuzzyAhoCorasick::from_bytes(&bytes[..bytes.len() - 1]).unwrap_err(),
+            DeserializeError::Truncated
+        );
+    }
+}
@@ -0,0 +1,288 @@
+//! Snippet rendering: the matched terms of a [`FuzzyMatches`] shown in their surrounding text, with
+//! pluggable markup around each hit.
+//!
+//! Windows are measured in grapheme clusters, so a snippet never splits a cluster, and hits are
+//! taken left to right skipping overlaps, exactly as [`FuzzyMatches::segment_iter`] does.
+
+use crate::{FuzzyMatch, FuzzyMatches};
+use std::fmt;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// How [`FuzzyMatches::snippets`] and [`FuzzyMatches::highlight`] mark up a hit.
+#[derive(Clone, Copy)]
+pub enum Markup<'f> {
+    /// Wrap each hit in bold red ANSI escapes (`\x1b[1;31m` … `\x1b[0m`), for terminals.
+    Ansi,
+    /// Wrap each hit in `<mark>` … `</mark>`, HTML-escaping all haystack text (the ellipsis is
+    /// inserted verbatim).
+    Html,
+    /// Replace each hit with what the callback returns; the surrounding text is copied verbatim.
+    Custom(&'f dyn Fn(&FuzzyMatch<'_>) -> String),
+}
+
+impl fmt::Debug for Markup<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Markup::Ansi => f.write_str("Ansi"),
+            Markup::Html => f.write_str("Html"),
+            Markup::Custom(_) => f.debug_tuple("Custom").finish_non_exhaustive(),
+        }
+    }
+}
+
+impl Markup<'_> {
+    /// Append unmatched haystack text.
+    fn push_text(&self, out: &mut String, text: &str) {
+        match self {
+            Markup::Html => push_escaped(out, text),
+            Markup::Ansi | Markup::Custom(_) => out.push_str(text),
+        }
+    }
+
+    /// Append a hit.
+    fn push_hit(&self, out: &mut String, hit: &FuzzyMatch<'_>) {
+        match self {
+            Markup::Ansi => {
+                out.push_str("\x1b[1;31m");
+                out.push_str(hit.text);
+                out.push_str("\x1b[0m");
+            }
+            Markup::Html => {
+                out.push_str("<mark>");
+                push_escaped(out, hit.text);
+                out.push_str("</mark>");
+            }
+            Markup::Custom(callback) => out.push_str(&callback(hit)),
+        }
+    }
+}
+
+fn push_escaped(out: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+}
+
+/// Configuration for [`FuzzyMatches::snippets`]: how much `context` to show around each hit, the
+/// `ellipsis` marking cut-off text, when nearby hits share a snippet (`merge_gap`), and the
+/// `markup`. Construct with [`SnippetOptions::new`] and refine with the chainable setters.
+///
+/// ```
+/// use fuzzy_aho_corasick::{Markup, SnippetOptions};
+/// let opts = SnippetOptions::new().context(20).ellipsis("...").markup(Markup::Html);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct SnippetOptions<'f> {
+    /// Grapheme clusters of context shown on each side of a hit. Defaults to 30.
+    pub context: usize,
+    /// Inserted where a snippet starts after the haystack's start or ends before its end. Defaults
+    /// to `"…"`.
+    pub ellipsis: &'f str,
+    /// Hits whose context windows are at most this many grapheme clusters apart share one snippet
+    /// (the gap shown in full). Defaults to 0: only touching or overlapping windows merge.
+    pub merge_gap: usize,
+    /// How hits are marked up. Defaults to [`Markup::Ansi`].
+    pub markup: Markup<'f>,
+}
+
+impl Default for SnippetOptions<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'f> SnippetOptions<'f> {
+    /// All defaults: 30 graphemes of context, `"…"`, no merge gap, [`Markup::Ansi`].
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            context: 30,
+            ellipsis: "…",
+            merge_gap: 0,
+            markup: Markup::Ansi,
+        }
+    }
+
+    /// Set the context width, in grapheme clusters on each side of a hit.
+    #[must_use]
+    pub const fn context(mut self, context: usize) -> Self {
+        self.context = context;
+        self
+    }
+
+    /// Set the marker for cut-off text (`""` for none).
+    #[must_use]
+    pub const fn ellipsis(mut self, ellipsis: &'f str) -> Self {
+        self.ellipsis = ellipsis;
+        self
+    }
+
+    /// Set how far apart, in grapheme clusters, two context windows may be and still merge.
+    #[must_use]
+    pub const fn merge_gap(mut self, merge_gap: usize) -> Self {
+        self.merge_gap = merge_gap;
+        self
+    }
+
+    /// Set the markup.
+    #[must_use]
+    pub const fn markup(mut self, markup: Markup<'f>) -> Self {
+        self.markup = markup;
+        self
+    }
+}
+
+/// One rendered snippet from [`FuzzyMatches::snippets`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snippet {
+    /// Inclusive start byte index of the haystack text shown.
+    pub start: usize,
+    /// Exclusive end byte index of the haystack text shown.
+    pub end: usize,
+    /// The rendered snippet: the context and marked-up hits, with ellipses where text was cut off.
+    pub text: String,
+    /// Number of hits in the snippet.
+    pub hits: usize,
+}
+
+impl<'a> FuzzyMatches<'a> {
+    /// The hits in haystack order, skipping any that overlap an earlier one.
+    fn hits(&self) -> Vec<&FuzzyMatch<'a>> {
+        let mut sorted: Vec<_> = self.inner.iter().collect();
+        sorted.sort_by_key(|m| m.start);
+        let mut last = 0;
+        sorted.retain(|m| {
+            let keep = m.start >= last;
+            if keep {
+                last = m.end;
+            }
+            keep
+        });
+        sorted
+    }
+
+    /// Render `haystack[start..end]` with `hits` (all inside it) marked up.
+    fn render(
+        &self,
+        out: &mut String,
+        markup: Markup<'_>,
+        start: usize,
+        end: usize,
+        hits: &[&FuzzyMatch<'a>],
+    ) {
+        let mut last = start;
+        for hit in hits {
+            markup.push_text(out, &self.haystack[last..hit.start]);
+            markup.push_hit(out, hit);
+            last = hit.end;
+        }
+        markup.push_text(out, &self.haystack[last..end]);
+    }
+
+    /// Render the matches in context: each hit with `opts.context` grapheme clusters either side,
+    /// hits whose windows touch (or lie within `opts.merge_gap` of each other) sharing one snippet,
+    /// and `opts.ellipsis` wherever a snippet cuts the haystack off. Overlapping matches are skipped
+    /// as in [`segment_iter`](Self::segment_iter), so resolve overlaps first for a meaningful choice.
+    ///
+    /// # Example
+    /// ```
+    /// use fuzzy_aho_corasick::{FuzzyAhoCorasickBuilder, FuzzyLimits, Markup, SearchOptions, SnippetOptions};
+    ///
+    /// let engine = FuzzyAhoCorasickBuilder::new()
+    ///     .fuzzy(FuzzyLimits::new().edits(1))
+    ///     .build(["fox", "dog"]);
+    /// let haystack = "the quick brown fax jumps over the lazy dgo by the river";
+    /// let matches = engine
+    ///     .search(haystack, &SearchOptions::new().threshold(0.6).non_overlapping())
+    ///     .unwrap();
+    /// let opts = SnippetOptions::new().context(6).markup(Markup::Html);
+    /// let snippets: Vec<String> = matches.snippets(&opts).into_iter().map(|s| s.text).collect();
+    /// assert_eq!(snippets, ["…brown <mark>fax</mark> jumps…", "… lazy <mark>dgo</mark> by th…"]);
+    /// ```
+    #[must_use]
+    pub fn snippets(&self, opts: &SnippetOptions<'_>) -> Vec<Snippet> {
+        let hits = self.hits();
+        if hits.is_empty() {
+            return Vec::new();
+        }
+        // Byte offset of every grapheme boundary, the haystack's end included.
+        let boundaries: Vec<usize> = self
+            .haystack
+            .grapheme_indices(true)
+            .map(|(offset, _)| offset)
+            .chain([self.haystack.len()])
+            .collect();
+        let graphemes = boundaries.len() - 1;
+        // Each hit's context window, as grapheme boundary indices.
+        let window = |hit: &FuzzyMatch<'_>| {
+            let first = boundaries.partition_point(|&b| b <= hit.start) - 1;
+            let last = boundaries.partition_point(|&b| b < hit.end);
+            (
+                first.saturating_sub(opts.context),
+                (last + opts.context).min(graphemes),
+            )
+        };
+
+        let mut snippets = Vec::new();
+        let mut group = 0;
+        while group < hits.len() {
+            let (from, mut to) = window(hits[group]);
+            let mut next = group + 1;
+            while let Some(&hit) = hits.get(next) {
+                let (hit_from, hit_to) = window(hit);
+                if hit_from > to + opts.merge_gap {
+                    break;
+                }
+                to = hit_to;
+                next += 1;
+            }
+            let (start, end) = (boundaries[from], boundaries[to]);
+            let mut text = String::new();
+            if from > 0 {
+                text.push_str(opts.ellipsis);
+            }
+            self.render(&mut text, opts.markup, start, end, &hits[group..next]);
+            if to < graphemes {
+                text.push_str(opts.ellipsis);
+            }
+            snippets.push(Snippet {
+                start,
+                end,
+                text,
+                hits: next - group,
+            });
+            group = next;
+        }
+        snippets
+    }
+
+    /// The whole haystack with every hit marked up (overlapping matches skipped as in
+    /// [`segment_iter`](Self::segment_iter)).
+    ///
+    /// # Example
+    /// ```
+    /// use fuzzy_aho_corasick::{FuzzyAhoCorasickBuilder, FuzzyLimits, Markup, SearchOptions};
+    ///
+    /// let engine = FuzzyAhoCorasickBuilder::new()
+    ///     .fuzzy(FuzzyLimits::new().edits(1))
+    ///     .build(["world"]);
+    /// let matches = engine.search("hello, <wrld>", &SearchOptions::new().threshold(0.75)).unwrap();
+    /// assert_eq!(matches.highlight(Markup::Html), "hello, &lt;<mark>wrld</mark>&gt;");
+    ///
+    /// let upper = |m: &fuzzy_aho_corasick::FuzzyMatch<'_>| m.pattern.as_str().to_uppercase();
+    /// assert_eq!(matches.highlight(Markup::Custom(&upper)), "hello, <WORLD>");
+    /// ```
+    #[must_use]
+    pub fn highlight(&self, markup: Markup<'_>) -> String {
+        let mut out = String::with_capacity(self.haystack.len());
+        self.render(&mut out, markup, 0, self.haystack.len(), &self.hits());
+        out
+    }
+}
@@ -23,7 +23,7 @@
 //! * [`replace_stream_parallel`](crate::FuzzyAhoCorasick::replace_stream_parallel) — parallel search,
 //!   output reassembled in stream order on the calling thread.
 
-use crate::{FuzzyAhoCorasick, FuzzyLimits, FuzzyMatch, NumEdits, SearchOptions};
+use crate::{FuzzyAhoCorasick, FuzzyLimits, FuzzyMatch, NumEdits, OwnedFuzzyMatch, SearchOptions};
 use std::collections::{HashMap, VecDeque};
 use std::io::{self, Read, Write};
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -36,6 +36,7 @@ use unicode_segmentation::UnicodeSegmentation;
 /// `text`, so it is `Send` and outlives the transient window it was found in. Look the pattern up
 /// via `engine.patterns()[pattern_index]` if you need it.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StreamMatch {
     /// Absolute (stream-wide) inclusive start byte offset.
     pub start: u64,
@@ -62,7 +63,7 @@ pub struct StreamMatch {
 /// Default per-window byte target. Window size does not affect throughput (cost is dominated by the
 /// per-position search), so this is chosen only to keep the per-window grapheme buffers cache-
 /// resident; it grows automatically if the overlap needs more room.
-const DEFAULT_WINDOW: usize = 256 * 1024;
+pub(crate) const DEFAULT_WINDOW: usize = 256 * 1024;
 
 /// An owned window handed to the search: covers global bytes `[base, base + text.len())`, and owns
 /// the matches whose start byte is `< commit`.
@@ -130,21 +131,11 @@ impl<R: Read> WindowReader<R> {
                 }));
             }
 
-            // Commit boundary: keep the last `overlap_graphemes` graphemes so no match is split.
-            // Walk graphemes from the *end* — O(overlap), not O(window): re-segmenting the whole
-            // window here (on the single producer thread) would serialize the parallel search.
-            let commit = match text
-                .grapheme_indices(true)
-                .rev()
-                .nth(self.overlap_graphemes - 1)
-            {
-                Some((off, _)) if off > 0 => off,
-                _ => {
-                    // Fewer than overlap_graphemes+1 graphemes: too small to make progress (huge
-                    // clusters, or a very long longest-match). Grow the window and read more.
-                    self.window += self.window.max(64 * 1024);
-                    continue;
-                }
+            let Some(commit) = commit_boundary(text, self.overlap_graphemes) else {
+                // Too small to make progress (huge clusters, or a very long longest-match): grow
+                // the window and read more.
+                self.window += self.window.max(64 * 1024);
+                continue;
             };
             let out = StreamWindow {
                 base: self.base,
@@ -158,6 +149,59 @@ impl<R: Read> WindowReader<R> {
     }
 }
 
+/// Commit boundary of a (non-final) window: keep the last `overlap_graphemes` graphemes so no match
+/// is split. `None` when the window has too few graphemes to commit any.
+///
+/// Walks graphemes from the *end* — O(overlap), not O(window): re-segmenting the whole window here
+/// (on the single producer thread) would serialize the parallel search.
+fn commit_boundary(text: &str, overlap_graphemes: usize) -> Option<usize> {
+    match text.grapheme_indices(true).rev().nth(overlap_graphemes - 1) {
+        Some((off, _)) if off > 0 => Some(off),
+        _ => None,
+    }
+}
+
+/// A window of an in-memory haystack: bytes `[base, base + text.len())`, owning the matches whose
+/// start byte (relative to `base`) is `< commit`. The borrowed counterpart of a `StreamWindow`.
+pub(crate) struct SliceWindow<'a> {
+    pub(crate) base: usize,
+    pub(crate) text: &'a str,
+    pub(crate) commit: usize,
+}
+
+/// Cut `haystack` into overlapping windows of about `window` bytes, committed like the stream's
+/// (see [`commit_boundary`]), without copying the text.
+pub(crate) fn slice_windows(
+    haystack: &str,
+    mut window: usize,
+    overlap_graphemes: usize,
+) -> Vec<SliceWindow<'_>> {
+    let mut windows = Vec::new();
+    let mut base = 0;
+    loop {
+        let mut end = (base + window).min(haystack.len());
+        while !haystack.is_char_boundary(end) {
+            end -= 1;
+        }
+        let text = &haystack[base..end];
+        if end == haystack.len() {
+            windows.push(SliceWindow {
+                base,
+                text,
+                commit: text.len(),
+            });
+            return windows;
+        }
+        match commit_boundary(text, overlap_graphemes) {
+            Some(commit) => {
+                windows.push(SliceWindow { base, text, commit });
+                base += commit;
+            }
+            None => window += window.max(64 * 1024),
+        }
+    }
+}
+
 /// Iterator returned by [`FuzzyAhoCorasick::stream_matches`].
 ///
 /// Yields `io::Result<StreamMatch>`: an `Err` is produced once if the underlying reader fails, after
@@ -219,14 +263,7 @@ impl FuzzyAhoCorasick {
             .unwrap_or(0);
         // Longest haystack side of any mapping (a mapping may consume more haystack graphemes than
         // it does pattern graphemes); at least 1 so a plain insertion counts as one grapheme.
-        let max_mapping_haystack = self
-            .mappings
-            .values()
-            .flat_map(|m| m.iter())
-            .map(|mt| mt.haystack.len())
-            .max()
-            .unwrap_or(1)
-            .max(1);
+        let max_mapping_haystack = self.max_mapping_haystack().max(1);
         let edits_of = |l: &FuzzyLimits| -> usize {
             l.edits.map_or_else(
                 || {
@@ -253,7 +290,7 @@ impl FuzzyAhoCorasick {
     }
 
     /// Grapheme overlap the windows carry (`max_match_graphemes` plus a one-grapheme margin).
-    fn stream_overlap(&self) -> usize {
+    pub(crate) fn stream_overlap(&self) -> usize {
         self.max_match_graphemes() + 1
     }
 
@@ -565,8 +602,8 @@ impl FuzzyAhoCorasick {
                         };
                         let matches = self
                             .window_replace_matches(&w.text, w.commit, threshold)
-                            .iter()
-                            .map(OwnedMatch::from)
+                            .into_iter()
+                            .map(FuzzyMatch::into_owned)
                             .collect();
                         let res = ReplaceResult {
                             seq,
@@ -611,7 +648,7 @@ impl FuzzyAhoCorasick {
                     let matches: Vec<FuzzyMatch> = r
                         .matches
                         .iter()
-                        .map(|om| om.to_match(self, &r.text))
+                        .map(|om| om.to_match(self, &r.text).expect("match of this window"))
                         .collect();
                     if let Err(e) = cursor.emit_window(
                         &mut writer,
@@ -711,54 +748,5 @@ struct ReplaceResult {
     base: u64,
     text: String,
     commit: usize,
-    matches: Vec<OwnedMatch>,
-}
-
-/// The scalar fields of a [`FuzzyMatch`], owned so it can cross a thread boundary. The borrowed
-/// `pattern` and `text` are re-attached on the collector via [`OwnedMatch::to_match`].
-struct OwnedMatch {
-    start: usize,
-    end: usize,
-    pattern_index: usize,
-    similarity: f32,
-    insertions: NumEdits,
-    deletions: NumEdits,
-    substitutions: NumEdits,
-    swaps: NumEdits,
-    edits: NumEdits,
-}
-
-impl From<&FuzzyMatch<'_>> for OwnedMatch {
-    fn from(m: &FuzzyMatch<'_>) -> Self {
-        Self {
-            start: m.start,
-            end: m.end,
-            pattern_index: m.pattern_index,
-            similarity: m.similarity,
-            insertions: m.insertions,
-            deletions: m.deletions,
-            substitutions: m.substitutions,
-            swaps: m.swaps,
-            edits: m.edits,
-        }
-    }
-}
-
-impl OwnedMatch {
-    /// Rebuild a borrowed [`FuzzyMatch`] over the window `text` and `engine`'s pattern table.
-    fn to_match<'a>(&self, engine: &'a FuzzyAhoCorasick, text: &'a str) -> FuzzyMatch<'a> {
-        FuzzyMatch {
-            insertions: self.insertions,
-            deletions: self.deletions,
-            substitutions: self.substitutions,
-            swaps: self.swaps,
-            edits: self.edits,
-            pattern_index: self.pattern_index,
-            pattern: &engine.patterns[self.pattern_index],
-            start: self.start,
-            end: self.end,
-            similarity: self.similarity,
-            text: &text[self.start..self.end],
-        }
-    }
+    matches: Vec<OwnedFuzzyMatch>,
 }
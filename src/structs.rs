@@ -1,7 +1,11 @@
-use crate::PatternIndex;
-use std::collections::HashMap;
+use crate::cost::CustomCost;
+use crate::phonetic::PhoneticIndex;
+use crate::trie::SharedTrie;
+use crate::{EditOp, PatternIndex};
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use std::hash::{BuildHasherDefault, Hasher};
+use std::sync::Arc;
 use unicode_segmentation::UnicodeSegmentation;
 
 /// Combined similarity data: hashmap for non-ASCII and precomputed ASCII table for O(1) lookup.
@@ -19,6 +23,14 @@ impl fmt::Debug for Similarity {
     }
 }
 
+impl PartialEq for Similarity {
+    /// Two tables are equal when they hold the same pairs; the ASCII table is derived from the map,
+    /// so comparing the maps suffices.
+    fn eq(&self, other: &Self) -> bool {
+        self.map == other.map
+    }
+}
+
 impl Similarity {
     /// Build similarity data from `(char, char) -> score` pairs (e.g. a `HashMap` or an array of
     /// tuples). Each pair maps an ordered character substitution to its similarity in `0.0..=1.0`;
@@ -75,6 +87,12 @@ impl Similarity {
         max
     }
 
+    /// The `(char, char) -> score` pairs the table was built from, in no particular order. Feeding
+    /// them back into [`from_map`](Self::from_map) reproduces an equal table.
+    pub(crate) fn pairs(&self) -> impl Iterator<Item = ((char, char), f32)> + '_ {
+        self.map.iter().map(|(&pair, &sim)| (pair, sim))
+    }
+
     /// Get similarity between two characters.
     /// Uses fast ASCII lookup when possible, falls back to hashmap for non-ASCII.
     #[inline]
@@ -174,6 +192,9 @@ pub(crate) struct State {
     /// byte 2 = substitutions, byte 3 = swaps. Storing this directly avoids
     /// repacking at every dedup-key construction on the hot path.
     pub(crate) packed_counts: u32,
+    /// Index of the state's step in the trace arena of an alignment search (see `alignment`);
+    /// `0` and unused otherwise.
+    pub(crate) trace: u32,
     #[cfg(debug_assertions)]
     pub(crate) notes: Vec<String>,
 }
@@ -197,7 +218,9 @@ pub(crate) struct Edge {
 
 impl Edge {
     const SINGLE_BYTE: u32 = 1 << 31;
-    const NEXT_MASK: u32 = !Self::SINGLE_BYTE;
+    pub(crate) const NEXT_MASK: u32 = !Self::SINGLE_BYTE;
+    /// Number of addressable nodes: edge targets are 31-bit.
+    pub(crate) const MAX_NODES: usize = Self::NEXT_MASK as usize + 1;
 
     #[inline]
     pub(crate) fn new(first_char: char, next: u32, single_byte: bool) -> Self {
@@ -215,6 +238,21 @@ impl Edge {
         }
     }
 
+    /// Rebuild an edge from its [`packed`](Self::packed) target-and-flag word.
+    #[inline]
+    pub(crate) fn from_packed(first_char: char, packed_next: u32) -> Self {
+        Self {
+            first_char,
+            packed_next,
+        }
+    }
+
+    /// The target node index with the single-byte flag in bit 31.
+    #[inline]
+    pub(crate) fn packed(self) -> u32 {
+        self.packed_next
+    }
+
     /// Target node index.
     #[inline]
     pub(crate) fn next(self) -> u32 {
@@ -250,6 +288,10 @@ pub(crate) struct Node {
     /// Same edges as `transitions`, in a flat layout for hot-path iteration. Derived from
     /// `transitions` in a final build pass; must be kept consistent with it.
     pub(crate) edges: Vec<Edge>,
+    /// The full grapheme of each entry of `edges`, in the same order. Only filled for engines with a
+    /// custom [`SubstitutionCost`](crate::SubstitutionCost) model, which scores whole graphemes;
+    /// empty otherwise.
+    pub(crate) edge_graphemes: Vec<Box<str>>,
     /// All patterns that end in this state.
     pub(crate) output: Vec<u32>,
     /// Two precomputed coefficients of this node's pruning ceiling. A state at this node can only
@@ -290,11 +332,18 @@ pub(crate) struct Node {
 ///   its value, and — unless a total `edits` budget is set — every *unset* type defaults to `0`
 ///   (disallowed). So a [`FuzzyLimits::new`] with nothing set means exact matching.
 #[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct FuzzyLimits {
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub(crate) insertions: Option<NumEdits>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub(crate) deletions: Option<NumEdits>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub(crate) substitutions: Option<NumEdits>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub(crate) swaps: Option<NumEdits>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub(crate) edits: Option<NumEdits>,
 }
 
@@ -367,6 +416,8 @@ impl FuzzyLimits {
 /// penalty makes that edit hurt the score more. Tune these to fit which mistakes your domain
 /// considers "cheap" (e.g. a dropped vowel) versus "expensive" (a changed first letter).
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct FuzzyPenalties {
     /// Penalty for an inserted grapheme.
     pub insertion: f32,
@@ -429,6 +480,7 @@ impl Node {
             pattern_index: None,
             transitions: FxHashMap::default(),
             edges: Vec::new(),
+            edge_graphemes: Vec::new(),
             fail: 0,
             output: Vec::new(),
             prune_len: 0.0,
@@ -440,83 +492,6 @@ impl Node {
             grapheme: grapheme.map(str::to_string),
         }
     }
-
-    /// Look up the target node for an exact transition on `grapheme`.
-    ///
-    /// Fast path: when the grapheme is a single byte (ASCII char), a linear scan of the flat
-    /// `edges` list suffices — a single-byte grapheme can only match a single-char edge, and
-    /// `first_char` then identifies it exactly. This avoids hashing the `transitions` map (the
-    /// hottest lookup on the search path) for the overwhelmingly common ASCII case. Multi-byte
-    /// graphemes fall back to the `HashMap`.
-    #[inline]
-    pub(crate) fn find_transition(&self, grapheme: &str) -> Option<u32> {
-        let bytes = grapheme.as_bytes();
-        if bytes.len() == 1 {
-            let ch = bytes[0] as char;
-            for edge in &self.edges {
-                if edge.first_char == ch && edge.is_single_byte() {
-                    return Some(edge.next());
-                }
-            }
-            return None;
-        }
-        self.transitions.get(grapheme).copied()
-    }
-
-    /// Whether any outgoing single-ASCII-byte edge starts with `ch`. Used by the push-time dead-end
-    /// filter in the deletion/insertion scans. A linear scan of the node's (few) edges: nodes are
-    /// overwhelmingly low-degree, and a per-node cached bitmap costs 16 bytes/node while a side-map
-    /// lookup is slower than the scan on this hot path (both measured).
-    #[inline]
-    pub(crate) fn has_matching_edge_char(&self, ch: char) -> bool {
-        self.edges
-            .iter()
-            .any(|edge| edge.first_char == ch && edge.is_single_byte())
-    }
-
-    /// Bitmap of this node's single-ASCII-byte edge chars: bit `i` set iff an edge has
-    /// `first_char == i` with `i < 128`. Recomputed on demand from `edges` — used only by the
-    /// once-per-search window-skip pre-scan over the root and its children, so it isn't worth
-    /// caching 16 bytes on every node.
-    #[inline]
-    pub(crate) fn single_char_edge_bits(&self) -> u128 {
-        let mut bits = 0u128;
-        for edge in &self.edges {
-            if edge.is_single_byte() {
-                let idx = edge.first_char as u32;
-                if idx < 128 {
-                    bits |= 1u128 << idx;
-                }
-            }
-        }
-        bits
-    }
-
-    /// Like `find_transition` but takes a `char` directly, skipping the `&str` creation,
-    /// `as_bytes()`, and byte-length check. Correct only for single-byte graphemes
-    /// (guaranteed by the caller via `GraphemeStorage::gs_find_transition`).
-    #[inline]
-    pub(crate) fn find_transition_char(&self, ch: char) -> Option<u32> {
-        for edge in &self.edges {
-            if edge.first_char == ch && edge.is_single_byte() {
-                return Some(edge.next());
-            }
-        }
-        None
-    }
-
-    /// Like `find_transition_char` but skips the single-byte check. Only correct when the caller
-    /// guarantees no multi-char mapping edges exist (i.e., `MAPPINGS == false`), in which case
-    /// every edge is a single ASCII byte.
-    #[inline]
-    pub(crate) fn find_transition_char_no_mappings(&self, ch: char) -> Option<u32> {
-        for edge in &self.edges {
-            if edge.first_char == ch {
-                return Some(edge.next());
-            }
-        }
-        None
-    }
 }
 
 /// A compiled, immutable fuzzy Aho–Corasick automaton.
@@ -532,7 +507,9 @@ pub struct FuzzyAhoCorasick {
     /// Patterns
     pub(crate) patterns: Vec<Pattern>,
     /// Similarity data (ASCII table + non-ASCII fallback map)
-    pub(crate) similarity: &'static Similarity,
+    pub(crate) similarity: Arc<Similarity>,
+    /// Custom substitution model; when set it scores substitutions instead of `similarity`.
+    pub(crate) substitution_cost: Option<CustomCost>,
     /// Limits of errors
     pub(crate) limits: Option<FuzzyLimits>,
     /// Weight
@@ -564,6 +541,12 @@ pub struct FuzzyAhoCorasick {
     /// score — the paper's "weakest link" bound, so one wildly-dissimilar character can't be hidden
     /// by an otherwise-good long match. Exact matches and explicit mappings are unaffected.
     pub(crate) min_symbol_similarity: f32,
+    /// Phonetic index of the patterns, when sound-alike matching is enabled.
+    pub(crate) phonetic: Option<PhoneticIndex>,
+    /// Set when the engine searches a serialized trie in place (see
+    /// [`from_shared_bytes`](crate::FuzzyAhoCorasick::from_shared_bytes)); `nodes` and `mappings` are
+    /// then empty and every trie access goes through the shared bytes instead.
+    pub(crate) shared: Option<SharedTrie>,
 }
 
 #[allow(clippy::missing_fields_in_debug)]
@@ -594,19 +577,32 @@ pub enum UniqueId {
 /// One search pattern plus its per-pattern settings. Build with the `Pattern::from`
 /// conversions and refine with the chainable setters; passed to
 /// [`FuzzyAhoCorasickBuilder::build`](crate::FuzzyAhoCorasickBuilder::build).
+///
+/// With the `serde` feature, a pattern serializes without its derived `grapheme_len`, and only
+/// `pattern` is required to deserialize one: the other fields take the `Pattern::from` defaults.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "crate::serde_support::PatternRepr"))]
 pub struct Pattern {
     /// Length of the pattern in grapheme clusters (not bytes).
+    #[cfg_attr(feature = "serde", serde(skip_serializing))]
     pub grapheme_len: usize,
     /// The pattern text.
     pub pattern: String,
     /// Optional caller-assigned identity for pattern-unique overlap resolution (see [`UniqueId`]).
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub custom_unique_id: Option<usize>,
     /// Score multiplier for this pattern (default `1.0`); a weight above `1.0` can push a match's
     /// similarity past `1.0` to prioritize it.
     pub weight: f32,
     /// Per-pattern fuzzy limits, overriding the builder's global limits for this pattern.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub limits: Option<FuzzyLimits>,
+    /// Free-form key/value annotations (a category, a source, a note, …). The engine never reads
+    /// them; they travel with the pattern so a match can be traced back to them via
+    /// [`FuzzyAhoCorasick::patterns`].
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "BTreeMap::is_empty"))]
+    pub metadata: BTreeMap<String, String>,
 }
 
 impl fmt::Display for Pattern {
@@ -655,6 +651,14 @@ impl Pattern {
         self.custom_unique_id = Some(id);
         self
     }
+
+    /// Attach a metadata entry (see [`metadata`](Self::metadata)), replacing any earlier value for
+    /// `key`.
+    #[must_use]
+    pub fn metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
 }
 
 impl From<&str> for Pattern {
@@ -665,6 +669,7 @@ impl From<&str> for Pattern {
             weight: 1.,
             limits: None,
             custom_unique_id: None,
+            metadata: BTreeMap::new(),
         }
     }
 }
@@ -675,6 +680,7 @@ impl From<String> for Pattern {
             grapheme_len: s.graphemes(true).count(),
             pattern: s,
             custom_unique_id: None,
+            metadata: BTreeMap::new(),
             weight: 1.,
             limits: None,
         }
@@ -689,6 +695,7 @@ impl From<&String> for Pattern {
             weight: 1.,
             limits: None,
             custom_unique_id: None,
+            metadata: BTreeMap::new(),
         }
     }
 }
@@ -701,6 +708,7 @@ impl From<(&str, f32)> for Pattern {
             weight: w,
             limits: None,
             custom_unique_id: None,
+            metadata: BTreeMap::new(),
         }
     }
 }
@@ -711,6 +719,7 @@ impl From<(String, f32)> for Pattern {
             grapheme_len: s.graphemes(true).count(),
             pattern: s,
             custom_unique_id: None,
+            metadata: BTreeMap::new(),
             weight: w,
             limits: None,
         }
@@ -725,6 +734,7 @@ impl From<(&String, f32)> for Pattern {
             weight: w,
             limits: None,
             custom_unique_id: None,
+            metadata: BTreeMap::new(),
         }
     }
 }
@@ -737,6 +747,7 @@ impl<'a> From<(&'a str, f32, NumEdits)> for Pattern {
             weight: w,
             limits: Some(FuzzyLimits::default().edits(max_edits).finalize()),
             custom_unique_id: None,
+            metadata: BTreeMap::new(),
         }
     }
 }
@@ -749,6 +760,7 @@ impl From<(String, f32, NumEdits)> for Pattern {
             weight: w,
             limits: Some(FuzzyLimits::default().edits(max_edits).finalize()),
             custom_unique_id: None,
+            metadata: BTreeMap::new(),
         }
     }
 }
@@ -778,6 +790,73 @@ pub struct FuzzyMatch<'a> {
     pub similarity: f32,
     /// Slice of the original text that produced the match.
     pub text: &'a str,
+    /// How the pattern lines up with the haystack, step by step, when the search was run with
+    /// [`SearchOptions::alignment`](crate::SearchOptions::alignment); `None` otherwise, and for
+    /// phonetic matches. Trailing insertions (counted in `insertions`) lie past `end` unless the
+    /// search was anchored at the end.
+    pub alignment: Option<Vec<EditOp>>,
+}
+
+/// An owned copy of a [`FuzzyMatch`]: the same fields, with the matched `text` copied out of the
+/// haystack and the pattern referred to by `pattern_index` alone (look it up via
+/// [`FuzzyAhoCorasick::patterns`]). It borrows nothing, so it can outlive the haystack, cross
+/// threads, and — with the `serde` feature — be serialized.
+///
+/// Made by [`FuzzyMatch::into_owned`] and [`FuzzyMatches::into_owned`]; turned back into a
+/// [`FuzzyMatch`] by [`to_match`](Self::to_match) and [`FuzzyMatches::from_owned`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OwnedFuzzyMatch {
+    /// Number of insertions.
+    pub insertions: NumEdits,
+    /// Number of deletions.
+    pub deletions: NumEdits,
+    /// Number of substitutions.
+    pub substitutions: NumEdits,
+    /// Number of swaps (transpositions)
+    pub swaps: NumEdits,
+    /// Total number of edits
+    pub edits: NumEdits,
+    /// Pattern indexed (0-based)
+    pub pattern_index: usize,
+    /// Inclusive start byte index.
+    pub start: usize,
+    /// Exclusive end byte index.
+    pub end: usize,
+    /// Final similarity score ∈ `[0,1]`.
+    pub similarity: f32,
+    /// The matched text (owned).
+    pub text: String,
+    /// The match's [`alignment`](FuzzyMatch::alignment), if it was traced.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub alignment: Option<Vec<EditOp>>,
+}
+
+impl From<FuzzyMatch<'_>> for OwnedFuzzyMatch {
+    fn from(m: FuzzyMatch<'_>) -> Self {
+        Self {
+            insertions: m.insertions,
+            deletions: m.deletions,
+            substitutions: m.substitutions,
+            swaps: m.swaps,
+            edits: m.edits,
+            pattern_index: m.pattern_index,
+            start: m.start,
+            end: m.end,
+            similarity: m.similarity,
+            text: m.text.to_owned(),
+            alignment: m.alignment,
+        }
+    }
+}
+
+impl From<&FuzzyMatch<'_>> for OwnedFuzzyMatch {
+    fn from(m: &FuzzyMatch<'_>) -> Self {
+        m.clone().into()
+    }
 }
 
 /// Result of [`FuzzyAhoCorasick::segment_iter`]: either a successful match or
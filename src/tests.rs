@@ -2,7 +2,10 @@
  *  Tests
  * ---------------------------------------------------------------------- */
 use crate::{
-    FuzzyAhoCorasick, FuzzyAhoCorasickBuilder, FuzzyLimits, FuzzyPenalties, Pattern, SearchOptions,
+    ADJACENT_KEY_SIMILARITY, Anchored, Boundary, EditOp, FuzzyAhoCorasick, FuzzyAhoCorasickBuilder,
+    FuzzyLimits, FuzzyMatch, FuzzyMatches, FuzzyPenalties, KeyboardLayout, Markup, MatchKind,
+    Order, Overlap, Pattern, PatternListErrorKind, SAME_FINGER_SIMILARITY, SearchOptions,
+    Similarity, SnippetOptions, SubstitutionCost,
 };
 
 fn make_engine() -> FuzzyAhoCorasick {
@@ -1270,6 +1273,23 @@ fn test_fuzzy_replacer_replace_stream() {
         .replace_stream("hell0 w0rld!".as_bytes(), &mut out, 0.8)
         .unwrap();
     assert_eq!(String::from_utf8(out).unwrap(), "hi earth!");
+
+    let mut applied = Vec::new();
+    let mut out = Vec::new();
+    replacer
+        .replace_stream_with("w0rld hell0 world".as_bytes(), &mut out, 0.8, |m| {
+            applied.push((m.pattern_index, m.text.to_owned()));
+        })
+        .unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), "earth hi earth");
+    assert_eq!(
+        applied,
+        [
+            (1, "w0rld".into()),
+            (0, "hell0".into()),
+            (1, "world".into())
+        ]
+    );
 }
 
 #[test]
@@ -1342,11 +1362,1371 @@ fn test_min_symbol_similarity_floor() {
     );
 }
 
+/// A model that only rates graphemes sharing a base character as similar.
+struct BaseCharCost;
+
+impl SubstitutionCost for BaseCharCost {
+    fn char_similarity(&self, _a: char, _b: char) -> f32 {
+        0.0
+    }
+    fn grapheme_similarity(&self, pattern: &str, haystack: &str) -> f32 {
+        if pattern.chars().next() == haystack.chars().next() {
+            0.8
+        } else {
+            0.0
+        }
+    }
+}
+
+#[test]
+fn test_substitution_cost_scores_whole_graphemes() {
+    let opts = SearchOptions::new().threshold(0.5).sorted();
+    // The similarity table only sees first chars, so "e" and "e\u{301}" are indistinguishable.
+    let table = FuzzyAhoCorasickBuilder::new()
+        .fuzzy(FuzzyLimits::new().edits(1))
+        .build(["cafe\u{301}"]);
+    let hits = table.search("cafe", &opts).unwrap();
+    assert!((hits[0].similarity - 1.0).abs() < f32::EPSILON);
+
+    // A grapheme-level model scores the missing accent as a (cheap) substitution instead.
+    let model = FuzzyAhoCorasickBuilder::new()
+        .fuzzy(FuzzyLimits::new().edits(1))
+        .substitution_cost(BaseCharCost)
+        .build(["cafe\u{301}"]);
+    let hits = model.search("cafe", &opts).unwrap();
+    assert_eq!(hits[0].substitutions, 1);
+    assert!(hits[0].similarity < 1.0 && hits[0].similarity > 0.9);
+    let exact = model.search("cafe\u{301}", &opts).unwrap();
+    assert!((exact[0].similarity - 1.0).abs() < f32::EPSILON);
+}
+
+#[test]
+fn test_similarity_as_substitution_cost_matches_table() {
+    let table = Similarity::from_map([(('@', 'a'), 0.9), (('a', '@'), 0.9), (('e', 'i'), 0.5)]);
+    let builder = || FuzzyAhoCorasickBuilder::new().fuzzy(FuzzyLimits::new().edits(2));
+    let by_table = builder().similarity(table.clone()).build(["cat", "hello"]);
+    let by_model = builder().substitution_cost(table).build(["cat", "hello"]);
+    let opts = SearchOptions::new().threshold(0.5).sorted();
+    for hay in ["c@t hillo", "the cat said hallo", "c@@t h3llo"] {
+        assert_eq!(
+            by_model.search(hay, &opts).unwrap().inner,
+            by_table.search(hay, &opts).unwrap().inner
+        );
+    }
+}
+
+#[test]
+fn test_keyboard_layout_ranks_adjacent_typos_first() {
+    let engine = FuzzyAhoCorasickBuilder::new()
+        .fuzzy(FuzzyLimits::new().edits(1))
+        .keyboard_layout(KeyboardLayout::Qwerty)
+        .build(["hello"]);
+    let best = |hay: &str| {
+        engine
+            .search(hay, &SearchOptions::new().sorted())
+            .unwrap()
+            .iter()
+            .map(|m| m.similarity)
+            .fold(0.0f32, f32::max)
+    };
+    // 'w' neighbours 'e'; 'c' is struck by the same finger; 'p' is across the keyboard.
+    let adjacent = best("hwllo");
+    let same_finger = best("hcllo");
+    let distant = best("hpllo");
+    assert!(adjacent > same_finger, "{adjacent} <= {same_finger}");
+    assert!(same_finger > distant, "{same_finger} <= {distant}");
+}
+
+#[test]
+fn test_keyboard_layouts_differ() {
+    use KeyboardLayout::{Azerty, Dvorak, Qwerty, Qwertz};
+    let scores = |layout, a, b, expected: f32| {
+        (Similarity::keyboard(layout).get(a, b) - expected).abs() < f32::EPSILON
+    };
+
+    // Neighbours on one layout, unrelated on another.
+    assert!(scores(Azerty, 'a', 'z', ADJACENT_KEY_SIMILARITY));
+    assert!(scores(Qwerty, 'a', 'z', ADJACENT_KEY_SIMILARITY));
+    assert!(scores(Qwerty, 't', 'z', 0.0));
+    assert!(scores(Qwertz, 't', 'z', ADJACENT_KEY_SIMILARITY));
+    assert!(scores(Dvorak, 'a', 'o', ADJACENT_KEY_SIMILARITY));
+    assert!(scores(Qwerty, 'a', 'o', 0.0));
+    // Symmetric, both cases, and same-finger pairs in between.
+    assert!(scores(Qwerty, 'e', 'w', ADJACENT_KEY_SIMILARITY));
+    assert!(scores(Qwerty, 'W', 'E', ADJACENT_KEY_SIMILARITY));
+    assert!(scores(Qwerty, 'e', 'c', SAME_FINGER_SIMILARITY));
+    assert!(scores(Qwertz, 'ö', 'ä', ADJACENT_KEY_SIMILARITY));
+    assert!(scores(Qwertz, 'Ö', 'Ä', ADJACENT_KEY_SIMILARITY));
+}
+
+#[test]
+fn test_ocr_preset_matches_scanning_errors() {
+    let patterns = ["modern", "world", "bold", "find", "WORLD"];
+    let opts = SearchOptions::new()
+        .threshold(0.85)
+        .sorted()
+        .non_overlapping();
+    let plain = FuzzyAhoCorasickBuilder::new()
+        .fuzzy(FuzzyLimits::new().edits(1))
+        .build(patterns);
+    let ocr = FuzzyAhoCorasickBuilder::new()
+        .fuzzy(FuzzyLimits::new().edits(1))
+        .ocr_preset()
+        .build(patterns);
+
+    // Multi-glyph confusions (rn→m, vv→w, cl→d, ligatures) and single-glyph ones (0→O, 1→l).
+    for (scan, expected) in [
+        ("rnodern", "modern"),
+        ("vvorld", "world"),
+        ("bolcl", "bold"),
+        ("\u{fb01}nd", "find"),
+        ("W0RLD", "WORLD"),
+        ("wor1d", "world"),
+    ] {
+        let hits = ocr.search(scan, &opts).unwrap();
+        assert_eq!(hits.len(), 1, "{scan}");
+        assert_eq!(hits[0].pattern.as_str(), expected);
+        assert_eq!(hits[0].text, scan);
+        assert!(
+            plain
+                .search(scan, &opts)
+                .unwrap()
+                .iter()
+                .all(|m| m.text != scan || m.similarity < hits[0].similarity),
+            "{scan}"
+        );
+    }
+}
+
+#[test]
+fn test_phonetic_encodings() {
+    use crate::PhoneticAlgorithm::{Cologne, Metaphone, Soundex};
+
+    for (word, code) in [
+        ("Robert", "R163"),
+        ("Rupert", "R163"),
+        ("Rubin", "R150"),
+        ("Ashcraft", "A261"),
+        ("Tymczak", "T522"),
+        ("Pfister", "P236"),
+        ("Honeyman", "H555"),
+    ] {
+        assert_eq!(Soundex.encode(word).as_deref(), Some(code), "{word}");
+    }
+    for (word, code) in [("Müller-Lüdenscheidt", "65752682"), ("Wikipedia", "3412")] {
+        assert_eq!(Cologne.encode(word).as_deref(), Some(code), "{word}");
+    }
+    for (a, b) in [("Smith", "Smyth"), ("Knight", "Night"), ("Philip", "Filip")] {
+        assert_eq!(Metaphone.encode(a), Metaphone.encode(b), "{a} / {b}");
+    }
+    assert_ne!(Metaphone.encode("Smith"), Metaphone.encode("Schmidt"));
+    assert_eq!(Cologne.encode("-"), None);
+}
+
+#[test]
+fn test_phonetic_matches_sound_alike_surnames() {
+    let opts = SearchOptions::new()
+        .threshold(0.7)
+        .sorted()
+        .non_overlapping();
+    let haystack = "Smyth met Schmidt and Smith";
+    let plain = FuzzyAhoCorasickBuilder::new()
+        .fuzzy(FuzzyLimits::new().edits(1))
+        .build(["Smith"]);
+    assert_eq!(
+        plain.search(haystack, &opts).unwrap().matched_strings(),
+        ["Smyth", "Smith"]
+    );
+
+    let phonetic = FuzzyAhoCorasickBuilder::new()
+        .fuzzy(FuzzyLimits::new().edits(1))
+        .phonetic_scored(crate::PhoneticAlgorithm::Cologne, 0.7)
+        .build(["Smith"]);
+    let hits = phonetic.search(haystack, &opts).unwrap();
+    assert_eq!(hits.matched_strings(), ["Smyth", "Schmidt", "Smith"]);
+    // "Smyth" keeps the better of its one-edit score and the phonetic one; "Schmidt" is a pure
+    // sound-alike with no edits; the exact match keeps its edit-distance score.
+    assert!(hits[0].similarity > 0.7);
+    assert_eq!(hits[0].substitutions, 1);
+    assert!((hits[1].similarity - 0.7).abs() < f32::EPSILON);
+    assert_eq!(hits[1].edits, 0);
+    assert!((hits[2].similarity - 1.0).abs() < f32::EPSILON);
+    // The lazy iterator merges the phonetic matches in at their positions.
+    let lazy: Vec<_> = phonetic.find_iter(haystack, &opts).unwrap().collect();
+    assert_eq!(lazy, hits.inner);
+    let keep = SearchOptions::new().threshold(0.7);
+    let mut lazy = FuzzyMatches {
+        haystack,
+        inner: phonetic.find_iter(haystack, &keep).unwrap().collect(),
+    };
+    let mut all = phonetic.search(haystack, &keep).unwrap();
+    lazy.default_sort();
+    all.default_sort();
+    assert_eq!(lazy.inner, all.inner);
+
+    // Multi-word patterns match runs of words, code by code.
+    let names = FuzzyAhoCorasickBuilder::new()
+        .phonetic(crate::PhoneticAlgorithm::Soundex)
+        .build(["John Smith"]);
+    let hits = names.search("signed: Jon  Smyth.", &opts).unwrap();
+    assert_eq!(hits.matched_strings(), ["Jon  Smyth"]);
+    assert!(names.search("Jon", &opts).unwrap().is_empty());
+}
+
+#[test]
+fn test_phonetic_early_exit() {
+    use crate::phonetic::ENCODED_WORDS;
+
+    let engine = FuzzyAhoCorasickBuilder::new()
+        .phonetic(crate::PhoneticAlgorithm::Cologne)
+        .build(["Smith", "John Smith"]);
+    let opts = SearchOptions::new().threshold(0.7);
+    let haystack = format!("met Schmidt{}", " and others".repeat(1000));
+    let encoded = |search: &dyn Fn() -> bool| {
+        ENCODED_WORDS.set(0);
+        assert!(search());
+        ENCODED_WORDS.get()
+    };
+    // The early-exit entry points stop at the first phonetic hit, reading at most one
+    // multi-word run past it.
+    assert!(encoded(&|| engine.is_match(&haystack, &opts).unwrap()) <= 3);
+    assert!(encoded(&|| engine.find_first(&haystack, &opts).unwrap().is_some()) <= 3);
+    assert_eq!(
+        engine.find_first(&haystack, &opts).unwrap().unwrap().text,
+        "Schmidt"
+    );
+    // So does the lazy iterator, one step at a time.
+    ENCODED_WORDS.set(0);
+    let mut lazy = engine.find_iter(&haystack, &opts).unwrap();
+    assert_eq!(lazy.next().unwrap().text, "Schmidt");
+    assert!(ENCODED_WORDS.get() <= 3);
+    assert!(lazy.next().is_none());
+    // An edit-distance match further left ends the scan before the phonetic one.
+    let exact = format!("Smith, Schmidt{}", " and others".repeat(1000));
+    assert!(encoded(&|| engine.is_match(&exact, &opts).unwrap()) <= 2);
+    // A full search reads every word.
+    assert!(encoded(&|| !engine.search(&haystack, &opts).unwrap().is_empty()) > 2000);
+}
+
+#[test]
+fn test_boundary_modes() {
+    let engine = FuzzyAhoCorasickBuilder::new().build(["cat", "can"]);
+    let found = |haystack: &'static str, boundary: Boundary| {
+        let opts = SearchOptions::new().sorted().boundary(boundary);
+        engine.search(haystack, &opts).unwrap().matched_strings()
+    };
+    assert_eq!(found("concatenate", Boundary::None), ["cat"]);
+    assert!(found("concatenate", Boundary::Word).is_empty());
+    assert!(found("concatenate", Boundary::Separator).is_empty());
+    assert_eq!(
+        found("cat, (cat) cat!", Boundary::Word),
+        ["cat", "cat", "cat"]
+    );
+
+    // UAX #29 keeps "can't" one word; the separator mode splits it at the apostrophe.
+    assert!(found("can't", Boundary::Word).is_empty());
+    assert_eq!(found("can't", Boundary::Separator), ["can"]);
+    // Ideographs are words of their own under UAX #29, but not separators.
+    assert_eq!(found("cat猫", Boundary::Word), ["cat"]);
+    assert!(found("cat猫", Boundary::Separator).is_empty());
+}
+
+#[test]
+fn test_boundary_applies_to_fuzzy_matches() {
+    let engine = FuzzyAhoCorasickBuilder::new()
+        .fuzzy(FuzzyLimits::new().edits(1))
+        .build(["cat"]);
+    let opts = SearchOptions::new()
+        .threshold(0.6)
+        .sorted()
+        .non_overlapping()
+        .boundary(Boundary::Word);
+    // The exact "cat" inside "cats" ends mid-word; the one-substitution "sat" is a whole word.
+    let unbounded = opts.boundary(Boundary::None);
+    assert_eq!(
+        engine
+            .search("the cats sat", &unbounded)
+            .unwrap()
+            .matched_strings(),
+        ["cat", "sat"]
+    );
+    assert_eq!(
+        engine
+            .search("the cats sat", &opts)
+            .unwrap()
+            .matched_strings(),
+        ["sat"]
+    );
+    // An insertion inside the span is fine as long as both ends are on boundaries.
+    let hits = engine.search("a c-at", &opts).unwrap();
+    assert_eq!(hits.matched_strings(), ["c-at"]);
+    assert_eq!(hits[0].insertions, 1);
+    assert!(engine.search("scatter", &opts).unwrap().is_empty());
+
+    // The pre-filter searches windows of the haystack, but boundaries are the haystack's.
+    let pf = engine.with_prefilter();
+    assert!(pf.is_active());
+    let haystack = format!("{}bobcat cats cot{}", "x".repeat(200), " y".repeat(100));
+    let hits = engine.search(&haystack, &opts).unwrap();
+    assert_eq!(hits.matched_strings(), ["cot"]);
+    assert_eq!(pf.search(&haystack, &opts).unwrap().inner, hits.inner);
+}
+
+#[test]
+fn test_anchored_search() {
+    let engine = FuzzyAhoCorasickBuilder::new()
+        .fuzzy(FuzzyLimits::new().edits(1))
+        .build(["cat", "dog"]);
+    let found = |haystack: &'static str, anchored: Anchored| {
+        let opts = SearchOptions::new()
+            .threshold(0.6)
+            .sorted()
+            .non_overlapping()
+            .anchored(anchored);
+        engine.search(haystack, &opts).unwrap()
+    };
+    let hits = found("cat dog cat", Anchored::Start);
+    assert_eq!(hits.matched_strings(), ["cat"]);
+    assert_eq!(hits[0].start, 0);
+    let hits = found("cat dog cat", Anchored::End);
+    assert_eq!(hits.matched_strings(), ["cat"]);
+    assert_eq!(hits[0].start, 8);
+    assert!(found("cat dog cat", Anchored::Both).is_empty());
+    assert_eq!(found("cat dog", Anchored::Both).len(), 0);
+
+    // Whole-string lookup: edits at either anchored edge are part of the match.
+    for (haystack, insertions) in [("cat", 0), ("cot", 0), ("cats", 1), ("scat", 1)] {
+        let hits = found(haystack, Anchored::Both);
+        assert_eq!(hits.matched_strings(), [haystack]);
+        assert_eq!(hits[0].insertions, insertions);
+    }
+    assert!(found("scats", Anchored::Both).is_empty());
+    assert_eq!(found("dg", Anchored::Both).matched_strings(), ["dg"]);
+
+    // The pre-filter runs anchored searches unfiltered, with identical results.
+    let pf = engine.with_prefilter();
+    assert!(pf.is_active());
+    let haystack = format!("cot{}dgo", " y".repeat(200));
+    for anchored in [Anchored::Start, Anchored::End] {
+        let opts = SearchOptions::new().threshold(0.6).anchored(anchored);
+        let hits = engine.search(&haystack, &opts).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(pf.search(&haystack, &opts).unwrap().inner, hits.inner);
+    }
+}
+
+#[test]
+fn test_lookup_ranks_whole_string_candidates() {
+    let engine = FuzzyAhoCorasickBuilder::new()
+        .fuzzy(FuzzyLimits::new().edits(2))
+        .case_insensitive(true)
+        .build([
+            "color",
+            "colour",
+            "collar",
+            "cooler",
+            "Pixel 8",
+            "Pixel 8 Pro",
+        ]);
+    let ranked = |query: &'static str, k: usize| -> Vec<&str> {
+        engine
+            .lookup(query, k)
+            .unwrap()
+            .iter()
+            .map(|m| m.pattern.as_str())
+            .collect()
+    };
+    assert_eq!(ranked("COLOR", 4), ["color", "colour", "cooler", "collar"]);
+    assert_eq!(ranked("colour", 2), ["colour", "color"]);
+    assert_eq!(ranked("pixel 8 pr", 3), ["Pixel 8 Pro"]);
+    assert!(ranked("colr", 0).is_empty());
+
+    // Junk around the entry is part of the (whole-query) match, not skipped.
+    let hits = engine.lookup("xcolor", 1).unwrap();
+    assert_eq!(hits[0].pattern.as_str(), "color");
+    assert_eq!((hits[0].text, hits[0].insertions), ("xcolor", 1));
+    assert!(hits[0].similarity < 1.0);
+    assert!(engine.lookup("my color chart", 5).unwrap().is_empty());
+}
+
+#[test]
+fn test_is_match_and_find_first_agree_with_search() {
+    let engine = FuzzyAhoCorasickBuilder::new()
+        .fuzzy(FuzzyLimits::new().edits(1))
+        .case_insensitive(true)
+        .build(["spam", "scam", "spa", "offer"]);
+    let pf = engine.with_prefilter();
+    assert!(pf.is_active());
+    let filler = "lorem ipsum ".repeat(20);
+    let haystacks = [
+        "nothing to see here".to_string(),
+        "a sp4m and a scam".to_string(),
+        "special 0ffer".to_string(),
+        format!("{filler}limited offer, no spam"),
+        String::new(),
+    ];
+    let opts = SearchOptions::new().threshold(0.7);
+    for haystack in &haystacks {
+        let all = engine.search(haystack, &opts.sorted()).unwrap();
+        // Reference: the best-ranked of the matches starting leftmost.
+        let expected = all
+            .iter()
+            .map(|m| m.start)
+            .min()
+            .and_then(|start| all.iter().find(|m| m.start == start));
+        assert_eq!(engine.is_match(haystack, &opts).unwrap(), !all.is_empty());
+        assert_eq!(pf.is_match(haystack, &opts).unwrap(), !all.is_empty());
+        assert_eq!(
+            engine.find_first(haystack, &opts).unwrap().as_ref(),
+            expected
+        );
+        assert_eq!(pf.find_first(haystack, &opts).unwrap().as_ref(), expected);
+    }
+
+    // Several patterns start at the leftmost position; `order` picks among them.
+    let first = engine.find_first("spam", &opts).unwrap().unwrap();
+    assert_eq!(first.pattern.as_str(), "spam");
+    let first = engine
+        .find_first("a spa", &opts.boundary(Boundary::Word))
+        .unwrap()
+        .unwrap();
+    assert_eq!((first.text, first.pattern.as_str()), ("spa", "spa"));
+}
+
+#[test]
+fn test_find_iter_matches_positional_reference() {
+    let engine = FuzzyAhoCorasickBuilder::new()
+        .fuzzy(FuzzyLimits::new().edits(1))
+        .case_insensitive(true)
+        .build([
+            Pattern::from("spam"),
+            Pattern::from("scam").custom_unique_id(0),
+            Pattern::from("spa").custom_unique_id(0),
+            Pattern::from("offer"),
+        ]);
+    let haystacks = [
+        "a sp4m and a scam, a spa offer",
+        "spam spam spaam offr scam",
+        "nothing to see here",
+        "",
+    ];
+    let base = SearchOptions::new().threshold(0.6);
+    for haystack in haystacks {
+        let all = engine.search(haystack, &base.sorted()).unwrap();
+
+        // Keep: the same set as `search`, in leftmost order.
+        let kept: Vec<_> = engine.find_iter(haystack, &base).unwrap().collect();
+        assert_eq!(kept.len(), all.len());
+        assert!(kept.windows(2).all(|w| w[0].start <= w[1].start));
+        assert!(kept.iter().all(|m| all.inner.contains(m)));
+
+        // Non-overlapping: repeatedly the best-ranked match starting leftmost after the last one.
+        for order in [Order::Default, Order::Greedy] {
+            let all = engine.search(haystack, &base.order(order)).unwrap();
+            for overlap in [Overlap::NonOverlapping, Overlap::NonOverlappingUnique] {
+                let mut expected = Vec::new();
+                let mut used = Vec::new();
+                let mut pos = 0;
+                while let Some(start) = all
+                    .iter()
+                    .filter(|m| m.start >= pos)
+                    .filter(|m| {
+                        overlap == Overlap::NonOverlapping || !used.contains(&m.unique_id())
+                    })
+                    .map(|m| m.start)
+                    .min()
+                {
+                    let m = all
+                        .iter()
+                        .find(|m| {
+                            m.start == start
+                                && (overlap == Overlap::NonOverlapping
+                                    || !used.contains(&m.unique_id()))
+                        })
+                        .unwrap();
+                    used.push(m.unique_id());
+                    expected.push(m.clone());
+                    pos = m.end;
+                }
+                let opts = base.order(order).overlap(overlap);
+                let got: Vec<_> = engine.find_iter(haystack, &opts).unwrap().collect();
+                assert_eq!(got, expected, "{haystack:?} {order:?} {overlap:?}");
+            }
+        }
+    }
+
+    // Leftmost-best vs leftmost-longest at the same start: the exact "spa" outscores the one-edit
+    // "spam", but is the shorter pattern.
+    let first = |order| {
+        let opts = base.order(order).non_overlapping();
+        engine.find_iter("spaam", &opts).unwrap().next().unwrap()
+    };
+    assert_eq!(first(Order::Default).pattern.as_str(), "spa");
+    assert_eq!(first(Order::Greedy).pattern.as_str(), "spam");
+}
+
 /// Verify that search results are deterministic (bit-for-bit identical) across repeated calls.
 ///
 /// The algorithm uses `FxHashMap` (no random seed) and `sort_unstable_by` with total orderings,
 /// so the same input should always produce the same output. This test runs multiple searches
 /// on the same engine + haystack and asserts the match lists are identical.
+#[test]
+fn test_leftmost_match_kinds() {
+    let patterns = |opts: &SearchOptions, engine: &FuzzyAhoCorasick, haystack: &str| {
+        engine
+            .search(haystack, opts)
+            .unwrap()
+            .iter()
+            .map(|m| (m.start, m.pattern.as_str().to_string()))
+            .collect::<Vec<_>>()
+    };
+    let first = SearchOptions::new().match_kind(MatchKind::LeftmostFirst);
+    let longest = SearchOptions::new().match_kind(MatchKind::LeftmostLongest);
+
+    // Exact: the same picks as the `aho-corasick` crate's leftmost match kinds.
+    let engine = FuzzyAhoCorasickBuilder::new().build(["append", "appendage", "app"]);
+    let haystack = "append the app to the appendage";
+    assert_eq!(
+        patterns(&first, &engine, haystack),
+        [
+            (0, "append".into()),
+            (11, "app".into()),
+            (22, "append".into())
+        ]
+    );
+    assert_eq!(
+        patterns(&longest, &engine, haystack),
+        [
+            (0, "append".into()),
+            (11, "app".into()),
+            (22, "appendage".into())
+        ]
+    );
+    // Pattern priority follows insertion order.
+    let engine = FuzzyAhoCorasickBuilder::new().build(["Samwise", "Sam"]);
+    assert_eq!(
+        patterns(&first, &engine, "Samwise"),
+        [(0, "Samwise".to_string())]
+    );
+
+    // Fuzzy: the earliest start wins over equally good matches starting later ("pam" at 2).
+    let engine = FuzzyAhoCorasickBuilder::new()
+        .fuzzy(FuzzyLimits::new().edits(1))
+        .build(["spam", "spa", "pam"]);
+    let first = first.threshold(0.6);
+    let longest = longest.threshold(0.6);
+    assert_eq!(patterns(&first, &engine, "xspam"), [(1, "spam".into())]);
+    // Same span as the fuzzy "spam": the closer match wins.
+    assert_eq!(patterns(&longest, &engine, "xspa"), [(1, "spa".into())]);
+
+    // Every entry point agrees with `search`.
+    let engine = FuzzyAhoCorasickBuilder::new()
+        .fuzzy(FuzzyLimits::new().edits(1))
+        .case_insensitive(true)
+        .build(["spa", "spam", "scam", "offer"]);
+    let pf = engine.with_prefilter();
+    let filler = "lorem ipsum ".repeat(20);
+    let haystacks = [
+        "a sp4m and a scam, a spa offer".to_string(),
+        "spam spam spaam offr scam".to_string(),
+        format!("{filler}limited offer, no spam"),
+        String::new(),
+    ];
+    for kind in [MatchKind::LeftmostFirst, MatchKind::LeftmostLongest] {
+        let opts = SearchOptions::new().threshold(0.6).match_kind(kind);
+        for haystack in &haystacks {
+            let all = engine.search(haystack, &opts).unwrap();
+            assert!(all.inner.windows(2).all(|w| w[0].end <= w[1].start));
+            assert_eq!(pf.search(haystack, &opts).unwrap().inner, all.inner);
+            let iterated: Vec<_> = engine.find_iter(haystack, &opts).unwrap().collect();
+            assert_eq!(iterated, all.inner, "{haystack:?} {kind:?}");
+            assert_eq!(
+                engine.find_first(haystack, &opts).unwrap().as_ref(),
+                all.inner.first()
+            );
+            assert_eq!(
+                pf.find_first(haystack, &opts).unwrap().as_ref(),
+                all.inner.first()
+            );
+        }
+    }
+    let opts = SearchOptions::new().threshold(0.6);
+    let picked = |kind| {
+        engine
+            .find_first("spaam", &opts.match_kind(kind))
+            .unwrap()
+            .unwrap()
+            .pattern
+            .as_str()
+            .to_string()
+    };
+    assert_eq!(picked(MatchKind::LeftmostFirst), "spa");
+    assert_eq!(picked(MatchKind::LeftmostLongest), "spam");
+}
+
+#[test]
+fn test_optimal_overlap() {
+    // Greedy resolution keeps the best-ranked "newyo" and loses both words around it.
+    let engine = FuzzyAhoCorasickBuilder::new().build(["new", "york", "newyo"]);
+    let greedy = SearchOptions::new().sorted().non_overlapping();
+    let optimal = SearchOptions::new().non_overlapping_optimal();
+    assert_eq!(engine.segment_text("newyork", &greedy).unwrap(), "newyo rk");
+    assert_eq!(
+        engine.segment_text("newyork", &optimal).unwrap(),
+        "new york"
+    );
+    let upper = |m: &FuzzyMatch| Some(m.pattern.as_str().to_uppercase());
+    assert_eq!(
+        engine.replace("newyork", &optimal, upper).unwrap(),
+        "NEWYORK"
+    );
+    let replacer = FuzzyAhoCorasickBuilder::new().build_replacer([
+        ("new", "New "),
+        ("york", "York"),
+        ("newyo", "?"),
+    ]);
+    assert_eq!(replacer.replace("newyork", &greedy).unwrap(), "?rk");
+    assert_eq!(replacer.replace("newyork", &optimal).unwrap(), "New York");
+
+    // Pattern length counts graphemes: "b語c" is 3 of them (but 5 bytes), so the two ASCII words
+    // around it weigh more, while a byte count would keep it.
+    let engine = FuzzyAhoCorasickBuilder::new().build(["ab", "b語c", "cd"]);
+    let mut found = engine.search("ab語cd", &SearchOptions::new()).unwrap();
+    let mut by_bytes = engine.search("ab語cd", &SearchOptions::new()).unwrap();
+    found.non_overlapping_optimal();
+    by_bytes.non_overlapping_optimal_by(|m| m.similarity * m.pattern.len() as f32);
+    let patterns = |found: &FuzzyMatches| -> Vec<String> {
+        found.iter().map(|m| m.pattern.to_string()).collect()
+    };
+    assert_eq!(patterns(&found), ["ab", "cd"]);
+    assert_eq!(patterns(&by_bytes), ["b語c"]);
+
+    // The kept set's total score is the best over every non-overlapping subset.
+    let engine = FuzzyAhoCorasickBuilder::new()
+        .fuzzy(FuzzyLimits::new().edits(1))
+        .build(["spam", "scam", "spa", "am", "offer"]);
+    let objectives: [fn(&FuzzyMatch) -> f32; 2] = [
+        |m| m.similarity * m.pattern.grapheme_len as f32,
+        |m| m.similarity - 0.8,
+    ];
+    for haystack in ["spamoffer", "a sp4m scam", "spaam offr"] {
+        for objective in objectives {
+            let all = engine
+                .search(haystack, &SearchOptions::new().threshold(0.6))
+                .unwrap();
+            let mut candidates = all.inner.clone();
+            candidates.sort_by(|a, b| objective(b).total_cmp(&objective(a)));
+            candidates.truncate(14);
+            let total = |set: &[FuzzyMatch]| -> f32 {
+                set.iter().map(|m| objective(m)).filter(|&s| s > 0.0).sum()
+            };
+            let mut brute = 0.0_f32;
+            for mask in 0..1_u32 << candidates.len() {
+                let subset: Vec<_> = (0..candidates.len())
+                    .filter(|i| mask & (1 << i) != 0)
+                    .map(|i| candidates[i].clone())
+                    .collect();
+                let disjoint = subset.iter().enumerate().all(|(i, a)| {
+                    subset[i + 1..]
+                        .iter()
+                        .all(|b| a.end <= b.start || b.end <= a.start)
+                });
+                if disjoint {
+                    brute = brute.max(total(&subset));
+                }
+            }
+            let mut kept = all;
+            kept.inner = candidates;
+            kept.non_overlapping_optimal_by(objective);
+            assert!(kept.inner.windows(2).all(|w| w[0].end <= w[1].start));
+            assert!((total(&kept) - brute).abs() < 1e-4, "{haystack:?}");
+        }
+    }
+}
+
+#[test]
+fn test_alignment_reconstructs_the_match() {
+    let engines = [
+        FuzzyAhoCorasickBuilder::new()
+            .fuzzy(FuzzyLimits::new().edits(2))
+            .case_insensitive(true)
+            .build(["receive", "Saddam", "hussein"]),
+        FuzzyAhoCorasickBuilder::new()
+            .fuzzy(FuzzyLimits::new().edits(1))
+            .mapping("æ", "ae")
+            .build(["Cæsar", "encyclopædia"]),
+        FuzzyAhoCorasickBuilder::new()
+            .fuzzy(FuzzyLimits::new().edits(1).insertions(1).deletions(1))
+            .build(["café", "naïve"]),
+    ];
+    let haystacks = [
+        "I recieve from saddam husein",
+        "RECEIV the Sadam Hussien",
+        "Caesar's encyclopedia",
+        "a naive cafe, un café naïf",
+    ];
+    for engine in &engines {
+        for haystack in haystacks {
+            for anchored in [Anchored::No, Anchored::Both] {
+                let opts = SearchOptions::new()
+                    .threshold(0.5)
+                    .sorted()
+                    .anchored(anchored);
+                let plain = engine.search(haystack, &opts).unwrap();
+                let traced = engine.search(haystack, &opts.alignment(true)).unwrap();
+                assert_eq!(plain.len(), traced.len(), "{haystack:?}");
+                for (plain, m) in plain.iter().zip(traced.iter()) {
+                    let steps = m.alignment.as_ref().unwrap();
+                    assert_eq!(
+                        FuzzyMatch {
+                            alignment: None,
+                            ..m.clone()
+                        },
+                        *plain
+                    );
+
+                    // The steps tile the whole pattern and the matched text, in order.
+                    let pattern = m.pattern.as_str();
+                    assert_eq!(steps.first().map_or(0, |op| op.pattern().start), 0);
+                    assert_eq!(steps.last().map(|op| op.pattern().end), Some(pattern.len()));
+                    assert_eq!(steps.first().map(|op| op.text().start), Some(m.start));
+                    assert!(steps.windows(2).all(|w| {
+                        w[0].pattern().end == w[1].pattern().start
+                            && w[0].text().end == w[1].text().start
+                    }));
+                    let in_span = steps
+                        .iter()
+                        .rev()
+                        .find(|op| !matches!(op, EditOp::Ins { .. }) || op.text().start < m.end);
+                    assert_eq!(in_span.map(|op| op.text().end), Some(m.end), "{m:?}");
+
+                    // One step per counted edit.
+                    let count = |f: fn(&EditOp) -> bool| steps.iter().filter(|op| f(op)).count();
+                    assert_eq!(
+                        count(|op| matches!(op, EditOp::Ins { .. })),
+                        m.insertions as usize
+                    );
+                    assert_eq!(
+                        count(|op| matches!(op, EditOp::Del { .. })),
+                        m.deletions as usize
+                    );
+                    assert_eq!(
+                        count(|op| matches!(op, EditOp::Sub { .. })),
+                        m.substitutions as usize
+                    );
+                    assert_eq!(
+                        count(|op| matches!(op, EditOp::Swap { .. })),
+                        m.swaps as usize
+                    );
+                    for op in steps {
+                        if let EditOp::Match {
+                            pattern: p,
+                            text: t,
+                        } = op
+                        {
+                            assert_eq!(
+                                pattern[p.clone()].to_lowercase(),
+                                haystack[t.clone()].to_lowercase()
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // The other entry points report the same alignments.
+    let engine = &engines[0];
+    let opts = SearchOptions::new().threshold(0.5).alignment(true);
+    let haystack = haystacks[0];
+    let all = engine.search(haystack, &opts).unwrap();
+    let filtered = engine.with_prefilter();
+    let mut prefiltered = filtered.search(haystack, &opts).unwrap();
+    prefiltered.default_sort();
+    assert_eq!(
+        prefiltered.inner,
+        engine.search(haystack, &opts.sorted()).unwrap().inner
+    );
+    let first = engine.find_first(haystack, &opts).unwrap().unwrap();
+    assert!(first.alignment.is_some() && all.inner.contains(&first));
+    for m in engine.find_iter(haystack, &opts).unwrap() {
+        assert!(all.inner.contains(&m));
+    }
+    assert_eq!(
+        engine
+            .find_first("recieve", &opts)
+            .unwrap()
+            .unwrap()
+            .alignment,
+        Some(vec![
+            EditOp::Match {
+                pattern: 0..1,
+                text: 0..1
+            },
+            EditOp::Match {
+                pattern: 1..2,
+                text: 1..2
+            },
+            EditOp::Match {
+                pattern: 2..3,
+                text: 2..3
+            },
+            EditOp::Swap {
+                pattern: 3..5,
+                text: 3..5
+            },
+            EditOp::Match {
+                pattern: 5..6,
+                text: 5..6
+            },
+            EditOp::Match {
+                pattern: 6..7,
+                text: 6..7
+            },
+        ])
+    );
+}
+
+#[test]
+fn test_snippets() {
+    let engine = FuzzyAhoCorasickBuilder::new().build(["cat", "dog"]);
+    // The last grapheme before "x" is one cluster of three chars.
+    let haystack = "one cat two dog three four five cat e\u{301}\u{301}x";
+    let opts = SearchOptions::new()
+        .threshold(1.0)
+        .sorted()
+        .non_overlapping();
+    let matches = engine.search(haystack, &opts).unwrap();
+    let bracket = |m: &FuzzyMatch<'_>| format!("[{}]", m.text);
+    let render = |snippet_opts: SnippetOptions<'_>| {
+        matches
+            .snippets(&snippet_opts.markup(Markup::Custom(&bracket)))
+            .into_iter()
+            .map(|s| (s.start, s.end, s.text, s.hits))
+            .collect::<Vec<_>>()
+    };
+
+    let near = SnippetOptions::new().context(2).ellipsis("...");
+    assert_eq!(
+        render(near),
+        [
+            (2, 9, "...e [cat] t...".to_string(), 1),
+            (10, 17, "...o [dog] t...".to_string(), 1),
+            (30, 41, "...e [cat] e\u{301}\u{301}...".to_string(), 1),
+        ]
+    );
+    // One grapheme between the first two windows: merged with the gap shown.
+    assert_eq!(
+        render(near.merge_gap(1))[0],
+        (2, 17, "...e [cat] two [dog] t...".to_string(), 2)
+    );
+    // Windows reaching both ends of the haystack take no ellipsis.
+    assert_eq!(
+        render(near.context(40)),
+        [(0, haystack.len(), matches.replace(|m| Some(bracket(m))), 3)]
+    );
+    assert_eq!(
+        matches.highlight(Markup::Custom(&bracket)),
+        matches.replace(|m| Some(bracket(m)))
+    );
+
+    // HTML escapes the haystack text, hits included, but not the ellipsis.
+    let engine = FuzzyAhoCorasickBuilder::new()
+        .fuzzy(FuzzyLimits::new().edits(1))
+        .build(["a&b"]);
+    let matches = engine
+        .search(
+            "x<y> a&c \"q\" 'r'",
+            &SearchOptions::new()
+                .threshold(0.6)
+                .sorted()
+                .non_overlapping(),
+        )
+        .unwrap();
+    assert_eq!(
+        matches.highlight(Markup::Html),
+        "x&lt;y&gt; <mark>a&amp;c</mark> &quot;q&quot; &#39;r&#39;"
+    );
+    let snippets = matches.snippets(&SnippetOptions::new().context(1).markup(Markup::Html));
+    assert_eq!(snippets[0].text, "… <mark>a&amp;c</mark> …");
+    assert_eq!(
+        matches.highlight(Markup::Ansi),
+        "x<y> \x1b[1;31ma&c\x1b[0m \"q\" 'r'"
+    );
+
+    // Overlapping, unsorted matches: taken left to right, skipping overlaps.
+    let engine = FuzzyAhoCorasickBuilder::new().build(["ab", "bc", "d"]);
+    let mut matches = engine
+        .search("abc d", &SearchOptions::new().threshold(1.0))
+        .unwrap();
+    matches.inner.sort_by_key(|m| std::cmp::Reverse(m.start));
+    let snippets = matches.snippets(&SnippetOptions::new().context(0));
+    assert_eq!(
+        snippets.iter().map(|s| s.text.as_str()).collect::<Vec<_>>(),
+        ["\x1b[1;31mab\x1b[0m…", "…\x1b[1;31md\x1b[0m"]
+    );
+
+    matches.inner.clear();
+    assert!(matches.snippets(&SnippetOptions::new()).is_empty());
+    assert_eq!(matches.highlight(Markup::Html), "abc d");
+}
+
+#[test]
+fn test_pattern_list() {
+    let patterns = Pattern::parse_list(
+        "# header\n\
+         \n\
+         colour\tweight=1.5\tid=3\tmeta.region=uk\r\n\
+         \\#tag\tsubstitutions=1\tswaps=1\t\n\
+         tab\\tbed\tmeta.note=a\\tb\n",
+    )
+    .unwrap();
+    assert_eq!(
+        patterns,
+        [
+            Pattern::from("colour")
+                .weight(1.5)
+                .custom_unique_id(3)
+                .metadata("region", "uk"),
+            Pattern::from("#tag").fuzzy(FuzzyLimits::new().substitutions(1).swaps(1)),
+            Pattern::from("tab\tbed").metadata("note", "a\tb"),
+        ]
+    );
+
+    // Whitespace-only lines are blank, not patterns that would match almost anywhere.
+    assert_eq!(
+        Pattern::parse_list("  \n\t\n#x\n\\#y\n").unwrap(),
+        [Pattern::from("#y")]
+    );
+
+    let error = |text: &str| Pattern::parse_list(text).unwrap_err();
+    assert_eq!(error("ok\n\tweight=2").line, 2);
+    assert_eq!(error("\tweight=2").kind, PatternListErrorKind::EmptyPattern);
+    assert_eq!(
+        error("a\tweight").kind,
+        PatternListErrorKind::MissingValue {
+            field: "weight".into()
+        }
+    );
+    assert_eq!(
+        error("a\tcolor=red").kind,
+        PatternListErrorKind::UnknownOption {
+            key: "color".into()
+        }
+    );
+    assert_eq!(
+        error("a\tedits=1\tedits=2").kind,
+        PatternListErrorKind::DuplicateOption {
+            key: "edits".into()
+        }
+    );
+    assert_eq!(
+        error("a\tweight=-1").kind,
+        PatternListErrorKind::InvalidValue {
+            key: "weight".into(),
+            value: "-1".into()
+        }
+    );
+    assert_eq!(
+        error("a\tedits=300").kind,
+        PatternListErrorKind::InvalidValue {
+            key: "edits".into(),
+            value: "300".into()
+        }
+    );
+    assert_eq!(
+        error("a\\x").kind,
+        PatternListErrorKind::InvalidEscape {
+            escape: "\\x".into()
+        }
+    );
+    assert_eq!(
+        error("ok\nbad\tid=x").to_string(),
+        "line 2: invalid value \"x\" for option \"id\""
+    );
+}
+
+#[test]
+fn test_payloads() {
+    let engine = FuzzyAhoCorasickBuilder::new()
+        .fuzzy(FuzzyLimits::new().edits(1))
+        .case_insensitive(true)
+        .build_with_payloads([
+            (Pattern::from("colour").weight(1.2), "color"),
+            (Pattern::from("grey"), "gray"),
+        ]);
+    assert_eq!(engine.payloads(), ["color", "gray"]);
+
+    let haystack = "Grey colours, greys";
+    let opts = SearchOptions::new()
+        .threshold(0.8)
+        .sorted()
+        .non_overlapping();
+    let found: Vec<_> = engine
+        .search(haystack, &opts)
+        .unwrap()
+        .into_iter()
+        .map(|(m, canonical)| (m.start, *canonical))
+        .collect();
+    let plain: Vec<_> = engine
+        .engine()
+        .search(haystack, &opts)
+        .unwrap()
+        .iter()
+        .map(|m| (m.start, *engine.payload(m.pattern_index)))
+        .collect();
+    assert_eq!(found, plain);
+    assert!(found.contains(&(0, "gray")) && found.contains(&(5, "color")));
+
+    let mut streamed = Vec::new();
+    engine
+        .search_stream(haystack.as_bytes(), 0.8, |m, canonical| {
+            streamed.push((m.start, m.text, *canonical));
+        })
+        .unwrap();
+    let mut parallel = Vec::new();
+    engine
+        .search_stream_parallel(haystack.as_bytes(), 0.8, 2, |m, canonical| {
+            parallel.push((m.start, m.text, *canonical));
+        })
+        .unwrap();
+    parallel.sort_unstable();
+    streamed.sort_unstable();
+    assert_eq!(streamed, parallel);
+    assert!(
+        streamed
+            .iter()
+            .all(|(_, text, canonical)| (*canonical == "gray")
+                == text.to_lowercase().starts_with('g'))
+    );
+
+    let (plain_engine, payloads) = engine.into_parts();
+    assert_eq!(plain_engine.patterns().len(), payloads.len());
+}
+
+#[test]
+fn test_owned_matches() {
+    let engine = FuzzyAhoCorasickBuilder::new()
+        .fuzzy(FuzzyLimits::new().edits(1))
+        .build(["hello", "world"]);
+    let opts = SearchOptions::new()
+        .threshold(0.8)
+        .sorted()
+        .non_overlapping()
+        .alignment(true);
+    let search = |haystack: String| engine.search(&haystack, &opts).unwrap().into_owned();
+
+    // The haystack is dropped before the matches are used, and they cross a thread.
+    let owned = std::thread::scope(|scope| {
+        scope
+            .spawn(|| search("helo, wrld!".to_owned()))
+            .join()
+            .unwrap()
+    });
+    let haystack = "helo, wrld!";
+    let expected = engine.search(haystack, &opts).unwrap();
+    assert_eq!(owned.len(), 2);
+    assert_eq!(owned[0].text, "helo");
+    assert!(owned.iter().all(|m| m.alignment.is_some()));
+
+    let rehydrated = FuzzyMatches::from_owned(&engine, haystack, &owned).unwrap();
+    assert_eq!(rehydrated.inner, expected.inner);
+    assert_eq!(
+        rehydrated.segment_text(),
+        engine.segment_text(haystack, &opts).unwrap()
+    );
+    assert_eq!(
+        owned[1].to_match(&engine, haystack).unwrap(),
+        expected.inner[1]
+    );
+    assert_eq!(expected.inner[0].clone().into_owned(), owned[0]);
+
+    // A haystack or engine the matches don't belong to is rejected.
+    assert!(owned[0].to_match(&engine, "hi").is_none());
+    assert!(owned[0].to_match(&engine, "hola, wrld!").is_none());
+    assert!(FuzzyMatches::from_owned(&engine, "helo, word!", &owned).is_none());
+    let small = FuzzyAhoCorasickBuilder::new().build(["hello"]);
+    assert!(owned[1].to_match(&small, haystack).is_none());
+}
+
+#[test]
+fn test_searcher() {
+    let engine = FuzzyAhoCorasickBuilder::new()
+        .fuzzy(FuzzyLimits::new().edits(1))
+        .case_insensitive(true)
+        .build(["hello", "world", "café"]);
+    let haystacks = [
+        "helo wrld, and a much longer line that grows every buffer: hallo, wordl, cafe",
+        "",
+        "HELLO",
+        "Ça va? un CAFÉ, hellö",
+        "xx",
+        "wrld hello",
+    ];
+    let option_sets = [
+        SearchOptions::new()
+            .threshold(0.7)
+            .sorted()
+            .non_overlapping(),
+        SearchOptions::new().threshold(0.7).sorted().alignment(true),
+        SearchOptions::new()
+            .threshold(0.7)
+            .sorted()
+            .boundary(Boundary::Word),
+        SearchOptions::new()
+            .threshold(0.7)
+            .sorted()
+            .anchored(Anchored::Start),
+    ];
+    // One searcher across every haystack and option set, in both orders: reused buffers must not
+    // leak from one search into the next.
+    let mut searcher = engine.searcher();
+    for _ in 0..2 {
+        for opts in &option_sets {
+            for haystack in haystacks.iter().chain(haystacks.iter().rev()) {
+                let expected = engine.search(haystack, opts).unwrap();
+                assert_eq!(
+                    searcher.search(haystack, opts).unwrap().inner,
+                    expected.inner
+                );
+                assert_eq!(
+                    searcher.is_match(haystack, opts).unwrap(),
+                    engine.is_match(haystack, opts).unwrap()
+                );
+                assert_eq!(
+                    searcher.find_first(haystack, opts).unwrap(),
+                    engine.find_first(haystack, opts).unwrap()
+                );
+                assert_eq!(
+                    searcher.segment_text(haystack, opts).unwrap(),
+                    engine.segment_text(haystack, opts).unwrap()
+                );
+                let fix = |m: &FuzzyMatch| Some(m.pattern.as_str().to_uppercase());
+                assert_eq!(
+                    searcher.replace(haystack, opts, fix).unwrap(),
+                    engine.replace(haystack, opts, fix).unwrap()
+                );
+                assert!(
+                    searcher
+                        .split(haystack, opts)
+                        .unwrap()
+                        .eq(engine.split(haystack, opts).unwrap())
+                );
+            }
+        }
+    }
+    assert_eq!(
+        searcher.lookup("helo", 2).unwrap().inner,
+        engine.lookup("helo", 2).unwrap().inner
+    );
+}
+
+#[test]
+fn test_search_batch() {
+    fn unwrap(
+        results: Vec<Result<FuzzyMatches<'_>, crate::SearchError>>,
+    ) -> Vec<Vec<FuzzyMatch<'_>>> {
+        results.into_iter().map(|r| r.unwrap().inner).collect()
+    }
+
+    let engine = FuzzyAhoCorasickBuilder::new()
+        .fuzzy(FuzzyLimits::new().edits(1))
+        .case_insensitive(true)
+        .build(["smith", "jones", "müller"]);
+    let opts = SearchOptions::new()
+        .threshold(0.7)
+        .sorted()
+        .non_overlapping();
+    let records: Vec<String> = (0..1000)
+        .map(|i| match i % 4 {
+            0 => format!("record {i}: john smyth"),
+            1 => format!("record {i}: JNOES and Smith"),
+            2 => format!("record {i}: Hans Muller"),
+            _ => String::new(),
+        })
+        .collect();
+    let expected: Vec<Vec<FuzzyMatch>> = records
+        .iter()
+        .map(|record| engine.search(record, &opts).unwrap().inner)
+        .collect();
+
+    assert_eq!(unwrap(engine.search_batch(&records, &opts)), expected);
+    // One thread, a few, and more threads than there are records to split.
+    for threads in [0, 1, 3, 8] {
+        assert_eq!(
+            unwrap(engine.search_batch_parallel(&records, &opts, threads)),
+            expected
+        );
+        assert_eq!(
+            unwrap(engine.search_batch_parallel(&records[..5], &opts, threads)),
+            expected[..5]
+        );
+    }
+    assert!(engine.search_batch::<&str>(&[], &opts).is_empty());
+    assert!(
+        engine
+            .search_batch_parallel::<&str>(&[], &opts, 4)
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_search_parallel() {
+    let engine = FuzzyAhoCorasickBuilder::new()
+        .fuzzy(FuzzyLimits::new().edits(1))
+        .case_insensitive(true)
+        .mapping("ae", "æ")
+        .phonetic(crate::PhoneticAlgorithm::Soundex)
+        .build(["timeout", "error", "cæsar", "Smith"]);
+    // Long enough to be cut into many windows, with matches and words straddling every offset.
+    let line = "GET /api/Cæsar ok; POST eror: timout for Smyth, xtimeoutx caesar ÉRROR\n";
+    let haystack: String = (0..500)
+        .flat_map(|i| ["x".repeat(i % 7), line.to_owned()])
+        .collect();
+    assert!(haystack.len() > 2 * 16 * 1024);
+
+    let option_sets = [
+        SearchOptions::new().threshold(0.7),
+        SearchOptions::new()
+            .threshold(0.7)
+            .sorted()
+            .non_overlapping(),
+        SearchOptions::new()
+            .threshold(0.7)
+            .sorted()
+            .boundary(Boundary::Word)
+            .alignment(true),
+        SearchOptions::new()
+            .threshold(0.7)
+            .match_kind(MatchKind::LeftmostLongest),
+    ];
+    for opts in &option_sets {
+        let mut expected = engine.search(&haystack, opts).unwrap();
+        for threads in [0, 4] {
+            let mut found = engine.search_parallel(&haystack, opts, threads).unwrap();
+            if opts.order == Order::Unsorted && opts.overlap == Overlap::Keep {
+                // Raw matches come in no particular order.
+                found.default_sort();
+                expected.default_sort();
+            }
+            assert_eq!(found.inner, expected.inner);
+        }
+    }
+    // Greedy overlap resolution of unsorted matches depends on their raw order, which differs
+    // between the two; the result must still be non-overlapping.
+    let unsorted = SearchOptions::new().threshold(0.7).non_overlapping();
+    let mut found = engine.search_parallel(&haystack, &unsorted, 4).unwrap();
+    assert!(!found.is_empty());
+    found.inner.sort_by_key(|m| m.start);
+    assert!(found.inner.windows(2).all(|w| w[0].end <= w[1].start));
+
+    let opts = SearchOptions::new().threshold(0.7).sorted();
+    assert_eq!(
+        engine.search_parallel("eror", &opts, 4).unwrap().inner,
+        engine.search("eror", &opts).unwrap().inner
+    );
+    // An anchored search isn't split.
+    let anchored = opts.anchored(Anchored::End);
+    let tail = &haystack[haystack.len() - 100..];
+    assert_eq!(
+        engine.search_parallel(tail, &anchored, 4).unwrap().inner,
+        engine.search(tail, &anchored).unwrap().inner
+    );
+    assert!(engine.search_parallel("", &opts, 4).unwrap().is_empty());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_round_trip() {
+    use crate::{Boundary, OwnedFuzzyMatch, StreamMatch};
+
+    fn round_trip<T: serde::Serialize + serde::de::DeserializeOwned>(value: &T) -> T {
+        serde_json::from_str(&serde_json::to_string(value).unwrap()).unwrap()
+    }
+
+    let patterns = vec![
+        Pattern::from("colour")
+            .weight(1.5)
+            .custom_unique_id(3)
+            .fuzzy(FuzzyLimits::new().substitutions(1))
+            .metadata("region", "uk"),
+        Pattern::from("e\u{301}cole"),
+    ];
+    assert_eq!(round_trip(&patterns), patterns);
+    // Only the text is required; `grapheme_len` is recomputed and never written.
+    let minimal: Pattern = serde_json::from_str(r#"{"pattern":"e\u0301cole"}"#).unwrap();
+    assert_eq!(minimal, patterns[1]);
+    assert_eq!(
+        serde_json::to_string(&minimal).unwrap(),
+        "{\"pattern\":\"e\u{301}cole\",\"weight\":1.0}"
+    );
+    let limited: Pattern = serde_json::from_str(r#"{"pattern":"x","limits":{"edits":2}}"#).unwrap();
+    assert_eq!(
+        limited,
+        Pattern::from("x").fuzzy(FuzzyLimits::new().edits(2))
+    );
+
+    let limits = FuzzyLimits::new().insertions(1).swaps(2);
+    assert_eq!(round_trip(&limits), limits);
+    let penalties = round_trip(&FuzzyPenalties::default().swap(0.3));
+    assert!((penalties.swap - 0.3).abs() < f32::EPSILON);
+    let partial: FuzzyPenalties = serde_json::from_str(r#"{"deletion":0.5}"#).unwrap();
+    assert!((partial.insertion - FuzzyPenalties::default().insertion).abs() < f32::EPSILON);
+
+    let opts = SearchOptions::new()
+        .threshold(0.7)
+        .greedy()
+        .non_overlapping_optimal()
+        .boundary(Boundary::Word)
+        .anchored(Anchored::Start)
+        .match_kind(MatchKind::LeftmostLongest)
+        .alignment(true);
+    assert_eq!(round_trip(&opts), opts);
+    let partial: SearchOptions =
+        serde_json::from_str(r#"{"threshold":0.8,"order":"Default"}"#).unwrap();
+    assert_eq!(partial, SearchOptions::new().threshold(0.8).sorted());
+    assert_eq!(
+        round_trip(&Order::CoverageWeighted),
+        Order::CoverageWeighted
+    );
+    assert_eq!(
+        round_trip(&Overlap::NonOverlappingUnique),
+        Overlap::NonOverlappingUnique
+    );
+
+    // Results: a rebuilt engine gives the same owned matches, which survive a round trip.
+    let engine = FuzzyAhoCorasickBuilder::new()
+        .fuzzy(FuzzyLimits::new().edits(1))
+        .build(patterns.clone());
+    let rebuilt = FuzzyAhoCorasickBuilder::new()
+        .fuzzy(FuzzyLimits::new().edits(1))
+        .build(round_trip(&patterns));
+    let haystack = "the colur of the ecole";
+    let search = SearchOptions::new().threshold(0.5).sorted().alignment(true);
+    let owned: Vec<OwnedFuzzyMatch> = engine
+        .search(haystack, &search)
+        .unwrap()
+        .iter()
+        .map(OwnedFuzzyMatch::from)
+        .collect();
+    let rebuilt_owned: Vec<OwnedFuzzyMatch> = rebuilt
+        .search(haystack, &search)
+        .unwrap()
+        .iter()
+        .map(OwnedFuzzyMatch::from)
+        .collect();
+    assert!(!owned.is_empty() && owned[0].alignment.is_some());
+    assert_eq!(owned, rebuilt_owned);
+    assert_eq!(round_trip(&owned), owned);
+
+    let mut streamed: Vec<StreamMatch> = Vec::new();
+    engine
+        .search_stream(haystack.as_bytes(), 0.5, |m| streamed.push(m))
+        .unwrap();
+    assert!(!streamed.is_empty());
+    assert_eq!(round_trip(&streamed), streamed);
+}
+
 #[test]
 fn test_deterministic_search() {
     // Build a larger engine with multiple patterns and edit budgets
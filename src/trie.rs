@@ -0,0 +1,500 @@
+//! Read-only access to the automaton's trie, abstracted over its two storage forms so the search hot
+//! loop can be monomorphized over either: the owned [`Node`] graph the builder produces, and the flat
+//! record layout of a serialized automaton (see `serialize`), searched in place from its bytes.
+use crate::cost::CustomCost;
+use crate::serialize::{
+    EDGE_RECORD, MAPPING_GRAPHEME_RECORD, MAPPING_INDEX_RECORD, MAPPING_RECORD, NO_PATTERN,
+    NODE_RECORD, field_f32, field_u32,
+};
+use crate::structs::{Edge, FxHashMap, MappingTransition, Node};
+use crate::{FuzzyAhoCorasick, PatternIndex};
+use std::ops::Range;
+use std::sync::Arc;
+
+/// One trie node, as seen by the search.
+pub(crate) trait TrieNode: Copy {
+    /// Outgoing edges, in the order the search explores them.
+    fn edges(self) -> impl Iterator<Item = Edge>;
+    /// Indices of the patterns that end at this node.
+    fn output(self) -> impl Iterator<Item = u32>;
+    /// Whether any pattern ends at this node.
+    fn has_output(self) -> bool;
+    /// See [`Node::prune_len`].
+    fn prune_len(self) -> f32;
+    /// See [`Node::prune_len_over_weight`].
+    fn prune_len_over_weight(self) -> f32;
+    /// The first pattern whose path passes through this node (the source of per-node limits).
+    fn pattern_index(self) -> Option<PatternIndex>;
+
+    /// Look up the target node for an exact transition on `grapheme`.
+    ///
+    /// Fast path: when the grapheme is a single byte (ASCII char), a linear scan of the flat
+    /// `edges` list suffices — a single-byte grapheme can only match a single-char edge, and
+    /// `first_char` then identifies it exactly. This avoids hashing the `transitions` map (the
+    /// hottest lookup on the search path) for the overwhelmingly common ASCII case. Multi-byte
+    /// graphemes fall back to a full comparison.
+    fn find_transition(self, grapheme: &str) -> Option<u32>;
+
+    /// Score substituting the grapheme of the `index`-th edge (in [`edges`](Self::edges) order) with
+    /// the haystack grapheme `haystack`, whose first `char` is `ch`, under a custom model.
+    fn edge_similarity(self, index: usize, cost: &CustomCost, haystack: &str, ch: char) -> f32;
+
+    /// Whether any outgoing single-ASCII-byte edge starts with `ch`. Used by the push-time dead-end
+    /// filter in the deletion/insertion scans. A linear scan of the node's (few) edges: nodes are
+    /// overwhelmingly low-degree, and a per-node cached bitmap costs 16 bytes/node while a side-map
+    /// lookup is slower than the scan on this hot path (both measured).
+    #[inline]
+    fn has_matching_edge_char(self, ch: char) -> bool {
+        self.edges()
+            .any(|edge| edge.first_char == ch && edge.is_single_byte())
+    }
+
+    /// Bitmap of this node's single-ASCII-byte edge chars: bit `i` set iff an edge has
+    /// `first_char == i` with `i < 128`. Recomputed on demand from `edges` — used only by the
+    /// once-per-search window-skip pre-scan over the root and its children, so it isn't worth
+    /// caching 16 bytes on every node.
+    #[inline]
+    fn single_char_edge_bits(self) -> u128 {
+        let mut bits = 0u128;
+        for edge in self.edges() {
+            if edge.is_single_byte() {
+                let idx = edge.first_char as u32;
+                if idx < 128 {
+                    bits |= 1u128 << idx;
+                }
+            }
+        }
+        bits
+    }
+
+    /// Like `find_transition` but takes a `char` directly, skipping the `&str` creation,
+    /// `as_bytes()`, and byte-length check. Correct only for single-byte graphemes
+    /// (guaranteed by the caller via `GraphemeStorage::gs_find_transition`).
+    #[inline]
+    fn find_transition_char(self, ch: char) -> Option<u32> {
+        self.edges()
+            .find(|edge| edge.first_char == ch && edge.is_single_byte())
+            .map(Edge::next)
+    }
+
+    /// Like `find_transition_char` but skips the single-byte check. Only correct when the caller
+    /// guarantees no multi-char mapping edges exist (i.e., `MAPPINGS == false`), in which case
+    /// every edge is a single ASCII byte.
+    #[inline]
+    fn find_transition_char_no_mappings(self, ch: char) -> Option<u32> {
+        self.edges()
+            .find(|edge| edge.first_char == ch)
+            .map(Edge::next)
+    }
+}
+
+/// One precomputed multi-character mapping transition (see [`MappingTransition`]).
+pub(crate) trait MappingView: Copy {
+    /// Node reached after applying the mapping.
+    fn next(self) -> u32;
+    /// Penalty added when the mapping is applied.
+    fn penalty(self) -> f32;
+    /// Number of haystack graphemes the mapping consumes.
+    fn haystack_len(self) -> usize;
+    /// Whether the mapping's `k`-th haystack grapheme is `grapheme`.
+    fn haystack_eq(self, k: usize, grapheme: &str) -> bool;
+}
+
+/// The whole trie: nodes by index, plus the mapping transitions available from each node.
+pub(crate) trait TrieView: Copy {
+    type Node: TrieNode;
+    type Mapping: MappingView;
+    /// The node at `index` (the root is `0`).
+    fn node(self, index: u32) -> Self::Node;
+    /// The mapping transitions available from `node` (empty for most nodes).
+    fn mappings(self, node: u32) -> impl Iterator<Item = Self::Mapping>;
+}
+
+impl TrieNode for &Node {
+    #[inline]
+    fn edges(self) -> impl Iterator<Item = Edge> {
+        self.edges.iter().copied()
+    }
+    #[inline]
+    fn output(self) -> impl Iterator<Item = u32> {
+        self.output.iter().copied()
+    }
+    #[inline]
+    fn has_output(self) -> bool {
+        !self.output.is_empty()
+    }
+    #[inline]
+    fn prune_len(self) -> f32 {
+        self.prune_len
+    }
+    #[inline]
+    fn prune_len_over_weight(self) -> f32 {
+        self.prune_len_over_weight
+    }
+    #[inline]
+    fn pattern_index(self) -> Option<PatternIndex> {
+        self.pattern_index
+    }
+    #[inline]
+    fn find_transition(self, grapheme: &str) -> Option<u32> {
+        let bytes = grapheme.as_bytes();
+        if bytes.len() == 1 {
+            return self.find_transition_char(bytes[0] as char);
+        }
+        self.transitions.get(grapheme).copied()
+    }
+    #[inline]
+    fn edge_similarity(self, index: usize, cost: &CustomCost, haystack: &str, ch: char) -> f32 {
+        cost.score(
+            &self.edge_graphemes[index],
+            self.edges[index].first_char,
+            haystack,
+            ch,
+        )
+    }
+}
+
+impl MappingView for &MappingTransition {
+    #[inline]
+    fn next(self) -> u32 {
+        self.next
+    }
+    #[inline]
+    fn penalty(self) -> f32 {
+        self.penalty
+    }
+    #[inline]
+    fn haystack_len(self) -> usize {
+        self.haystack.len()
+    }
+    #[inline]
+    fn haystack_eq(self, k: usize, grapheme: &str) -> bool {
+        self.haystack[k].as_ref() == grapheme
+    }
+}
+
+/// The builder's owned trie.
+#[derive(Clone, Copy)]
+pub(crate) struct OwnedTrie<'a> {
+    pub(crate) nodes: &'a [Node],
+    pub(crate) mappings: &'a FxHashMap<u32, Box<[MappingTransition]>>,
+}
+
+impl<'a> TrieView for OwnedTrie<'a> {
+    type Node = &'a Node;
+    type Mapping = &'a MappingTransition;
+
+    #[inline]
+    fn node(self, index: u32) -> &'a Node {
+        &self.nodes[index as usize]
+    }
+    #[inline]
+    fn mappings(self, node: u32) -> impl Iterator<Item = &'a MappingTransition> {
+        self.mappings.get(&node).into_iter().flat_map(|m| m.iter())
+    }
+}
+
+/// A serialized trie read in place: the flat record sections of the format, borrowed straight from
+/// the serialized bytes. Every accessor is plain bounds-checked slicing and `from_le_bytes`, so a
+/// damaged buffer can at worst panic, never read out of bounds; loading validates every
+/// cross-reference up front so a buffer that loaded successfully doesn't.
+#[derive(Clone, Copy)]
+pub(crate) struct FlatTrie<'a> {
+    pub(crate) nodes: &'a [u8],
+    pub(crate) edges: &'a [u8],
+    pub(crate) outputs: &'a [u8],
+    pub(crate) mapping_index: &'a [u8],
+    pub(crate) mappings: &'a [u8],
+    pub(crate) mapping_graphemes: &'a [u8],
+    pub(crate) pool: &'a [u8],
+}
+
+/// Slice `count` fixed-size records starting at record `start` out of `section`.
+#[inline]
+fn records(section: &[u8], record: usize, start: u32, count: u32) -> &[u8] {
+    &section[start as usize * record..(start as usize + count as usize) * record]
+}
+
+impl<'a> FlatTrie<'a> {
+    pub(crate) fn node_count(self) -> usize {
+        self.nodes.len() / NODE_RECORD
+    }
+    pub(crate) fn node_record(self, i: usize) -> &'a [u8] {
+        &self.nodes[i * NODE_RECORD..(i + 1) * NODE_RECORD]
+    }
+    pub(crate) fn edge_records(self) -> impl Iterator<Item = &'a [u8]> {
+        self.edges.chunks_exact(EDGE_RECORD)
+    }
+    pub(crate) fn output_records(self) -> impl Iterator<Item = u32> {
+        self.outputs.chunks_exact(4).map(|o| field_u32(o, 0))
+    }
+    pub(crate) fn mapping_index_records(self) -> impl Iterator<Item = &'a [u8]> {
+        self.mapping_index.chunks_exact(MAPPING_INDEX_RECORD)
+    }
+    pub(crate) fn mapping_records(self) -> impl Iterator<Item = &'a [u8]> {
+        self.mappings.chunks_exact(MAPPING_RECORD)
+    }
+    pub(crate) fn mapping_grapheme_records(self) -> impl Iterator<Item = &'a [u8]> {
+        self.mapping_graphemes.chunks_exact(MAPPING_GRAPHEME_RECORD)
+    }
+    /// The mapping transition records from `start`, `count` long.
+    pub(crate) fn mapping_range(self, start: u32, count: u32) -> &'a [u8] {
+        records(self.mappings, MAPPING_RECORD, start, count)
+    }
+    /// The mapping grapheme records from `start`, `count` long.
+    pub(crate) fn mapping_grapheme_range(self, start: u32, count: u32) -> &'a [u8] {
+        records(
+            self.mapping_graphemes,
+            MAPPING_GRAPHEME_RECORD,
+            start,
+            count,
+        )
+    }
+}
+
+/// A node record of a [`FlatTrie`], with its edge and output records pre-sliced.
+#[derive(Clone, Copy)]
+pub(crate) struct FlatNode<'a> {
+    record: &'a [u8],
+    edges: &'a [u8],
+    outputs: &'a [u8],
+    pool: &'a [u8],
+}
+
+impl TrieNode for FlatNode<'_> {
+    #[inline]
+    fn edges(self) -> impl Iterator<Item = Edge> {
+        self.edges.chunks_exact(EDGE_RECORD).map(|e| {
+            Edge::from_packed(
+                char::from_u32(field_u32(e, 0)).unwrap_or('\0'),
+                field_u32(e, 1),
+            )
+        })
+    }
+    #[inline]
+    fn output(self) -> impl Iterator<Item = u32> {
+        self.outputs.chunks_exact(4).map(|o| field_u32(o, 0))
+    }
+    #[inline]
+    fn has_output(self) -> bool {
+        !self.outputs.is_empty()
+    }
+    #[inline]
+    fn prune_len(self) -> f32 {
+        field_f32(self.record, 4)
+    }
+    #[inline]
+    fn prune_len_over_weight(self) -> f32 {
+        field_f32(self.record, 5)
+    }
+    #[inline]
+    fn pattern_index(self) -> Option<PatternIndex> {
+        let first = field_u32(self.record, 8);
+        (first != NO_PATTERN).then_some(first as usize)
+    }
+    // The char scans compare the raw `first_char` / `packed_next` words instead of decoding each
+    // record into an `Edge`.
+    #[inline]
+    fn has_matching_edge_char(self, ch: char) -> bool {
+        self.edges
+            .chunks_exact(EDGE_RECORD)
+            .any(|e| field_u32(e, 0) == ch as u32 && field_u32(e, 1) & !Edge::NEXT_MASK != 0)
+    }
+    #[inline]
+    fn find_transition_char(self, ch: char) -> Option<u32> {
+        self.edges
+            .chunks_exact(EDGE_RECORD)
+            .find(|e| field_u32(e, 0) == ch as u32 && field_u32(e, 1) & !Edge::NEXT_MASK != 0)
+            .map(|e| field_u32(e, 1) & Edge::NEXT_MASK)
+    }
+    #[inline]
+    fn find_transition_char_no_mappings(self, ch: char) -> Option<u32> {
+        self.edges
+            .chunks_exact(EDGE_RECORD)
+            .find(|e| field_u32(e, 0) == ch as u32)
+            .map(|e| field_u32(e, 1) & Edge::NEXT_MASK)
+    }
+    #[inline]
+    fn find_transition(self, grapheme: &str) -> Option<u32> {
+        let bytes = grapheme.as_bytes();
+        if bytes.len() == 1 {
+            return self.find_transition_char(bytes[0] as char);
+        }
+        // No hash map here: compare the grapheme against each edge's pooled text.
+        self.edges
+            .chunks_exact(EDGE_RECORD)
+            .find(|e| {
+                let start = field_u32(e, 2) as usize;
+                self.pool.get(start..start + field_u32(e, 3) as usize) == Some(bytes)
+            })
+            .map(|e| field_u32(e, 1) & Edge::NEXT_MASK)
+    }
+    #[inline]
+    fn edge_similarity(self, index: usize, cost: &CustomCost, haystack: &str, ch: char) -> f32 {
+        let e = &self.edges[index * EDGE_RECORD..(index + 1) * EDGE_RECORD];
+        let start = field_u32(e, 2) as usize;
+        // The pool is validated UTF-8 with every range on a char boundary.
+        let grapheme = self
+            .pool
+            .get(start..start + field_u32(e, 3) as usize)
+            .and_then(|g| std::str::from_utf8(g).ok())
+            .unwrap_or_default();
+        cost.score(
+            grapheme,
+            char::from_u32(field_u32(e, 0)).unwrap_or('\0'),
+            haystack,
+            ch,
+        )
+    }
+}
+
+/// A mapping transition record of a [`FlatTrie`], with its haystack grapheme records pre-sliced.
+#[derive(Clone, Copy)]
+pub(crate) struct FlatMapping<'a> {
+    record: &'a [u8],
+    graphemes: &'a [u8],
+    pool: &'a [u8],
+}
+
+impl MappingView for FlatMapping<'_> {
+    #[inline]
+    fn next(self) -> u32 {
+        field_u32(self.record, 0)
+    }
+    #[inline]
+    fn penalty(self) -> f32 {
+        field_f32(self.record, 1)
+    }
+    #[inline]
+    fn haystack_len(self) -> usize {
+        self.graphemes.len() / MAPPING_GRAPHEME_RECORD
+    }
+    #[inline]
+    fn haystack_eq(self, k: usize, grapheme: &str) -> bool {
+        let g = &self.graphemes[k * MAPPING_GRAPHEME_RECORD..];
+        let start = field_u32(g, 0) as usize;
+        self.pool.get(start..start + field_u32(g, 1) as usize) == Some(grapheme.as_bytes())
+    }
+}
+
+impl<'a> TrieView for FlatTrie<'a> {
+    type Node = FlatNode<'a>;
+    type Mapping = FlatMapping<'a>;
+
+    #[inline]
+    fn node(self, index: u32) -> FlatNode<'a> {
+        let record = self.node_record(index as usize);
+        FlatNode {
+            record,
+            edges: records(
+                self.edges,
+                EDGE_RECORD,
+                field_u32(record, 0),
+                field_u32(record, 1),
+            ),
+            outputs: records(self.outputs, 4, field_u32(record, 2), field_u32(record, 3)),
+            pool: self.pool,
+        }
+    }
+
+    #[inline]
+    fn mappings(self, node: u32) -> impl Iterator<Item = FlatMapping<'a>> {
+        // The index is sorted by node: binary-search it for this node's entry.
+        let entry = |i: usize| &self.mapping_index[i * MAPPING_INDEX_RECORD..];
+        let (mut lo, mut hi) = (0, self.mapping_index.len() / MAPPING_INDEX_RECORD);
+        let mut transitions: &[u8] = &[];
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            let e = entry(mid);
+            match field_u32(e, 0).cmp(&node) {
+                std::cmp::Ordering::Less => lo = mid + 1,
+                std::cmp::Ordering::Greater => hi = mid,
+                std::cmp::Ordering::Equal => {
+                    transitions = self.mapping_range(field_u32(e, 1), field_u32(e, 2));
+                    break;
+                }
+            }
+        }
+        transitions
+            .chunks_exact(MAPPING_RECORD)
+            .map(move |record| FlatMapping {
+                record,
+                graphemes: self.mapping_grapheme_range(field_u32(record, 2), field_u32(record, 3)),
+                pool: self.pool,
+            })
+    }
+}
+
+/// Byte ranges of the flat sections within a serialized buffer, recorded at load so the sections
+/// can be re-borrowed from the shared bytes on every search without re-parsing.
+#[derive(Clone, Debug)]
+pub(crate) struct FlatLayout {
+    pub(crate) nodes: Range<usize>,
+    pub(crate) edges: Range<usize>,
+    pub(crate) outputs: Range<usize>,
+    pub(crate) mapping_index: Range<usize>,
+    pub(crate) mappings: Range<usize>,
+    pub(crate) mapping_graphemes: Range<usize>,
+    pub(crate) pool: Range<usize>,
+}
+
+impl FlatLayout {
+    /// Borrow the sections out of `bytes`, the buffer this layout was parsed from.
+    #[inline]
+    pub(crate) fn resolve<'a>(&self, bytes: &'a [u8]) -> FlatTrie<'a> {
+        FlatTrie {
+            nodes: &bytes[self.nodes.clone()],
+            edges: &bytes[self.edges.clone()],
+            outputs: &bytes[self.outputs.clone()],
+            mapping_index: &bytes[self.mapping_index.clone()],
+            mappings: &bytes[self.mappings.clone()],
+            mapping_graphemes: &bytes[self.mapping_graphemes.clone()],
+            pool: &bytes[self.pool.clone()],
+        }
+    }
+}
+
+/// Backing bytes of an engine loaded with [`FuzzyAhoCorasick::from_shared_bytes`]: the serialized
+/// buffer (an mmap, a `Vec<u8>`, a `&'static [u8]`, …) and where its flat sections lie.
+#[derive(Clone)]
+pub(crate) struct SharedTrie {
+    pub(crate) bytes: Arc<dyn AsRef<[u8]> + Send + Sync>,
+    /// Length of the serialized automaton at the start of `bytes` (header plus payload).
+    pub(crate) len: usize,
+    pub(crate) layout: FlatLayout,
+}
+
+impl SharedTrie {
+    #[inline]
+    pub(crate) fn view(&self) -> FlatTrie<'_> {
+        self.layout.resolve((*self.bytes).as_ref())
+    }
+}
+
+impl FuzzyAhoCorasick {
+    /// Whether any multi-character mapping transitions exist.
+    pub(crate) fn has_mappings(&self) -> bool {
+        match &self.shared {
+            Some(shared) => !shared.view().mapping_index.is_empty(),
+            None => !self.mappings.is_empty(),
+        }
+    }
+
+    /// The most haystack graphemes a single mapping transition consumes (`0` without mappings).
+    pub(crate) fn max_mapping_haystack(&self) -> usize {
+        match &self.shared {
+            Some(shared) => shared
+                .view()
+                .mapping_records()
+                .map(|m| field_u32(m, 3) as usize)
+                .max(),
+            None => self
+                .mappings
+                .values()
+                .flat_map(|m| m.iter())
+                .map(|mt| mt.haystack.len())
+                .max(),
+        }
+        .unwrap_or(0)
+    }
+}